@@ -11,6 +11,11 @@
 #![deny(missing_docs)]
 
 extern crate rustc_serialize;
+#[cfg(feature = "with-serde")]
+extern crate serde;
+#[cfg(feature = "with-serde")]
+#[macro_use]
+extern crate serde_derive;
 
 pub mod types;
 pub mod wm;
@@ -22,6 +22,10 @@
 //! types used in the struct or enum also implement these traits. That is why
 //! we automatically derive most useful traits for every struct/enum in this
 //! module.
+//!
+//! With the `with-serde` feature, serde's `Serialize` and `Deserialize` are
+//! derived as well. They produce the same JSON as the `rustc_serialize`
+//! encoding.
 
 
 use std::fmt;
@@ -39,6 +43,7 @@ pub type Window = c_ulong;
 /// Note that the origin lies in the top-left corner. The X-axis goes from
 /// left to right and the Y-axis from top to bottom.
 #[derive(Copy, Clone, RustcDecodable, RustcEncodable, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "with-serde", derive(Serialize, Deserialize))]
 pub struct Geometry {
     /// X-coordinate of the top-left corner of a window.
     pub x: c_int,
@@ -67,6 +72,7 @@ impl fmt::Display for Geometry {
 /// to track the position of a screen relative to the origin or another
 /// screen.
 #[derive(Copy, Clone, RustcDecodable, RustcEncodable, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "with-serde", derive(Serialize, Deserialize))]
 pub struct Screen {
     /// The width of the screen.
     pub width: c_uint,
@@ -100,6 +106,7 @@ impl Screen {
 /// Using a simple data type like this instead of a boolean is much clearer
 /// and will not lead to confusion.
 #[derive(Copy, Clone, RustcDecodable, RustcEncodable, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "with-serde", derive(Serialize, Deserialize))]
 pub enum FloatOrTile {
     /// Floating above the tiled windows.
     Float,
@@ -127,6 +134,7 @@ pub enum FloatOrTile {
 /// methods of the [`WindowManager`](../wm/trait.WindowManager.html) trait,
 /// and will also be useful when defining a window manager data type yourself.
 #[derive(Copy, Clone, RustcDecodable, RustcEncodable, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "with-serde", derive(Serialize, Deserialize))]
 pub struct WindowWithInfo {
     /// The window.
     pub window: Window,
@@ -174,6 +182,7 @@ impl WindowWithInfo {
 /// This data structure is passed to the backend to make sure the display
 /// server actually displays the desired window layout of the window manager.
 #[derive(Clone, RustcDecodable, RustcEncodable, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "with-serde", derive(Serialize, Deserialize))]
 pub struct WindowLayout {
     /// The focused window or `None` in which case no window is focused.
     ///
@@ -216,6 +225,7 @@ impl WindowLayout {
 /// Using a simple data type like this instead of a boolean is much clearer
 /// and will not lead to confusion.
 #[derive(Copy, Clone, RustcDecodable, RustcEncodable, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "with-serde", derive(Serialize, Deserialize))]
 pub enum PrevOrNext {
     /// Previous
     Prev,
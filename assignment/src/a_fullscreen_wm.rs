@@ -27,6 +27,7 @@ pub type WMName = FullscreenWM;
 
 /// Main struct of the window manager
 #[derive(RustcDecodable, RustcEncodable, Debug, Clone)]
+#[cfg_attr(feature = "with-serde", derive(Serialize, Deserialize))]
 pub struct FullscreenWM {
     /// A vector of windows, the first one is on the bottom, the last one is on top
     pub windows: Vec<Window>,
@@ -161,6 +162,8 @@ mod tests {
     // Import expectest names
     pub use expectest::prelude::*;
 
+    conformance_tests!(WMName: window_manager);
+
     describe! full_screen_wm {
         before_each {
             let screen: Screen = Screen {
@@ -18,6 +18,7 @@
 //! A lot of code (+ tests) were copied from assignment a
 //!
 
+use std::cmp;
 use std::os::raw::{c_int, c_uint};
 use cplwm_api::types::{Geometry, PrevOrNext, Screen, Window, WindowLayout, WindowWithInfo};
 use cplwm_api::types::PrevOrNext::*;
@@ -27,13 +28,18 @@ use cplwm_api::wm::{TilingSupport, WindowManager};
 use error::WMError;
 use error::WMError::*;
 
-use layouter::Layouter;
+use layouter::{Layouter, MasterSupport};
+
+use direction::{Direction, DirectionSupport, find_window_in_direction};
+
+use struts::{StrutSupport, Struts};
 
 /// Type alias for automated tests
 pub type WMName = TilingWM<SimpleLayouter>;
 
 /// Main struct of the window manager
 #[derive(RustcDecodable, RustcEncodable, Debug, Clone)]
+#[cfg_attr(feature = "with-serde", derive(Serialize, Deserialize))]
 pub struct TilingWM<MyLayouter: Layouter> {
     /// A vector of windows, the first one is the master window.
     pub windows: Vec<Window>,
@@ -43,41 +49,93 @@ pub struct TilingWM<MyLayouter: Layouter> {
     pub focused_index: Option<usize>,
     /// The layouter to use to tile the windows
     pub layouter: MyLayouter,
+    /// The explicitly reserved edges of the screen
+    pub struts: Struts,
+    /// The dock windows and the edges they reserve
+    pub docks: Vec<(Window, Struts)>,
 }
 
+/// The smallest fraction of the screen width the master area can take
+pub static MIN_MASTER_RATIO: f64 = 0.1;
+
+/// The largest fraction of the screen width the master area can take
+pub static MAX_MASTER_RATIO: f64 = 0.9;
+
+/// The fraction by which the master area grows or shrinks
+pub static MASTER_RATIO_STEP: f64 = 0.05;
+
 /// The main struct for a simple tiled layout without gaps
 #[derive(RustcDecodable, RustcEncodable, Debug, Clone)]
-pub struct SimpleLayouter;
+#[cfg_attr(feature = "with-serde", derive(Serialize, Deserialize))]
+pub struct SimpleLayouter {
+    /// The fraction of the screen width used by the master area
+    pub master_ratio: f64,
+    /// The number of windows in the master area
+    pub nb_masters: usize,
+}
 
 impl Layouter for SimpleLayouter {
-    fn get_master_geom(&self, screen: Screen, nb_windows: usize) -> Geometry {
-        if nb_windows > 1 {
-            // There are slaves
-            Geometry {
-                x: 0,
-                y: 0,
-                width: (screen.width / 2) as c_uint,
-                height: screen.height,
-            }
+    fn get_geom(&self, i: usize, screen: Screen, nb_windows: usize) -> Geometry {
+        let nb_masters = cmp::min(self.nb_masters, nb_windows);
+        let nb_slaves = nb_windows - nb_masters;
+
+        let master_width = if nb_slaves > 0 {
+            // There are slaves, the ratio is clamped again because it may have been decoded or set directly
+            let master_ratio = self.master_ratio.max(MIN_MASTER_RATIO).min(MAX_MASTER_RATIO);
+            (screen.width as f64 * master_ratio) as c_uint
+        } else {
+            screen.width
+        };
+
+        if i < nb_masters {
+            self.get_column_geom(i, nb_masters, 0, master_width, screen.height)
         } else {
-            screen.to_geometry()
+            self.get_column_geom(i - nb_masters, nb_slaves, master_width, screen.width - master_width, screen.height)
+        }
+    }
+
+    fn new() -> SimpleLayouter {
+        SimpleLayouter {
+            master_ratio: 0.5,
+            nb_masters: 1,
         }
     }
+}
 
-    fn get_slave_geom(&self, i: usize, screen: Screen, nb_windows: usize) -> Geometry {
-        let nn = (nb_windows - 1) as c_uint; // number of slaves
-        let ii = i as c_uint;
+impl SimpleLayouter {
+    /// Return the geometry for the i-th window of a column that starts at x, is width wide and contains nb_windows
+    ///
+    /// The window ends where the next one starts, so the remainder of the height is spread over the windows and the
+    /// column is covered exactly.
+    fn get_column_geom(&self, i: usize, nb_windows: usize, x: c_uint, width: c_uint, height: c_uint) -> Geometry {
+        let top = (height as u64 * i as u64 / nb_windows as u64) as c_uint;
+        let bottom = (height as u64 * (i + 1) as u64 / nb_windows as u64) as c_uint;
 
         Geometry {
-            x: (screen.width / 2) as c_int,
-            y: ((screen.height / nn) * ii) as c_int,
-            width: screen.width / 2,
-            height: (screen.height / nn) as c_uint,
+            x: x as c_int,
+            y: top as c_int,
+            width: width,
+            height: bottom - top,
         }
     }
+}
 
-    fn new() -> SimpleLayouter {
-        SimpleLayouter {}
+impl MasterSupport for SimpleLayouter {
+    fn get_master_ratio(&self) -> f64 {
+        self.master_ratio
+    }
+
+    fn set_master_ratio(&mut self, ratio: f64) {
+        self.master_ratio = ratio.max(MIN_MASTER_RATIO).min(MAX_MASTER_RATIO);
+    }
+
+    fn get_nb_masters(&self) -> usize {
+        self.nb_masters
+    }
+
+    fn set_nb_masters(&mut self, nb_masters: usize) {
+        // There is always at least one master window
+        self.nb_masters = cmp::max(nb_masters, 1);
     }
 }
 
@@ -91,6 +149,8 @@ impl<MyLayouter: Layouter> WindowManager for TilingWM<MyLayouter> {
             screen: screen,
             focused_index: None,
             layouter: MyLayouter::new(),
+            struts: Struts::new(),
+            docks: Vec::new(),
         }
     }
 
@@ -134,11 +194,7 @@ impl<MyLayouter: Layouter> WindowManager for TilingWM<MyLayouter> {
         } else {
             WindowLayout {
                 focused_window: self.get_focused_window(),
-                windows: self.windows
-                    .iter()
-                    .enumerate()
-                    .map(|(i, w)| (*w, self.get_geom(i)))
-                    .collect(),
+                windows: self.windows.iter().cloned().zip(self.get_geoms()).collect(),
             }
         }
     }
@@ -240,10 +296,79 @@ impl<MyLayouter: Layouter> TilingSupport for TilingWM<MyLayouter> {
     }
 }
 
+impl<MyLayouter: Layouter> StrutSupport for TilingWM<MyLayouter> {
+    fn get_struts(&self) -> Struts {
+        self.docks
+            .iter()
+            .fold(self.struts, |struts, &(_, dock_struts)| struts.max(&dock_struts))
+    }
+
+    fn set_struts(&mut self, struts: Struts) {
+        self.struts = struts;
+    }
+
+    fn add_dock(&mut self, window: Window, struts: Struts) {
+        match self.docks.iter().position(|&(w, _)| w == window) {
+            Some(i) => self.docks[i].1 = struts,
+            None => self.docks.push((window, struts)),
+        }
+    }
+
+    fn remove_dock(&mut self, window: Window) -> Result<(), Self::Error> {
+        self.docks
+            .iter()
+            .position(|&(w, _)| w == window)
+            .ok_or(UnknownWindow(window))
+            .map(|i| {
+                self.docks.remove(i);
+            })
+    }
+
+    fn get_docks(&self) -> Vec<Window> {
+        self.docks.iter().map(|&(w, _)| w).collect()
+    }
+
+    fn copy_struts(&mut self, other: &TilingWM<MyLayouter>) {
+        self.struts = other.struts;
+        self.docks = other.docks.clone();
+    }
+}
+
+impl<MyLayouter: Layouter> DirectionSupport for TilingWM<MyLayouter> {
+    fn swap_direction(&mut self, dir: Direction) {
+        let layout = self.get_window_layout();
+        let windows = layout.focused_window
+            .and_then(|w| find_window_in_direction(&layout, w, dir).map(|other| (w, other)));
+
+        if let Some((window, other)) = windows {
+            // Both windows are visible so they are managed and we can safely unwrap
+            self.swap_two_windows(window, other).ok().unwrap();
+        }
+    }
+}
+
 impl<MyLayouter: Layouter> TilingWM<MyLayouter> {
     /// Return the geometry for the window at position i
     fn get_geom(&self, i: usize) -> Geometry {
-        self.layouter.get_geom(i, self.get_screen(), self.windows.len())
+        let area = self.get_usable_area();
+        let geom = self.layouter.get_geom(i, area_screen(area), self.windows.len());
+
+        move_to_area(geom, area)
+    }
+
+    /// Return the geometries of all windows, in the order of their positions
+    fn get_geoms(&self) -> Vec<Geometry> {
+        let area = self.get_usable_area();
+        self.layouter
+            .get_geoms(area_screen(area), self.windows.len())
+            .into_iter()
+            .map(|geom| move_to_area(geom, area))
+            .collect()
+    }
+
+    /// Return the part of the screen that is not reserved by struts
+    pub fn get_usable_area(&self) -> Geometry {
+        self.get_struts().apply(self.screen.to_geometry())
     }
 
     /// Return the 'next' index in the direction of dir
@@ -254,6 +379,38 @@ impl<MyLayouter: Layouter> TilingWM<MyLayouter> {
         }
     }
 
+    /// Swap the tiles of 2 windows, the focus stays on the same window
+    pub fn swap_two_windows(&mut self, window: Window, other: Window) -> Result<(), WMError> {
+        let pos = try!(self.windows.iter().position(|w| *w == window).ok_or(UnknownWindow(window)));
+        let other_pos = try!(self.windows.iter().position(|w| *w == other).ok_or(UnknownWindow(other)));
+
+        self.windows.swap(pos, other_pos);
+
+        // Keep the focus on the same window
+        self.focused_index = self.focused_index.map(|i| {
+            if i == pos {
+                other_pos
+            } else if i == other_pos {
+                pos
+            } else {
+                i
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Put another window in the tile of the given window, the focus stays on the tile
+    pub fn replace_window(&mut self, window: Window, new_window: Window) -> Result<(), WMError> {
+        self.windows
+            .iter()
+            .position(|w| *w == window)
+            .ok_or(UnknownWindow(window))
+            .map(|pos| {
+                self.windows[pos] = new_window;
+            })
+    }
+
     /// Cycle focus but "no window" is also considered a window
     pub fn cycle_focus_helper(&mut self, dir: PrevOrNext) {
         let is_going_to_wrap = self.focused_index
@@ -273,6 +430,52 @@ impl<MyLayouter: Layouter> TilingWM<MyLayouter> {
     }
 }
 
+/// Return the screen in which the windows are laid out: the usable area as if it were the screen
+fn area_screen(area: Geometry) -> Screen {
+    Screen {
+        width: area.width,
+        height: area.height,
+    }
+}
+
+/// Move a geometry from the screen of the layout into the usable area
+fn move_to_area(geom: Geometry, area: Geometry) -> Geometry {
+    Geometry {
+        x: geom.x + area.x,
+        y: geom.y + area.y,
+        width: geom.width,
+        height: geom.height,
+    }
+}
+
+impl<MyLayouter: Layouter + MasterSupport> TilingWM<MyLayouter> {
+    /// Make the master area wider
+    pub fn grow_master(&mut self) {
+        let ratio = self.layouter.get_master_ratio();
+        self.layouter.set_master_ratio(ratio + MASTER_RATIO_STEP);
+    }
+
+    /// Make the master area narrower
+    pub fn shrink_master(&mut self) {
+        let ratio = self.layouter.get_master_ratio();
+        self.layouter.set_master_ratio(ratio - MASTER_RATIO_STEP);
+    }
+
+    /// Put one more window in the master area
+    pub fn increment_nb_masters(&mut self) {
+        let nb_masters = self.layouter.get_nb_masters();
+        self.layouter.set_nb_masters(nb_masters + 1);
+    }
+
+    /// Put one window less in the master area (there is always at least one master window)
+    pub fn decrement_nb_masters(&mut self) {
+        let nb_masters = self.layouter.get_nb_masters();
+        if nb_masters > 1 {
+            self.layouter.set_nb_masters(nb_masters - 1);
+        }
+    }
+}
+
 #[cfg(test)]
 #[allow(unused_mut)]
 #[allow(unused_variables)]
@@ -283,10 +486,15 @@ mod tests {
     pub use cplwm_api::wm::*;
     pub use cplwm_api::types::*;
     pub use cplwm_api::types::PrevOrNext::*;
+    pub use rustc_serialize::json;
+    pub use direction::*;
+    pub use struts::*;
 
     // Import expectest names
     pub use expectest::prelude::*;
 
+    conformance_tests!(WMName: window_manager, tiling);
+
     describe! wm {
         before_each {
             let screen: Screen = Screen {
@@ -811,5 +1019,244 @@ mod tests {
                 }
             }
         }
+
+        describe! master_support {
+            before_each {
+                wm.add_window(WindowWithInfo::new_tiled(1, some_geom)).unwrap();
+                wm.add_window(WindowWithInfo::new_tiled(2, some_geom)).unwrap();
+                wm.add_window(WindowWithInfo::new_tiled(3, some_geom)).unwrap();
+            }
+
+            it "should use a 50/50 split with one master by default" {
+                expect!(wm.layouter.get_master_ratio()).to(be_equal_to(0.5));
+                expect!(wm.layouter.get_nb_masters()).to(be_equal_to(1));
+            }
+
+            it "should be able to grow the master area" {
+                wm.grow_master();
+
+                let windows = vec![(1, Geometry { x: 0, y: 0, width: 440, height: 600 }),
+                                   (2, Geometry { x: 440, y: 0, width: 360, height: 300 }),
+                                   (3, Geometry { x: 440, y: 300, width: 360, height: 300 })];
+                expect!(wm.get_window_layout().windows).to(be_equal_to(windows));
+            }
+
+            it "should be able to shrink the master area" {
+                wm.shrink_master();
+
+                let windows = vec![(1, Geometry { x: 0, y: 0, width: 360, height: 600 }),
+                                   (2, Geometry { x: 360, y: 0, width: 440, height: 300 }),
+                                   (3, Geometry { x: 360, y: 300, width: 440, height: 300 })];
+                expect!(wm.get_window_layout().windows).to(be_equal_to(windows));
+            }
+
+            it "shouldn't grow the master area beyond the maximum" {
+                for _ in 0..20 {
+                    wm.grow_master();
+                }
+
+                expect!(wm.layouter.get_master_ratio()).to(be_equal_to(MAX_MASTER_RATIO));
+            }
+
+            it "shouldn't shrink the master area beyond the minimum" {
+                for _ in 0..20 {
+                    wm.shrink_master();
+                }
+
+                expect!(wm.layouter.get_master_ratio()).to(be_equal_to(MIN_MASTER_RATIO));
+            }
+
+            it "should stack multiple masters in the master area" {
+                wm.increment_nb_masters();
+
+                let windows = vec![(1, Geometry { x: 0, y: 0, width: 400, height: 300 }),
+                                   (2, Geometry { x: 0, y: 300, width: 400, height: 300 }),
+                                   (3, right_half)];
+                expect!(wm.get_window_layout().windows).to(be_equal_to(windows));
+                expect!(wm.get_master_window()).to(be_equal_to(Some(1)));
+            }
+
+            it "should use the whole screen if all windows are masters" {
+                wm.increment_nb_masters();
+                wm.increment_nb_masters();
+                wm.increment_nb_masters();
+
+                let windows = vec![(1, Geometry { x: 0, y: 0, width: 800, height: 200 }),
+                                   (2, Geometry { x: 0, y: 200, width: 800, height: 200 }),
+                                   (3, Geometry { x: 0, y: 400, width: 800, height: 200 })];
+                expect!(wm.get_window_layout().windows).to(be_equal_to(windows));
+            }
+
+            it "should be able to decrement the number of masters" {
+                wm.increment_nb_masters();
+                wm.decrement_nb_masters();
+
+                let windows = vec![(1, left_half),
+                                   (2, right_upper_quarter),
+                                   (3, right_lower_quarter)];
+                expect!(wm.get_window_layout().windows).to(be_equal_to(windows));
+            }
+
+            it "should keep at least one master" {
+                wm.decrement_nb_masters();
+
+                expect!(wm.layouter.get_nb_masters()).to(be_equal_to(1));
+            }
+
+            it "should keep the settings after encoding and decoding" {
+                wm.grow_master();
+                wm.increment_nb_masters();
+
+                let encoded = json::encode(&wm).unwrap();
+                let decoded: TilingWM<SimpleLayouter> = json::decode(&encoded).unwrap();
+
+                expect!(decoded.layouter.get_master_ratio()).to(be_equal_to(wm.layouter.get_master_ratio()));
+                expect!(decoded.layouter.get_nb_masters()).to(be_equal_to(2));
+                expect!(decoded.get_window_layout()).to(be_equal_to(wm.get_window_layout()));
+            }
+
+            it "should clamp a decoded master ratio that is too large" {
+                let encoded = json::encode(&wm).unwrap().replace("\"master_ratio\":0.5", "\"master_ratio\":1.5");
+                let decoded: TilingWM<SimpleLayouter> = json::decode(&encoded).unwrap();
+
+                let windows = vec![(1, Geometry { x: 0, y: 0, width: 720, height: 600 }),
+                                   (2, Geometry { x: 720, y: 0, width: 80, height: 300 }),
+                                   (3, Geometry { x: 720, y: 300, width: 80, height: 300 })];
+                expect!(decoded.get_window_layout().windows).to(be_equal_to(windows));
+            }
+        }
+
+        describe! strut_support {
+            before_each {
+                wm.add_window(WindowWithInfo::new_tiled(1, some_geom)).unwrap();
+                wm.add_window(WindowWithInfo::new_tiled(2, some_geom)).unwrap();
+
+                let top_bar = Struts { top: 20, bottom: 0, left: 0, right: 0 };
+                let bottom_bar = Struts { top: 10, bottom: 30, left: 0, right: 0 };
+            }
+
+            it "should not reserve any edges initially" {
+                expect!(wm.get_struts()).to(be_equal_to(Struts::new()));
+                expect!(wm.get_docks()).to(be_equal_to(vec![]));
+                expect!(wm.get_usable_area()).to(be_equal_to(screen_geom));
+            }
+
+            it "should keep tiled windows out of explicitly reserved edges" {
+                wm.set_struts(top_bar);
+
+                let windows = vec![(1, Geometry { x: 0, y: 20, width: 400, height: 580 }),
+                                   (2, Geometry { x: 400, y: 20, width: 400, height: 580 })];
+                expect!(wm.get_window_info(1).unwrap().geometry).to(be_equal_to(windows[0].1));
+                expect!(wm.get_window_layout().windows).to(be_equal_to(windows));
+            }
+
+            it "should combine the struts of the docks with the explicit struts" {
+                wm.set_struts(top_bar);
+                wm.add_dock(10, bottom_bar);
+
+                expect!(wm.get_struts()).to(be_equal_to(Struts { top: 20, bottom: 30, left: 0, right: 0 }));
+                expect!(wm.get_docks()).to(be_equal_to(vec![10]));
+                let windows = vec![(1, Geometry { x: 0, y: 20, width: 400, height: 550 }),
+                                   (2, Geometry { x: 400, y: 20, width: 400, height: 550 })];
+                expect!(wm.get_window_layout().windows).to(be_equal_to(windows));
+            }
+
+            it "should release the edges of a removed dock" {
+                wm.add_dock(10, bottom_bar);
+                wm.remove_dock(10).unwrap();
+
+                expect!(wm.get_docks()).to(be_equal_to(vec![]));
+                expect!(wm.get_window_layout().windows).to(be_equal_to(vec![(1, left_half), (2, right_half)]));
+            }
+
+            it "should replace the struts of a dock that is added twice" {
+                wm.add_dock(10, bottom_bar);
+                wm.add_dock(10, top_bar);
+
+                expect!(wm.get_docks()).to(be_equal_to(vec![10]));
+                expect!(wm.get_struts()).to(be_equal_to(top_bar));
+            }
+
+            it "should error when removing an unknown dock" {
+                expect!(wm.remove_dock(10)).to(be_err());
+            }
+
+            it "should not manage the dock windows" {
+                wm.add_dock(10, bottom_bar);
+
+                expect!(wm.is_managed(10)).to(be_false());
+                expect!(wm.get_windows()).to(be_equal_to(vec![1, 2]));
+            }
+        }
+
+        describe! direction_support {
+            before_each {
+                wm.add_window(WindowWithInfo::new_tiled(1, some_geom)).unwrap();
+                wm.add_window(WindowWithInfo::new_tiled(2, some_geom)).unwrap();
+                wm.add_window(WindowWithInfo::new_tiled(3, some_geom)).unwrap();
+            }
+
+            it "should focus the window to the right of the master window" {
+                wm.add_window(WindowWithInfo::new_tiled(4, some_geom)).unwrap();
+                wm.focus_window(Some(1)).unwrap();
+
+                wm.focus_direction(Direction::Right);
+
+                // The middle slave is the nearest one
+                expect!(wm.get_focused_window()).to(be_equal_to(Some(3)));
+            }
+
+            it "should focus the windows above, below and to the left" {
+                wm.focus_direction(Direction::Up);
+                expect!(wm.get_focused_window()).to(be_equal_to(Some(2)));
+
+                wm.focus_direction(Direction::Down);
+                expect!(wm.get_focused_window()).to(be_equal_to(Some(3)));
+
+                wm.focus_direction(Direction::Left);
+                expect!(wm.get_focused_window()).to(be_equal_to(Some(1)));
+            }
+
+            it "shouldn't change the focus if there is no window in that direction" {
+                wm.focus_direction(Direction::Down);
+                expect!(wm.get_focused_window()).to(be_equal_to(Some(3)));
+
+                wm.focus_window(Some(1)).unwrap();
+                wm.focus_direction(Direction::Left);
+                expect!(wm.get_focused_window()).to(be_equal_to(Some(1)));
+            }
+
+            it "shouldn't do anything if there is no focused window" {
+                wm.focus_window(None).unwrap();
+
+                wm.focus_direction(Direction::Left);
+                wm.swap_direction(Direction::Left);
+
+                expect!(wm.get_focused_window()).to(be_equal_to(None));
+                expect!(wm.get_windows()).to(be_equal_to(vec![1, 2, 3]));
+            }
+
+            it "should swap the focused window with the window in that direction" {
+                wm.focus_window(Some(2)).unwrap();
+
+                wm.swap_direction(Direction::Left);
+
+                expect!(wm.get_focused_window()).to(be_equal_to(Some(2)));
+                let windows = vec![(2, left_half),
+                                   (1, right_upper_quarter),
+                                   (3, right_lower_quarter)];
+                expect!(wm.get_window_layout().windows).to(be_equal_to(windows));
+            }
+
+            it "shouldn't swap if there is no window in that direction" {
+                wm.swap_direction(Direction::Right);
+
+                expect!(wm.get_focused_window()).to(be_equal_to(Some(3)));
+                let windows = vec![(1, left_half),
+                                   (2, right_upper_quarter),
+                                   (3, right_lower_quarter)];
+                expect!(wm.get_window_layout().windows).to(be_equal_to(windows));
+            }
+        }
     }
 }
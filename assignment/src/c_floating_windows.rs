@@ -32,14 +32,20 @@ use cplwm_api::types::FloatOrTile;
 pub use cplwm_api::types::FloatOrTile::*;
 use cplwm_api::wm::{FloatSupport, GapSupport, TilingSupport, WindowManager};
 use std::collections::HashMap;
+use std::os::raw::c_uint;
 
 use error::WMError;
 use error::WMError::*;
 use b_tiling_wm::{SimpleLayouter, TilingWM};
-use f_gaps::GappedLayouter;
+use f_gaps::{ExtendedGapSupport, GappedLayouter, OuterGaps};
 use layouter::Layouter;
 use layouter::GapSupport as GenericGapSupport;
+use layouter::LayoutSupport as GenericLayoutSupport;
+use layouts::{Layout, LayoutSupport};
 use fixed_window_manager::RealWindowInfo;
+use direction::{Direction, DirectionSupport, find_window_in_direction};
+use struts::{StrutSupport, Struts};
+use window_description::{DescriptionSupport, WindowDescription};
 
 /// Type alias for automated tests
 pub type WMName = FloatingWM<GappedLayouter<SimpleLayouter>>;
@@ -47,6 +53,7 @@ pub type WMName = FloatingWM<GappedLayouter<SimpleLayouter>>;
 /// Main struct of the window manager
 /// This WM can float or tile windows
 #[derive(RustcDecodable, RustcEncodable, Debug, Clone)]
+#[cfg_attr(feature = "with-serde", derive(Serialize, Deserialize))]
 pub struct FloatingWM<MyLayouter: Layouter> {
     /// A vector of floating windows (in order of adding them)
     pub floating_windows: Vec<Window>,
@@ -103,7 +110,12 @@ impl<MyLayouter: Layouter> WindowManager for FloatingWM<MyLayouter> {
         Ok(())
     }
 
+    /// Removing a dock window releases the edges reserved by the dock
     fn remove_window(&mut self, window: Window) -> Result<(), Self::Error> {
+        if !self.is_managed(window) && self.get_docks().contains(&window) {
+            return self.remove_dock(window);
+        }
+
         // Remove the window info from the wm
         self.infos.remove(&window);
 
@@ -128,8 +140,9 @@ impl<MyLayouter: Layouter> WindowManager for FloatingWM<MyLayouter> {
                     } else if let Some(j) = self.focused_index {
                         // A floating window has focus
                         if i <= j {
-                            // Update the index of the focused window to keep the same window in focus (it's possible that no window is in focus after this)
-                            self.focused_index = self.cycle_index_helper(i, Prev);
+                            // Update the index of the focused window to keep the same window in focus
+                            // (if the focused window was removed, it's possible that no window is in focus after this)
+                            self.focused_index = self.cycle_index_helper(j, Prev);
                             // re-order if necessary
                             let focused_window = self.get_focused_window();
                             try!(self.focus_window(focused_window));
@@ -290,7 +303,11 @@ impl<MyLayouter: Layouter> TilingSupport for FloatingWM<MyLayouter> {
         if self.floating_windows.contains(&window) {
             try!(self.float_or_tile_window(&window, Tile));
         }
-        self.tiling_wm.swap_with_master(window)
+        try!(self.tiling_wm.swap_with_master(window));
+
+        // The master window gets the focus, even if a floating window had the focus
+        self.focused_index = None;
+        Ok(())
     }
 
     /// If the focused window is a floating window, it will first be tiled
@@ -369,6 +386,114 @@ impl<MyLayouter: Layouter + GenericGapSupport> GapSupport for FloatingWM<MyLayou
     }
 }
 
+impl<MyLayouter: Layouter + GenericGapSupport> ExtendedGapSupport for FloatingWM<MyLayouter> {
+    fn get_inner_gap(&self) -> GapSize {
+        self.tiling_wm.get_inner_gap()
+    }
+
+    fn set_inner_gap(&mut self, gap: GapSize) {
+        self.tiling_wm.set_inner_gap(gap)
+    }
+
+    fn get_outer_gaps(&self) -> OuterGaps {
+        self.tiling_wm.get_outer_gaps()
+    }
+
+    fn set_outer_gaps(&mut self, gaps: OuterGaps) {
+        self.tiling_wm.set_outer_gaps(gaps)
+    }
+
+    fn get_smart_gaps(&self) -> bool {
+        self.tiling_wm.get_smart_gaps()
+    }
+
+    fn set_smart_gaps(&mut self, smart_gaps: bool) {
+        self.tiling_wm.set_smart_gaps(smart_gaps)
+    }
+
+    fn get_min_window_size(&self) -> c_uint {
+        self.tiling_wm.get_min_window_size()
+    }
+
+    fn set_min_window_size(&mut self, size: c_uint) {
+        self.tiling_wm.set_min_window_size(size)
+    }
+
+    fn try_set_gap(&mut self, gapsize: GapSize) -> Result<(), Self::Error> {
+        self.tiling_wm.try_set_gap(gapsize)
+    }
+}
+
+impl<MyLayouter: Layouter + GenericLayoutSupport> LayoutSupport for FloatingWM<MyLayouter> {
+    fn get_layout(&self) -> Layout {
+        self.tiling_wm.get_layout()
+    }
+
+    fn set_layout(&mut self, layout: Layout) {
+        self.tiling_wm.set_layout(layout)
+    }
+}
+
+/// Only tiled windows avoid the reserved edges, floating windows keep their geometry
+impl<MyLayouter: Layouter> StrutSupport for FloatingWM<MyLayouter> {
+    fn get_struts(&self) -> Struts {
+        self.tiling_wm.get_struts()
+    }
+
+    fn set_struts(&mut self, struts: Struts) {
+        self.tiling_wm.set_struts(struts)
+    }
+
+    fn add_dock(&mut self, window: Window, struts: Struts) {
+        self.tiling_wm.add_dock(window, struts)
+    }
+
+    fn remove_dock(&mut self, window: Window) -> Result<(), Self::Error> {
+        self.tiling_wm.remove_dock(window)
+    }
+
+    fn get_docks(&self) -> Vec<Window> {
+        self.tiling_wm.get_docks()
+    }
+
+    fn copy_struts(&mut self, other: &FloatingWM<MyLayouter>) {
+        self.tiling_wm.copy_struts(&other.tiling_wm)
+    }
+}
+
+impl<MyLayouter: Layouter> DescriptionSupport for FloatingWM<MyLayouter> {
+    /// Dialogs, splash screens, utility windows, transient windows, ... will float (see `WindowDescription::should_float`)
+    fn add_window_with_description(&mut self, description: WindowDescription) -> Result<(), Self::Error> {
+        if description.is_dock() {
+            self.add_dock(description.info.window, description.struts);
+            return Ok(());
+        }
+
+        let mut info = description.info;
+        if description.should_float() {
+            info.float_or_tile = Float;
+        }
+
+        self.add_window(info)
+    }
+}
+
+impl<MyLayouter: Layouter> DirectionSupport for FloatingWM<MyLayouter> {
+    /// If one of the windows is floating and the other one is tiled, they exchange places:
+    /// the tiled window will float where the floating window was and the floating window takes the tile.
+    fn swap_direction(&mut self, dir: Direction) {
+        let layout = self.get_window_layout();
+        let windows = layout.focused_window
+            .and_then(|w| find_window_in_direction(&layout, w, dir).map(|other| (w, other)));
+
+        if let Some((window, other)) = windows {
+            // Both windows are visible so they are managed and we can safely unwrap
+            self.swap_two_windows(window, other).unwrap();
+            self.focus_window(Some(window)).unwrap();
+        }
+    }
+}
+
 impl<MyLayouter: Layouter> FloatingWM<MyLayouter> {
     /// Get the requested geometry for this window
     /// Panics if the window is not in the managed windows
@@ -398,6 +523,42 @@ impl<MyLayouter: Layouter> FloatingWM<MyLayouter> {
         }
     }
 
+    /// Swap the places of 2 managed windows (tiled or floating)
+    fn swap_two_windows(&mut self, window: Window, other: Window) -> Result<(), WMError> {
+        match (self.is_floating(window), self.is_floating(other)) {
+            (false, false) => self.tiling_wm.swap_two_windows(window, other),
+            (true, true) => {
+                let geom = self.get_geom(&window);
+                let other_geom = self.get_geom(&other);
+                try!(self.set_window_geometry(window, other_geom));
+                self.set_window_geometry(other, geom)
+            }
+            (true, false) => self.exchange_floating_and_tiled(window, other),
+            (false, true) => self.exchange_floating_and_tiled(other, window),
+        }
+    }
+
+    /// Tile the floating window in the tile of the tiled window and let the tiled window float
+    /// where the floating window was.
+    fn exchange_floating_and_tiled(&mut self, floating: Window, tiled: Window) -> Result<(), WMError> {
+        try!(self.tiling_wm.replace_window(tiled, floating));
+
+        for w in self.floating_windows.iter_mut().chain(self.stack_order_floating_windows.iter_mut()) {
+            if *w == floating {
+                *w = tiled;
+            }
+        }
+
+        let floating_geom = self.get_geom(&floating);
+        self.infos.get_mut(&floating).map(|info| info.float_or_tile = Tile);
+        self.infos.get_mut(&tiled).map(|info| {
+            info.float_or_tile = Float;
+            info.geometry = floating_geom;
+        });
+
+        Ok(())
+    }
+
     /// Float or tile the window.
     fn float_or_tile_window(&mut self, window: &Window, float_or_tile: FloatOrTile) -> Result<(), WMError> {
         let window_info = self.infos.get(&window).map(|info| info.clone());
@@ -433,6 +594,10 @@ impl<MyLayouter: Layouter> RealWindowInfo for FloatingWM<MyLayouter> {
                 }
             })
     }
+
+    fn get_usable_area(&self) -> Geometry {
+        self.tiling_wm.get_usable_area()
+    }
 }
 
 #[cfg(test)]
@@ -445,10 +610,15 @@ mod tests {
     pub use cplwm_api::wm::*;
     pub use cplwm_api::types::*;
     pub use cplwm_api::types::PrevOrNext::*;
+    pub use direction::*;
+    pub use struts::*;
+    pub use window_description::*;
 
     // Import expectest names
     pub use expectest::prelude::*;
 
+    conformance_tests!(WMName: window_manager, tiling, floating, gap);
+
     describe! wm {
         before_each {
             let screen: Screen = Screen {
@@ -680,6 +850,21 @@ mod tests {
                 expect!(wl.windows).to(be_equal_to(vec![(2, left_half),(3, right_half)]));
             }
 
+            it "should not lose focus if we remove a float window before the focused float window" {
+                wm.add_window(WindowWithInfo::new_tiled(1, some_geom)).unwrap();
+                wm.add_window(WindowWithInfo::new_float(2, some_geom)).unwrap();
+                wm.add_window(WindowWithInfo::new_float(3, floating_geom)).unwrap();
+
+                wm.remove_window(2).unwrap();
+
+                expect!(wm.is_managed(2)).to(be_false());
+                expect!(wm.get_windows()).to(be_equal_to(vec![1, 3]));
+                let wl = wm.get_window_layout();
+                expect!(wl.focused_window).to(be_equal_to(Some(3)));
+                expect!(wm.get_focused_window()).to(be_equal_to(Some(3)));
+                expect!(wl.windows).to(be_equal_to(vec![(1, screen_geom),(3, floating_geom)]));
+            }
+
             it "should do be in initial state if we remove all windows" {
                 wm.add_window(WindowWithInfo::new_tiled(1, some_geom)).unwrap();
                 wm.add_window(WindowWithInfo::new_float(2, some_geom)).unwrap();
@@ -1040,6 +1225,16 @@ mod tests {
                                                                                 (5, some_geom)]));
                 }
 
+                it "should focus the master window if a floating window was focused" {
+                    wm.focus_window(Some(5)).unwrap();
+
+                    wm.swap_with_master(2).unwrap();
+
+                    expect!(wm.get_focused_window()).to(be_equal_to(Some(2)));
+                    expect!(wm.get_window_layout().focused_window).to(be_equal_to(Some(2)));
+                    expect!(wm.get_floating_windows()).to(be_equal_to(vec![5]));
+                }
+
                 it "should focus the master tile if it is already the master window" {
                     wm.swap_with_master(1).unwrap();
 
@@ -1199,6 +1394,136 @@ mod tests {
                 }
             }
         }
+
+        describe! direction_support {
+            before_each {
+                wm.add_window(WindowWithInfo::new_tiled(1, some_geom)).unwrap();
+                wm.add_window(WindowWithInfo::new_tiled(2, some_geom)).unwrap();
+                wm.add_window(WindowWithInfo::new_float(3, floating_geom)).unwrap();
+            }
+
+            it "should move the focus from a floating window to a tiled window" {
+                wm.focus_direction(Direction::Right);
+                expect!(wm.get_focused_window()).to(be_equal_to(Some(1)));
+            }
+
+            it "should move the focus from a tiled window to a floating window" {
+                wm.focus_window(Some(1)).unwrap();
+
+                wm.focus_direction(Direction::Up);
+
+                expect!(wm.get_focused_window()).to(be_equal_to(Some(3)));
+            }
+
+            it "should swap 2 tiled windows" {
+                wm.focus_window(Some(2)).unwrap();
+
+                wm.swap_direction(Direction::Left);
+
+                expect!(wm.get_focused_window()).to(be_equal_to(Some(2)));
+                let windows = vec![(2, left_half), (1, right_half), (3, floating_geom)];
+                expect!(wm.get_window_layout().windows).to(be_equal_to(windows));
+            }
+
+            it "should swap 2 floating windows" {
+                let other_geom = Geometry { x: 20, y: 100, width: 200, height: 20 };
+                wm.add_window(WindowWithInfo::new_float(4, other_geom)).unwrap();
+                wm.focus_window(Some(3)).unwrap();
+
+                wm.swap_direction(Direction::Down);
+
+                expect!(wm.get_focused_window()).to(be_equal_to(Some(3)));
+                let windows = vec![(1, left_half), (2, right_half), (4, floating_geom), (3, other_geom)];
+                expect!(wm.get_window_layout().windows).to(be_equal_to(windows));
+            }
+
+            it "should exchange the places of a floating and a tiled window" {
+                wm.swap_direction(Direction::Right);
+
+                expect!(wm.get_focused_window()).to(be_equal_to(Some(3)));
+                expect!(wm.get_floating_windows()).to(be_equal_to(vec![1]));
+                let windows = vec![(3, left_half), (2, right_half), (1, floating_geom)];
+                expect!(wm.get_window_layout().windows).to(be_equal_to(windows));
+            }
+
+            it "should exchange the places of a tiled and a floating window" {
+                wm.focus_window(Some(1)).unwrap();
+
+                wm.swap_direction(Direction::Up);
+
+                expect!(wm.get_focused_window()).to(be_equal_to(Some(1)));
+                expect!(wm.get_floating_windows()).to(be_equal_to(vec![1]));
+                let windows = vec![(3, left_half), (2, right_half), (1, floating_geom)];
+                expect!(wm.get_window_layout().windows).to(be_equal_to(windows));
+            }
+        }
+
+        describe! description_support {
+            it "should tile a normal window" {
+                wm.add_window_with_description(WindowDescription::new(WindowWithInfo::new_tiled(1, some_geom))).unwrap();
+
+                expect!(wm.get_floating_windows()).to(be_equal_to(vec![]));
+                expect!(wm.get_window_layout().windows).to(be_equal_to(vec![(1, screen_geom)]));
+            }
+
+            it "should float a dialog" {
+                let mut description = WindowDescription::new(WindowWithInfo::new_tiled(1, floating_geom));
+                description.window_type = WindowType::Dialog;
+
+                wm.add_window_with_description(description).unwrap();
+
+                expect!(wm.get_floating_windows()).to(be_equal_to(vec![1]));
+                expect!(wm.get_window_layout().windows).to(be_equal_to(vec![(1, floating_geom)]));
+            }
+
+            it "should float a transient window" {
+                wm.add_window(WindowWithInfo::new_tiled(1, some_geom)).unwrap();
+                let mut description = WindowDescription::new(WindowWithInfo::new_tiled(2, floating_geom));
+                description.transient_for = Some(1);
+
+                wm.add_window_with_description(description).unwrap();
+
+                expect!(wm.get_floating_windows()).to(be_equal_to(vec![2]));
+            }
+
+            it "should float a window that can't be resized" {
+                let mut description = WindowDescription::new(WindowWithInfo::new_tiled(1, floating_geom));
+                description.size_hints = SizeHints {
+                    min_width: Some(200),
+                    min_height: Some(20),
+                    max_width: Some(200),
+                    max_height: Some(20),
+                };
+
+                wm.add_window_with_description(description).unwrap();
+
+                expect!(wm.get_floating_windows()).to(be_equal_to(vec![1]));
+            }
+
+            it "should not manage a dock but reserve its edges" {
+                let struts = Struts { top: 20, bottom: 0, left: 0, right: 0 };
+                wm.add_window(WindowWithInfo::new_tiled(1, some_geom)).unwrap();
+
+                wm.add_window_with_description(WindowDescription::new_dock(WindowWithInfo::new_tiled(10, some_geom), struts)).unwrap();
+
+                expect!(wm.is_managed(10)).to(be_false());
+                expect!(wm.get_docks()).to(be_equal_to(vec![10]));
+                expect!(wm.get_focused_window()).to(be_equal_to(Some(1)));
+                let geom = Geometry { x: 0, y: 20, width: 800, height: 580 };
+                expect!(wm.get_window_layout().windows).to(be_equal_to(vec![(1, geom)]));
+            }
+
+            it "should release the edges of a dock when removing the dock window" {
+                let struts = Struts { top: 20, bottom: 0, left: 0, right: 0 };
+                wm.add_window(WindowWithInfo::new_tiled(1, some_geom)).unwrap();
+                wm.add_window_with_description(WindowDescription::new_dock(WindowWithInfo::new_tiled(10, some_geom), struts)).unwrap();
+
+                expect!(wm.remove_window(10)).to(be_ok());
+
+                expect!(wm.get_docks()).to(be_equal_to(vec![]));
+                expect!(wm.get_window_layout().windows).to(be_equal_to(vec![(1, screen_geom)]));
+            }
+        }
     }
 
     describe! integration_test {
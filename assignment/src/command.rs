@@ -0,0 +1,515 @@
+//! This module provides a small command language to control a window manager
+//!
+//! Keybindings and scripts can send lines like `focus next`, `swap master`, `toggle floating`, `workspace 2`,
+//! `gap 10`, `layout spiral` or `minimise 0x1a00003` instead of calling the traits of `cplwm_api::wm` themselves.
+//!
+//! The language:
+//!
+//! ```text
+//! focus (next | prev | left | right | up | down | master | none | <window>)
+//! swap (next | prev | left | right | up | down | master [<window>])
+//! toggle (floating | fullscreen | minimised) [<window>]
+//! minimise [<window>]
+//! workspace <index>
+//! move <index> [<window>]
+//! gap <size>
+//! layout (simple | spiral)
+//! ```
+//!
+//! Windows are written in hexadecimal (`0x1a00003`) or decimal. When a command takes an optional window,
+//! the focused window is used if no window is given.
+//!
+
+use std::fmt;
+use std::str::FromStr;
+
+use cplwm_api::types::{GapSize, PrevOrNext, Window, WorkspaceIndex};
+use cplwm_api::types::PrevOrNext::*;
+use cplwm_api::wm::{FloatSupport, FullscreenSupport, MinimiseSupport, MultiWorkspaceSupport, TilingSupport, WindowManager};
+
+use direction::{Direction, DirectionSupport};
+use error::{CommandError, ParseError};
+use f_gaps::ExtendedGapSupport;
+use g_multiple_workspaces::MoveWindowSupport;
+use layouts::{Layout, LayoutSupport};
+
+/// A command for a window manager.
+///
+/// The commands that take an `Option<Window>` use the focused window when it's `None`.
+#[derive(RustcDecodable, RustcEncodable, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    /// `focus next`, `focus prev`: see `WindowManager::cycle_focus`
+    CycleFocus(PrevOrNext),
+    /// `focus left`, ...: see `DirectionSupport::focus_direction`
+    FocusDirection(Direction),
+    /// `focus master`: focus the master window
+    FocusMaster,
+    /// `focus none`, `focus <window>`: see `WindowManager::focus_window`
+    FocusWindow(Option<Window>),
+    /// `swap next`, `swap prev`: see `TilingSupport::swap_windows`
+    SwapWindows(PrevOrNext),
+    /// `swap left`, ...: see `DirectionSupport::swap_direction`
+    SwapDirection(Direction),
+    /// `swap master [<window>]`: see `TilingSupport::swap_with_master`
+    SwapWithMaster(Option<Window>),
+    /// `toggle floating [<window>]`: see `FloatSupport::toggle_floating`
+    ToggleFloating(Option<Window>),
+    /// `toggle fullscreen [<window>]`: see `FullscreenSupport::toggle_fullscreen`
+    ToggleFullscreen(Option<Window>),
+    /// `toggle minimised [<window>]`: see `MinimiseSupport::toggle_minimised`
+    ToggleMinimised(Option<Window>),
+    /// `minimise [<window>]`: minimise the window if it isn't minimised yet
+    Minimise(Option<Window>),
+    /// `workspace <index>`: see `MultiWorkspaceSupport::switch_workspace`
+    SwitchWorkspace(WorkspaceIndex),
+    /// `move <index> [<window>]`: see `MoveWindowSupport::move_window_to_workspace`
+    MoveToWorkspace(WorkspaceIndex, Option<Window>),
+    /// `gap <size>`: see `ExtendedGapSupport::try_set_gap`
+    SetGap(GapSize),
+    /// `layout simple`, `layout spiral`: see `LayoutSupport::set_layout`
+    SetLayout(Layout),
+}
+
+/// Parse a window in hexadecimal (with a `0x` prefix) or decimal notation
+fn parse_window(argument: &str) -> Result<Window, ParseError> {
+    let result = if argument.starts_with("0x") {
+        Window::from_str_radix(&argument[2..], 16)
+    } else {
+        argument.parse()
+    };
+
+    result.map_err(|_| ParseError::InvalidArgument(argument.to_string()))
+}
+
+/// Parse a number (e.g. a workspace index or a gap size)
+fn parse_number<T: FromStr>(argument: &str) -> Result<T, ParseError> {
+    argument.parse().map_err(|_| ParseError::InvalidArgument(argument.to_string()))
+}
+
+/// Parse a direction of `DirectionSupport`
+fn parse_direction(argument: &str) -> Option<Direction> {
+    match argument {
+        "left" => Some(Direction::Left),
+        "right" => Some(Direction::Right),
+        "up" => Some(Direction::Up),
+        "down" => Some(Direction::Down),
+        _ => None,
+    }
+}
+
+/// Parse a layout of `LayoutSupport`
+fn parse_layout(argument: &str) -> Option<Layout> {
+    match argument {
+        "simple" => Some(Layout::Simple),
+        "spiral" => Some(Layout::Spiral),
+        _ => None,
+    }
+}
+
+/// Parse an optional window argument
+fn parse_optional_window(argument: Option<&str>) -> Result<Option<Window>, ParseError> {
+    match argument {
+        Some(argument) => parse_window(argument).map(Some),
+        None => Ok(None),
+    }
+}
+
+impl FromStr for Command {
+    type Err = ParseError;
+
+    fn from_str(line: &str) -> Result<Command, ParseError> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let (name, arguments) = match words.split_first() {
+            Some((name, arguments)) => (*name, arguments),
+            None => return Err(ParseError::EmptyCommand),
+        };
+
+        let max_arguments = match name {
+            "swap" | "toggle" | "move" => 2,
+            _ => 1,
+        };
+        if arguments.len() > max_arguments {
+            return Err(ParseError::TooManyArguments(name.to_string()));
+        }
+
+        let first = arguments.get(0).cloned();
+        let second = arguments.get(1).cloned();
+        let missing = || ParseError::MissingArgument(name.to_string());
+
+        match name {
+            "focus" => {
+                match try!(first.ok_or_else(&missing)) {
+                    "next" => Ok(Command::CycleFocus(Next)),
+                    "prev" => Ok(Command::CycleFocus(Prev)),
+                    "master" => Ok(Command::FocusMaster),
+                    "none" => Ok(Command::FocusWindow(None)),
+                    argument => {
+                        match parse_direction(argument) {
+                            Some(dir) => Ok(Command::FocusDirection(dir)),
+                            None => Ok(Command::FocusWindow(Some(try!(parse_window(argument))))),
+                        }
+                    }
+                }
+            }
+            "swap" => {
+                match try!(first.ok_or_else(&missing)) {
+                    "master" => Ok(Command::SwapWithMaster(try!(parse_optional_window(second)))),
+                    _ if second.is_some() => Err(ParseError::TooManyArguments(name.to_string())),
+                    "next" => Ok(Command::SwapWindows(Next)),
+                    "prev" => Ok(Command::SwapWindows(Prev)),
+                    argument => {
+                        parse_direction(argument)
+                            .map(Command::SwapDirection)
+                            .ok_or_else(|| ParseError::InvalidArgument(argument.to_string()))
+                    }
+                }
+            }
+            "toggle" => {
+                let window = try!(parse_optional_window(second));
+                match try!(first.ok_or_else(&missing)) {
+                    "floating" => Ok(Command::ToggleFloating(window)),
+                    "fullscreen" => Ok(Command::ToggleFullscreen(window)),
+                    "minimised" => Ok(Command::ToggleMinimised(window)),
+                    argument => Err(ParseError::InvalidArgument(argument.to_string())),
+                }
+            }
+            "minimise" => Ok(Command::Minimise(try!(parse_optional_window(first)))),
+            "workspace" => Ok(Command::SwitchWorkspace(try!(parse_number(try!(first.ok_or_else(&missing)))))),
+            "move" => {
+                let index = try!(parse_number(try!(first.ok_or_else(&missing))));
+                Ok(Command::MoveToWorkspace(index, try!(parse_optional_window(second))))
+            }
+            "gap" => Ok(Command::SetGap(try!(parse_number(try!(first.ok_or_else(&missing)))))),
+            "layout" => {
+                let argument = try!(first.ok_or_else(&missing));
+                parse_layout(argument).map(Command::SetLayout).ok_or_else(|| ParseError::InvalidArgument(argument.to_string()))
+            }
+            _ => Err(ParseError::UnknownCommand(name.to_string())),
+        }
+    }
+}
+
+/// Write the name of a direction as it's parsed
+fn direction_name(dir: Direction) -> &'static str {
+    match dir {
+        Direction::Left => "left",
+        Direction::Right => "right",
+        Direction::Up => "up",
+        Direction::Down => "down",
+    }
+}
+
+/// Write the name of a PrevOrNext as it's parsed
+fn prev_or_next_name(dir: PrevOrNext) -> &'static str {
+    match dir {
+        Prev => "prev",
+        Next => "next",
+    }
+}
+
+/// Write an optional window argument (with a leading space)
+fn optional_window(window: Option<Window>) -> String {
+    window.map_or(String::new(), |window| format!(" {:#x}", window))
+}
+
+/// Commands are written in the language they're parsed from, so `command.to_string().parse()` returns the command.
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Command::CycleFocus(dir) => write!(f, "focus {}", prev_or_next_name(dir)),
+            Command::FocusDirection(dir) => write!(f, "focus {}", direction_name(dir)),
+            Command::FocusMaster => write!(f, "focus master"),
+            Command::FocusWindow(None) => write!(f, "focus none"),
+            Command::FocusWindow(Some(window)) => write!(f, "focus {:#x}", window),
+            Command::SwapWindows(dir) => write!(f, "swap {}", prev_or_next_name(dir)),
+            Command::SwapDirection(dir) => write!(f, "swap {}", direction_name(dir)),
+            Command::SwapWithMaster(window) => write!(f, "swap master{}", optional_window(window)),
+            Command::ToggleFloating(window) => write!(f, "toggle floating{}", optional_window(window)),
+            Command::ToggleFullscreen(window) => write!(f, "toggle fullscreen{}", optional_window(window)),
+            Command::ToggleMinimised(window) => write!(f, "toggle minimised{}", optional_window(window)),
+            Command::Minimise(window) => write!(f, "minimise{}", optional_window(window)),
+            Command::SwitchWorkspace(index) => write!(f, "workspace {}", index),
+            Command::MoveToWorkspace(index, window) => write!(f, "move {}{}", index, optional_window(window)),
+            Command::SetGap(gap) => write!(f, "gap {}", gap),
+            Command::SetLayout(layout) => write!(f, "layout {}", layout.name()),
+        }
+    }
+}
+
+/// The traits a window manager needs to run every command.
+///
+/// It's implemented for every window manager that implements all of them.
+pub trait CommandSupport<W: WindowManager>: TilingSupport + FloatSupport + MinimiseSupport + FullscreenSupport + ExtendedGapSupport + LayoutSupport +
+    DirectionSupport + MultiWorkspaceSupport<W> + MoveWindowSupport {
+}
+
+impl<W, WM> CommandSupport<W> for WM
+    where W: WindowManager,
+          WM: TilingSupport + FloatSupport + MinimiseSupport + FullscreenSupport + ExtendedGapSupport + LayoutSupport +
+              DirectionSupport + MultiWorkspaceSupport<W> + MoveWindowSupport
+{
+}
+
+/// Return the given window or the focused window
+fn window_or_focused<WM: WindowManager>(wm: &WM, window: Option<Window>) -> Result<Window, CommandError<WM::Error>> {
+    window.or_else(|| wm.get_focused_window()).ok_or(CommandError::NoFocusedWindow)
+}
+
+/// Run the command on the window manager.
+///
+/// Errors of the window manager are returned as a `CommandError::WrappedError`.
+pub fn execute<W, WM>(wm: &mut WM, command: Command) -> Result<(), CommandError<WM::Error>>
+    where W: WindowManager,
+          WM: CommandSupport<W>
+{
+    match command {
+        Command::CycleFocus(dir) => wm.cycle_focus(dir),
+        Command::FocusDirection(dir) => wm.focus_direction(dir),
+        Command::FocusMaster => {
+            let master = wm.get_master_window();
+            if master.is_some() {
+                try!(wm.focus_window(master));
+            }
+        }
+        Command::FocusWindow(window) => try!(wm.focus_window(window)),
+        Command::SwapWindows(dir) => wm.swap_windows(dir),
+        Command::SwapDirection(dir) => wm.swap_direction(dir),
+        Command::SwapWithMaster(window) => {
+            let window = try!(window_or_focused(wm, window));
+            try!(wm.swap_with_master(window));
+        }
+        Command::ToggleFloating(window) => {
+            let window = try!(window_or_focused(wm, window));
+            try!(wm.toggle_floating(window));
+        }
+        Command::ToggleFullscreen(window) => {
+            let window = try!(window_or_focused(wm, window));
+            try!(wm.toggle_fullscreen(window));
+        }
+        Command::ToggleMinimised(window) => {
+            let window = try!(window_or_focused(wm, window));
+            try!(wm.toggle_minimised(window));
+        }
+        Command::Minimise(window) => {
+            let window = try!(window_or_focused(wm, window));
+            if !wm.is_minimised(window) {
+                try!(wm.toggle_minimised(window));
+            }
+        }
+        Command::SwitchWorkspace(index) => try!(wm.switch_workspace(index)),
+        Command::MoveToWorkspace(index, window) => {
+            let window = try!(window_or_focused(wm, window));
+            try!(wm.move_window_to_workspace(window, index));
+        }
+        Command::SetGap(gap) => try!(wm.try_set_gap(gap)),
+        Command::SetLayout(layout) => wm.set_layout(layout),
+    }
+
+    Ok(())
+}
+
+/// Parse the line and run the command on the window manager (see `execute`).
+pub fn run<W, WM>(wm: &mut WM, line: &str) -> Result<(), CommandError<WM::Error>>
+    where W: WindowManager,
+          WM: CommandSupport<W>
+{
+    let command = try!(line.parse().map_err(CommandError::ParseError));
+    execute(wm, command)
+}
+
+#[cfg(test)]
+#[allow(unused_mut)]
+#[allow(unused_variables)]
+mod tests {
+    pub use super::*;
+
+    pub use cplwm_api::wm::*;
+    pub use cplwm_api::types::*;
+    pub use cplwm_api::types::PrevOrNext::*;
+    pub use direction::*;
+    pub use error::*;
+    pub use g_multiple_workspaces::WMName;
+    pub use layouts::*;
+
+    // Import expectest names
+    pub use expectest::prelude::*;
+
+    describe! parse {
+        it "should parse the focus commands" {
+            expect!("focus next".parse()).to(be_equal_to(Ok(Command::CycleFocus(Next))));
+            expect!("focus prev".parse()).to(be_equal_to(Ok(Command::CycleFocus(Prev))));
+            expect!("focus left".parse()).to(be_equal_to(Ok(Command::FocusDirection(Direction::Left))));
+            expect!("focus master".parse()).to(be_equal_to(Ok(Command::FocusMaster)));
+            expect!("focus none".parse()).to(be_equal_to(Ok(Command::FocusWindow(None))));
+            expect!("focus 0x1a00003".parse()).to(be_equal_to(Ok(Command::FocusWindow(Some(0x1a00003)))));
+        }
+
+        it "should parse the swap commands" {
+            expect!("swap next".parse()).to(be_equal_to(Ok(Command::SwapWindows(Next))));
+            expect!("swap down".parse()).to(be_equal_to(Ok(Command::SwapDirection(Direction::Down))));
+            expect!("swap master".parse()).to(be_equal_to(Ok(Command::SwapWithMaster(None))));
+            expect!("swap master 12".parse()).to(be_equal_to(Ok(Command::SwapWithMaster(Some(12)))));
+        }
+
+        it "should parse the toggle commands" {
+            expect!("toggle floating".parse()).to(be_equal_to(Ok(Command::ToggleFloating(None))));
+            expect!("toggle fullscreen 0x10".parse()).to(be_equal_to(Ok(Command::ToggleFullscreen(Some(16)))));
+            expect!("toggle minimised".parse()).to(be_equal_to(Ok(Command::ToggleMinimised(None))));
+        }
+
+        it "should parse the other commands" {
+            expect!("minimise 0x1a00003".parse()).to(be_equal_to(Ok(Command::Minimise(Some(0x1a00003)))));
+            expect!("workspace 2".parse()).to(be_equal_to(Ok(Command::SwitchWorkspace(2))));
+            expect!("move 3".parse()).to(be_equal_to(Ok(Command::MoveToWorkspace(3, None))));
+            expect!("gap 10".parse()).to(be_equal_to(Ok(Command::SetGap(10))));
+            expect!("layout spiral".parse()).to(be_equal_to(Ok(Command::SetLayout(Layout::Spiral))));
+        }
+
+        it "should ignore extra whitespace" {
+            expect!("  workspace \t 2 ".parse()).to(be_equal_to(Ok(Command::SwitchWorkspace(2))));
+        }
+
+        it "should return an error for invalid commands" {
+            expect!("".parse::<Command>()).to(be_equal_to(Err(ParseError::EmptyCommand)));
+            expect!("jump 2".parse::<Command>()).to(be_equal_to(Err(ParseError::UnknownCommand(String::from("jump")))));
+            expect!("workspace".parse::<Command>()).to(be_equal_to(Err(ParseError::MissingArgument(String::from("workspace")))));
+            expect!("workspace two".parse::<Command>()).to(be_equal_to(Err(ParseError::InvalidArgument(String::from("two")))));
+            expect!("toggle maximised".parse::<Command>()).to(be_equal_to(Err(ParseError::InvalidArgument(String::from("maximised")))));
+            expect!("layout grid".parse::<Command>()).to(be_equal_to(Err(ParseError::InvalidArgument(String::from("grid")))));
+            expect!("gap 10 20".parse::<Command>()).to(be_equal_to(Err(ParseError::TooManyArguments(String::from("gap")))));
+            expect!("swap next 1".parse::<Command>()).to(be_equal_to(Err(ParseError::TooManyArguments(String::from("swap")))));
+        }
+
+        it "should write commands the way they're parsed" {
+            let commands = vec![Command::CycleFocus(Prev),
+                                Command::FocusDirection(Direction::Up),
+                                Command::FocusMaster,
+                                Command::FocusWindow(None),
+                                Command::FocusWindow(Some(0x1a00003)),
+                                Command::SwapWindows(Next),
+                                Command::SwapDirection(Direction::Right),
+                                Command::SwapWithMaster(Some(1)),
+                                Command::ToggleFloating(None),
+                                Command::ToggleFullscreen(Some(2)),
+                                Command::ToggleMinimised(None),
+                                Command::Minimise(Some(3)),
+                                Command::SwitchWorkspace(2),
+                                Command::MoveToWorkspace(1, Some(4)),
+                                Command::SetGap(10),
+                                Command::SetLayout(Layout::Simple)];
+
+            for command in commands {
+                expect!(command.to_string().parse()).to(be_equal_to(Ok(command)));
+            }
+        }
+    }
+
+    describe! execute {
+        before_each {
+            let screen: Screen = Screen {
+                width: 800,
+                height: 600,
+            };
+
+            let some_geom: Geometry = Geometry {
+                x: 10,
+                y: 10,
+                width: 100,
+                height: 100,
+            };
+
+            let mut wm = WMName::new(screen);
+            wm.add_window(WindowWithInfo::new_tiled(1, some_geom)).unwrap();
+            wm.add_window(WindowWithInfo::new_tiled(2, some_geom)).unwrap();
+        }
+
+        it "should change the focus" {
+            run(&mut wm, "focus next").unwrap();
+            expect!(wm.get_focused_window()).to(be_equal_to(Some(1)));
+
+            run(&mut wm, "focus 0x2").unwrap();
+            expect!(wm.get_focused_window()).to(be_equal_to(Some(2)));
+
+            run(&mut wm, "focus master").unwrap();
+            expect!(wm.get_focused_window()).to(be_equal_to(Some(1)));
+        }
+
+        it "should swap the focused window with the master window" {
+            run(&mut wm, "swap master").unwrap();
+
+            expect!(wm.get_master_window()).to(be_equal_to(Some(2)));
+        }
+
+        it "should toggle the focused window" {
+            run(&mut wm, "toggle floating").unwrap();
+            expect!(wm.get_floating_windows()).to(be_equal_to(vec![2]));
+
+            run(&mut wm, "toggle fullscreen 1").unwrap();
+            expect!(wm.get_fullscreen_window()).to(be_equal_to(Some(1)));
+        }
+
+        it "should only minimise a window that isn't minimised" {
+            run(&mut wm, "minimise 1").unwrap();
+            run(&mut wm, "minimise 1").unwrap();
+
+            expect!(wm.get_minimised_windows()).to(be_equal_to(vec![1]));
+        }
+
+        it "should switch workspaces and move windows" {
+            run(&mut wm, "move 1").unwrap();
+            expect!(wm.get_workspace(1).unwrap().get_windows()).to(be_equal_to(vec![2]));
+
+            run(&mut wm, "workspace 1").unwrap();
+            expect!(wm.get_current_workspace_index()).to(be_equal_to(1));
+            expect!(wm.get_focused_window()).to(be_equal_to(Some(2)));
+        }
+
+        it "should set the gap" {
+            run(&mut wm, "gap 10").unwrap();
+
+            expect!(wm.get_gap()).to(be_equal_to(10));
+        }
+
+        it "should report a gap that doesn't fit" {
+            match run(&mut wm, "gap 1000") {
+                Err(CommandError::WrappedError(MultiWMError::WrappedError(WMError::GapTooLarge(1000)))) => (),
+                result => panic!("Expected a GapTooLarge error, got {:?}", result),
+            }
+            expect!(wm.get_gap()).to(be_equal_to(1000));
+        }
+
+        it "should set the layout of the current workspace" {
+            run(&mut wm, "layout spiral").unwrap();
+
+            expect!(wm.get_layout()).to(be_equal_to(Layout::Spiral));
+            expect!(wm.get_workspace(1).unwrap().get_layout()).to(be_equal_to(Layout::Simple));
+        }
+
+        it "should return a parse error" {
+            match run(&mut wm, "gap ten") {
+                Err(CommandError::ParseError(ParseError::InvalidArgument(_))) => (),
+                result => panic!("Expected a parse error, got {:?}", result),
+            }
+        }
+
+        it "should return the errors of the window manager" {
+            match run(&mut wm, "workspace 100") {
+                Err(CommandError::WrappedError(MultiWMError::UnknownWorkspace(100))) => (),
+                result => panic!("Expected an UnknownWorkspace error, got {:?}", result),
+            }
+            match run(&mut wm, "toggle floating 3") {
+                Err(CommandError::WrappedError(MultiWMError::WrappedError(WMError::UnknownWindow(3)))) => (),
+                result => panic!("Expected an UnknownWindow error, got {:?}", result),
+            }
+        }
+
+        it "should return an error when no window is focused" {
+            run(&mut wm, "focus none").unwrap();
+
+            match run(&mut wm, "toggle floating") {
+                Err(CommandError::NoFocusedWindow) => (),
+                result => panic!("Expected a NoFocusedWindow error, got {:?}", result),
+            }
+        }
+    }
+}
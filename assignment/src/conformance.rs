@@ -0,0 +1,674 @@
+//! This module contains a conformance suite for the traits of the API
+//!
+//! Every invariant that is documented in `cplwm_api::wm` is checked by a generic function in the submodule of its
+//! trait, e.g. `tiling::swap_with_master_focuses_master::<WM>()`. The checks only use the methods of the traits, so
+//! they hold for every window manager, wrapper or layouter.
+//!
+//! The `conformance_tests!` macro instantiates all checks of the given suites as `#[test]` functions, e.g. in the
+//! test module of a new window manager:
+//!
+//! ```ignore
+//! conformance_tests!(WMName: window_manager, tiling, floating, minimising, fullscreen, gap, workspaces);
+//! ```
+//!
+//! The suites are `window_manager`, `tiling`, `floating`, `minimising`, `fullscreen`, `gap` and `workspaces`. The
+//! type of the window manager must be in scope of the module that invokes the macro.
+//!
+
+use cplwm_api::types::{FloatOrTile, Geometry, Screen, Window, WindowWithInfo};
+
+/// Instantiate the conformance suites for a window manager, see the module documentation
+macro_rules! conformance_tests {
+    (@suite window_manager, $wm:ty) => {
+        conformance_tests!(@checks window_manager, [$wm], [
+            new_window_manager_is_empty,
+            add_window_manages_and_focuses_window,
+            get_windows_has_no_duplicates,
+            remove_window_unmanages_window,
+            focus_window_focuses_window,
+            focused_window_is_visible,
+            cycle_focus_back_and_forth_keeps_focus,
+            cycle_focus_focuses_a_window,
+            get_window_info_returns_managed_window,
+            resize_screen_changes_screen,
+            visible_windows_are_managed
+        ]);
+    };
+    (@suite tiling, $wm:ty) => {
+        conformance_tests!(@checks tiling, [$wm], [
+            master_window_is_managed,
+            swap_with_master_focuses_master,
+            swap_windows_keeps_focus,
+            swap_windows_back_and_forth_keeps_layout,
+            as_many_tiles_as_windows
+        ]);
+    };
+    (@suite floating, $wm:ty) => {
+        conformance_tests!(@checks floating, [$wm], [
+            float_window_floats,
+            toggle_floating_restores_geometry,
+            toggle_floating_twice_keeps_floating,
+            floating_windows_are_above_tiled_windows,
+            set_window_geometry_moves_floating_window,
+            unmanaged_window_does_not_float
+        ]);
+    };
+    (@suite minimising, $wm:ty) => {
+        conformance_tests!(@checks minimising, [$wm], [
+            toggle_minimised_hides_window,
+            toggle_minimised_twice_shows_window,
+            minimised_windows_are_in_order,
+            unmanaged_window_is_not_minimised
+        ]);
+    };
+    (@suite fullscreen, $wm:ty) => {
+        conformance_tests!(@checks fullscreen, [$wm], [
+            toggle_fullscreen_shows_only_window,
+            toggle_fullscreen_twice_shows_all_windows,
+            fullscreen_window_is_added_fullscreen
+        ]);
+    };
+    (@suite gap, $wm:ty) => {
+        conformance_tests!(@checks gap, [$wm], [
+            initial_gap_is_zero,
+            set_gap_changes_gap,
+            single_tile_has_gap
+        ]);
+    };
+    (@suite workspaces, $wm:ty) => {
+        conformance_tests!(@checks workspaces, [_, $wm], [
+            first_workspace_is_current,
+            switch_workspace_changes_current_workspace,
+            invalid_workspace_returns_error,
+            new_workspace_is_empty,
+            switch_workspace_back_keeps_layout
+        ]);
+    };
+    (@checks $suite:ident, $types:tt, [$($check:ident),+]) => {
+        mod $suite {
+            #[allow(unused_imports)]
+            use super::*;
+
+            $(conformance_tests!(@check $suite, $types, $check);)+
+        }
+    };
+    (@check $suite:ident, [$($types:ty),+], $check:ident) => {
+        #[test]
+        fn $check() {
+            ::conformance::$suite::$check::<$($types),+>();
+        }
+    };
+    ($wm:ty: $($suite:ident),+) => {
+        $(conformance_tests!(@suite $suite, $wm);)+
+    };
+}
+
+/// The screen of the window managers under test
+fn screen() -> Screen {
+    Screen {
+        width: 800,
+        height: 600,
+    }
+}
+
+/// The geometry of the windows that are added
+fn some_geom() -> Geometry {
+    Geometry {
+        x: 10,
+        y: 10,
+        width: 100,
+        height: 100,
+    }
+}
+
+/// The geometry of the floating windows that are added
+fn floating_geom() -> Geometry {
+    Geometry {
+        x: 20,
+        y: 40,
+        width: 200,
+        height: 20,
+    }
+}
+
+/// A tiled window
+fn tiled(window: Window) -> WindowWithInfo {
+    WindowWithInfo::new_tiled(window, some_geom())
+}
+
+/// A floating window
+fn floating(window: Window) -> WindowWithInfo {
+    WindowWithInfo::new_float(window, floating_geom())
+}
+
+/// A window manager with the given tiled windows, the last one is focused
+fn with_windows<WM: ::cplwm_api::wm::WindowManager>(windows: &[Window]) -> WM {
+    let mut wm = WM::new(screen());
+    for &window in windows {
+        wm.add_window(tiled(window)).unwrap();
+    }
+    wm
+}
+
+/// The position of the window in the stacking order of the window layout
+fn stack_position<WM: ::cplwm_api::wm::WindowManager>(wm: &WM, window: Window) -> Option<usize> {
+    wm.get_window_layout().windows.iter().position(|&(w, _)| w == window)
+}
+
+/// Checks of `WindowManager`
+pub mod window_manager {
+    use cplwm_api::types::PrevOrNext::*;
+    use cplwm_api::wm::WindowManager;
+    use expectest::prelude::*;
+
+    use super::*;
+
+    /// A new window manager has no windows and no focus
+    pub fn new_window_manager_is_empty<WM: WindowManager>() {
+        let wm = WM::new(screen());
+
+        expect!(wm.get_windows().is_empty()).to(be_true());
+        expect!(wm.get_focused_window()).to(be_equal_to(None));
+        expect!(wm.get_window_layout().windows.is_empty()).to(be_true());
+        expect!(wm.get_screen()).to(be_equal_to(screen()));
+    }
+
+    /// An added window is managed and focused
+    pub fn add_window_manages_and_focuses_window<WM: WindowManager>() {
+        let mut wm = WM::new(screen());
+
+        for window in 1..4 {
+            wm.add_window(tiled(window)).unwrap();
+
+            expect!(wm.is_managed(window)).to(be_true());
+            expect!(wm.get_windows().contains(&window)).to(be_true());
+            expect!(wm.get_focused_window()).to(be_equal_to(Some(window)));
+        }
+    }
+
+    /// `get_windows` never contains duplicates, even when a window is added twice
+    pub fn get_windows_has_no_duplicates<WM: WindowManager>() {
+        let mut wm = with_windows::<WM>(&[1, 2, 3]);
+        let _ = wm.add_window(tiled(2));
+
+        let windows = wm.get_windows();
+        let mut unique_windows = windows.clone();
+        unique_windows.sort();
+        unique_windows.dedup();
+        expect!(unique_windows.len()).to(be_equal_to(windows.len()));
+        expect!(unique_windows).to(be_equal_to(vec![1, 2, 3]));
+    }
+
+    /// A removed window is no longer managed, the focus only changes when the focused window is removed
+    pub fn remove_window_unmanages_window<WM: WindowManager>() {
+        let mut wm = with_windows::<WM>(&[1, 2, 3]);
+
+        wm.remove_window(2).unwrap();
+        expect!(wm.is_managed(2)).to(be_false());
+        expect!(wm.is_managed(1)).to(be_true());
+        expect!(wm.get_focused_window()).to(be_equal_to(Some(3)));
+
+        wm.remove_window(3).unwrap();
+        expect!(wm.is_managed(3)).to(be_false());
+        if let Some(window) = wm.get_focused_window() {
+            expect!(wm.is_managed(window)).to(be_true());
+        }
+
+        wm.remove_window(1).unwrap();
+        expect!(wm.get_windows().is_empty()).to(be_true());
+        expect!(wm.get_focused_window()).to(be_equal_to(None));
+    }
+
+    /// `focus_window` focuses the given window or nothing
+    pub fn focus_window_focuses_window<WM: WindowManager>() {
+        let mut wm = with_windows::<WM>(&[1, 2, 3]);
+
+        for window in 1..4 {
+            wm.focus_window(Some(window)).unwrap();
+            expect!(wm.get_focused_window()).to(be_equal_to(Some(window)));
+        }
+
+        wm.focus_window(None).unwrap();
+        expect!(wm.get_focused_window()).to(be_equal_to(None));
+    }
+
+    /// The focused window is the focused window of the layout and it is visible
+    pub fn focused_window_is_visible<WM: WindowManager>() {
+        let mut wm = with_windows::<WM>(&[1, 2, 3]);
+
+        for &focus in &[Some(1), None, Some(3)] {
+            wm.focus_window(focus).unwrap();
+            let layout = wm.get_window_layout();
+
+            expect!(layout.focused_window).to(be_equal_to(wm.get_focused_window()));
+            if let Some(window) = layout.focused_window {
+                expect!(layout.windows.iter().any(|&(w, _)| w == window)).to(be_true());
+            }
+        }
+    }
+
+    /// Cycling the focus back and forth doesn't change the focused window
+    pub fn cycle_focus_back_and_forth_keeps_focus<WM: WindowManager>() {
+        let mut wm = with_windows::<WM>(&[1, 2, 3]);
+
+        for &focus in &[1, 2, 3] {
+            wm.focus_window(Some(focus)).unwrap();
+
+            wm.cycle_focus(Next);
+            wm.cycle_focus(Prev);
+            expect!(wm.get_focused_window()).to(be_equal_to(Some(focus)));
+
+            wm.cycle_focus(Prev);
+            wm.cycle_focus(Next);
+            expect!(wm.get_focused_window()).to(be_equal_to(Some(focus)));
+        }
+    }
+
+    /// Cycling the focus does nothing without windows and focuses a window when nothing is focused
+    pub fn cycle_focus_focuses_a_window<WM: WindowManager>() {
+        let mut wm = WM::new(screen());
+        wm.cycle_focus(Next);
+        expect!(wm.get_focused_window()).to(be_equal_to(None));
+
+        wm.add_window(tiled(1)).unwrap();
+        wm.focus_window(None).unwrap();
+        wm.cycle_focus(Next);
+        expect!(wm.get_focused_window()).to(be_equal_to(Some(1)));
+
+        wm.cycle_focus(Prev);
+        expect!(wm.get_focused_window()).to(be_equal_to(Some(1)));
+    }
+
+    /// `get_window_info` returns the info of managed windows and an error for other windows
+    pub fn get_window_info_returns_managed_window<WM: WindowManager>() {
+        let wm = with_windows::<WM>(&[1, 2]);
+
+        expect!(wm.get_window_info(1).unwrap().window).to(be_equal_to(1));
+        expect!(wm.get_window_info(2).unwrap().window).to(be_equal_to(2));
+        expect!(wm.get_window_info(3).is_err()).to(be_true());
+    }
+
+    /// `get_screen` returns the screen after `resize_screen`
+    pub fn resize_screen_changes_screen<WM: WindowManager>() {
+        let mut wm = with_windows::<WM>(&[1, 2]);
+        let new_screen = Screen {
+            width: 1024,
+            height: 768,
+        };
+
+        wm.resize_screen(new_screen);
+        expect!(wm.get_screen()).to(be_equal_to(new_screen));
+    }
+
+    /// The visible windows are managed and only visible once
+    pub fn visible_windows_are_managed<WM: WindowManager>() {
+        let wm = with_windows::<WM>(&[1, 2, 3]);
+
+        let mut visible_windows: Vec<_> = wm.get_window_layout().windows.iter().map(|&(w, _)| w).collect();
+        for &window in &visible_windows {
+            expect!(wm.is_managed(window)).to(be_true());
+        }
+
+        let nb_visible_windows = visible_windows.len();
+        visible_windows.sort();
+        visible_windows.dedup();
+        expect!(visible_windows.len()).to(be_equal_to(nb_visible_windows));
+    }
+}
+
+/// Checks of `TilingSupport`
+pub mod tiling {
+    use cplwm_api::types::PrevOrNext::*;
+    use cplwm_api::wm::TilingSupport;
+    use expectest::prelude::*;
+
+    use super::*;
+
+    /// There is no master window without windows, otherwise the master window is managed
+    pub fn master_window_is_managed<WM: TilingSupport>() {
+        let mut wm = WM::new(screen());
+        expect!(wm.get_master_window()).to(be_equal_to(None));
+
+        wm.add_window(tiled(1)).unwrap();
+        wm.add_window(tiled(2)).unwrap();
+        let master = wm.get_master_window().unwrap();
+        expect!(wm.is_managed(master)).to(be_true());
+    }
+
+    /// After `swap_with_master(w)`, `w` is the master window and it is focused
+    pub fn swap_with_master_focuses_master<WM: TilingSupport>() {
+        let mut wm = with_windows::<WM>(&[1, 2, 3]);
+
+        for &window in &[3, 1, 1, 2] {
+            wm.swap_with_master(window).unwrap();
+            expect!(wm.get_master_window()).to(be_equal_to(Some(window)));
+            expect!(wm.get_focused_window()).to(be_equal_to(Some(window)));
+        }
+    }
+
+    /// Swapping windows doesn't change the focused window, even if no window is focused
+    pub fn swap_windows_keeps_focus<WM: TilingSupport>() {
+        let mut wm = with_windows::<WM>(&[1, 2, 3]);
+
+        for &focus in &[Some(1), Some(2), None] {
+            wm.focus_window(focus).unwrap();
+
+            wm.swap_windows(Next);
+            expect!(wm.get_focused_window()).to(be_equal_to(focus));
+            wm.swap_windows(Prev);
+            expect!(wm.get_focused_window()).to(be_equal_to(focus));
+        }
+    }
+
+    /// Swapping windows back and forth doesn't change the layout
+    pub fn swap_windows_back_and_forth_keeps_layout<WM: TilingSupport>() {
+        let mut wm = with_windows::<WM>(&[1, 2, 3]);
+
+        for &focus in &[1, 2, 3] {
+            wm.focus_window(Some(focus)).unwrap();
+            let layout = wm.get_window_layout();
+
+            wm.swap_windows(Next);
+            wm.swap_windows(Prev);
+            expect!(wm.get_window_layout()).to(be_equal_to(layout.clone()));
+
+            wm.swap_windows(Prev);
+            wm.swap_windows(Next);
+            expect!(wm.get_window_layout()).to(be_equal_to(layout));
+        }
+    }
+
+    /// There are as many tiles as there are (tiled) windows
+    pub fn as_many_tiles_as_windows<WM: TilingSupport>() {
+        let mut wm = WM::new(screen());
+
+        for window in 1..6 {
+            wm.add_window(tiled(window)).unwrap();
+            let layout = wm.get_window_layout();
+            expect!(layout.windows.len()).to(be_equal_to(window as usize));
+
+            for (i, &(_, geometry)) in layout.windows.iter().enumerate() {
+                expect!(layout.windows[i + 1..].iter().any(|&(_, other)| other == geometry)).to(be_false());
+            }
+        }
+    }
+}
+
+/// Checks of `FloatSupport`
+pub mod floating {
+    use cplwm_api::wm::FloatSupport;
+    use expectest::prelude::*;
+
+    use super::*;
+
+    /// A window that is added as floating window floats with its own geometry
+    pub fn float_window_floats<WM: FloatSupport>() {
+        let mut wm = with_windows::<WM>(&[1]);
+        wm.add_window(floating(2)).unwrap();
+
+        expect!(wm.is_floating(2)).to(be_true());
+        expect!(wm.is_floating(1)).to(be_false());
+        expect!(wm.get_floating_windows()).to(be_equal_to(vec![2]));
+        expect!(wm.get_window_layout().windows.contains(&(2, floating_geom()))).to(be_true());
+        expect!(wm.get_window_info(2).unwrap().float_or_tile).to(be_equal_to(FloatOrTile::Float));
+    }
+
+    /// A tiled window that starts to float gets its original geometry back
+    pub fn toggle_floating_restores_geometry<WM: FloatSupport>() {
+        let mut wm = with_windows::<WM>(&[1, 2]);
+
+        wm.toggle_floating(1).unwrap();
+        expect!(wm.is_floating(1)).to(be_true());
+        expect!(wm.get_window_layout().windows.contains(&(1, some_geom()))).to(be_true());
+
+        wm.toggle_floating(1).unwrap();
+        expect!(wm.is_floating(1)).to(be_false());
+    }
+
+    /// Toggling a window twice doesn't change whether it floats
+    pub fn toggle_floating_twice_keeps_floating<WM: FloatSupport>() {
+        let mut wm = with_windows::<WM>(&[1]);
+        wm.add_window(floating(2)).unwrap();
+
+        for &window in &[1, 2] {
+            let is_floating = wm.is_floating(window);
+            wm.toggle_floating(window).unwrap();
+            expect!(wm.is_floating(window)).to(be_equal_to(!is_floating));
+            wm.toggle_floating(window).unwrap();
+            expect!(wm.is_floating(window)).to(be_equal_to(is_floating));
+        }
+    }
+
+    /// Every floating window is placed above the tiled windows
+    pub fn floating_windows_are_above_tiled_windows<WM: FloatSupport>() {
+        let mut wm = WM::new(screen());
+        wm.add_window(floating(1)).unwrap();
+        wm.add_window(tiled(2)).unwrap();
+        wm.add_window(tiled(3)).unwrap();
+        wm.toggle_floating(3).unwrap();
+        wm.add_window(tiled(4)).unwrap();
+
+        for &floating_window in &[1, 3] {
+            for &tiled_window in &[2, 4] {
+                expect!(stack_position(&wm, floating_window) > stack_position(&wm, tiled_window)).to(be_true());
+            }
+        }
+    }
+
+    /// `set_window_geometry` moves a floating window
+    pub fn set_window_geometry_moves_floating_window<WM: FloatSupport>() {
+        let mut wm = with_windows::<WM>(&[1]);
+        wm.add_window(floating(2)).unwrap();
+        let new_geometry = Geometry {
+            x: 50,
+            y: 60,
+            width: 70,
+            height: 80,
+        };
+
+        wm.set_window_geometry(2, new_geometry).unwrap();
+        expect!(wm.get_window_layout().windows.contains(&(2, new_geometry))).to(be_true());
+        expect!(wm.get_window_info(2).unwrap().geometry).to(be_equal_to(new_geometry));
+    }
+
+    /// A window that isn't managed doesn't float
+    pub fn unmanaged_window_does_not_float<WM: FloatSupport>() {
+        let mut wm = WM::new(screen());
+        wm.add_window(floating(1)).unwrap();
+        wm.remove_window(1).unwrap();
+
+        expect!(wm.is_floating(1)).to(be_false());
+        expect!(wm.get_floating_windows().is_empty()).to(be_true());
+    }
+}
+
+/// Checks of `MinimiseSupport`
+pub mod minimising {
+    use cplwm_api::wm::MinimiseSupport;
+    use expectest::prelude::*;
+
+    use super::*;
+
+    /// A minimised window is still managed, but it isn't visible anymore
+    pub fn toggle_minimised_hides_window<WM: MinimiseSupport>() {
+        let mut wm = with_windows::<WM>(&[1, 2]);
+
+        wm.toggle_minimised(1).unwrap();
+        expect!(wm.is_minimised(1)).to(be_true());
+        expect!(wm.is_managed(1)).to(be_true());
+        expect!(stack_position(&wm, 1)).to(be_equal_to(None));
+        expect!(wm.get_focused_window() == Some(1)).to(be_false());
+    }
+
+    /// An unminimised window is visible again
+    pub fn toggle_minimised_twice_shows_window<WM: MinimiseSupport>() {
+        let mut wm = with_windows::<WM>(&[1, 2]);
+
+        wm.toggle_minimised(1).unwrap();
+        wm.toggle_minimised(1).unwrap();
+        expect!(wm.is_minimised(1)).to(be_false());
+        expect!(stack_position(&wm, 1).is_some()).to(be_true());
+    }
+
+    /// The minimised windows are in the order they were minimised
+    pub fn minimised_windows_are_in_order<WM: MinimiseSupport>() {
+        let mut wm = with_windows::<WM>(&[1, 2, 3]);
+
+        wm.toggle_minimised(2).unwrap();
+        wm.toggle_minimised(3).unwrap();
+        wm.toggle_minimised(1).unwrap();
+        expect!(wm.get_minimised_windows()).to(be_equal_to(vec![2, 3, 1]));
+
+        wm.toggle_minimised(3).unwrap();
+        expect!(wm.get_minimised_windows()).to(be_equal_to(vec![2, 1]));
+    }
+
+    /// A window that isn't managed isn't minimised
+    pub fn unmanaged_window_is_not_minimised<WM: MinimiseSupport>() {
+        let mut wm = with_windows::<WM>(&[1, 2]);
+        wm.toggle_minimised(1).unwrap();
+        wm.remove_window(1).unwrap();
+
+        expect!(wm.is_minimised(1)).to(be_false());
+        expect!(wm.get_minimised_windows().is_empty()).to(be_true());
+    }
+}
+
+/// Checks of `FullscreenSupport`
+pub mod fullscreen {
+    use cplwm_api::wm::FullscreenSupport;
+    use expectest::prelude::*;
+
+    use super::*;
+
+    /// The fullscreen window is the only visible window, it covers the screen and it is focused
+    pub fn toggle_fullscreen_shows_only_window<WM: FullscreenSupport>() {
+        let mut wm = with_windows::<WM>(&[1, 2, 3]);
+
+        wm.toggle_fullscreen(2).unwrap();
+        expect!(wm.get_fullscreen_window()).to(be_equal_to(Some(2)));
+        expect!(wm.get_focused_window()).to(be_equal_to(Some(2)));
+        expect!(wm.get_window_layout().windows).to(be_equal_to(vec![(2, screen().to_geometry())]));
+    }
+
+    /// When the fullscreen window is toggled again, all windows are visible again
+    pub fn toggle_fullscreen_twice_shows_all_windows<WM: FullscreenSupport>() {
+        let mut wm = with_windows::<WM>(&[1, 2, 3]);
+
+        wm.toggle_fullscreen(2).unwrap();
+        wm.toggle_fullscreen(2).unwrap();
+        expect!(wm.get_fullscreen_window()).to(be_equal_to(None));
+        for window in 1..4 {
+            expect!(stack_position(&wm, window).is_some()).to(be_true());
+        }
+    }
+
+    /// A window that is added as fullscreen window is fullscreen
+    pub fn fullscreen_window_is_added_fullscreen<WM: FullscreenSupport>() {
+        let mut wm = with_windows::<WM>(&[1]);
+        wm.add_window(WindowWithInfo::new_fullscreen(2, some_geom())).unwrap();
+
+        expect!(wm.get_fullscreen_window()).to(be_equal_to(Some(2)));
+        expect!(wm.get_focused_window()).to(be_equal_to(Some(2)));
+        expect!(wm.get_window_layout().windows).to(be_equal_to(vec![(2, screen().to_geometry())]));
+    }
+}
+
+/// Checks of `GapSupport`
+pub mod gap {
+    use cplwm_api::wm::GapSupport;
+    use expectest::prelude::*;
+
+    use super::*;
+
+    /// Initially the gap is 0
+    pub fn initial_gap_is_zero<WM: GapSupport>() {
+        let wm = WM::new(screen());
+        expect!(wm.get_gap()).to(be_equal_to(0));
+    }
+
+    /// `get_gap` returns the gap after `set_gap`
+    pub fn set_gap_changes_gap<WM: GapSupport>() {
+        let mut wm = with_windows::<WM>(&[1, 2]);
+
+        for &gap in &[10, 0, 25] {
+            wm.set_gap(gap);
+            expect!(wm.get_gap()).to(be_equal_to(gap));
+        }
+    }
+
+    /// Even a single tile has a gap around it
+    pub fn single_tile_has_gap<WM: GapSupport>() {
+        let mut wm = with_windows::<WM>(&[1]);
+        wm.set_gap(10);
+
+        expect!(wm.get_window_layout().windows).to(be_equal_to(vec![(1,
+                                                                     Geometry {
+                                                                         x: 10,
+                                                                         y: 10,
+                                                                         width: 780,
+                                                                         height: 580,
+                                                                     })]));
+    }
+}
+
+/// Checks of `MultiWorkspaceSupport`
+pub mod workspaces {
+    use cplwm_api::types::MAX_WORKSPACE_INDEX;
+    use cplwm_api::wm::{MultiWorkspaceSupport, WindowManager};
+    use expectest::prelude::*;
+
+    use super::*;
+
+    /// Initially the first workspace is the current workspace
+    pub fn first_workspace_is_current<W: WindowManager, WM: MultiWorkspaceSupport<W>>() {
+        let wm = WM::new(screen());
+        expect!(wm.get_current_workspace_index()).to(be_equal_to(0));
+    }
+
+    /// `switch_workspace` changes the current workspace, switching to the current workspace does nothing
+    pub fn switch_workspace_changes_current_workspace<W: WindowManager, WM: MultiWorkspaceSupport<W>>() {
+        let mut wm = with_windows::<WM>(&[1, 2]);
+
+        for index in (0..MAX_WORKSPACE_INDEX + 1).rev() {
+            wm.switch_workspace(index).unwrap();
+            expect!(wm.get_current_workspace_index()).to(be_equal_to(index));
+        }
+
+        let layout = wm.get_window_layout();
+        wm.switch_workspace(0).unwrap();
+        expect!(wm.get_window_layout()).to(be_equal_to(layout));
+    }
+
+    /// The workspaces after `MAX_WORKSPACE_INDEX` don't exist
+    pub fn invalid_workspace_returns_error<W: WindowManager, WM: MultiWorkspaceSupport<W>>() {
+        let mut wm = with_windows::<WM>(&[1, 2]);
+
+        expect!(wm.get_workspace(MAX_WORKSPACE_INDEX).is_ok()).to(be_true());
+        expect!(wm.get_workspace(MAX_WORKSPACE_INDEX + 1).is_err()).to(be_true());
+        expect!(wm.get_workspace_mut(MAX_WORKSPACE_INDEX + 1).is_err()).to(be_true());
+        expect!(wm.switch_workspace(MAX_WORKSPACE_INDEX + 1).is_err()).to(be_true());
+        expect!(wm.get_current_workspace_index()).to(be_equal_to(0));
+    }
+
+    /// The windows of the other workspaces aren't visible
+    pub fn new_workspace_is_empty<W: WindowManager, WM: MultiWorkspaceSupport<W>>() {
+        let mut wm = with_windows::<WM>(&[1, 2]);
+
+        wm.switch_workspace(1).unwrap();
+        expect!(wm.get_window_layout().windows.is_empty()).to(be_true());
+        expect!(wm.get_focused_window()).to(be_equal_to(None));
+    }
+
+    /// Switching to another workspace and back doesn't change the layout
+    pub fn switch_workspace_back_keeps_layout<W: WindowManager, WM: MultiWorkspaceSupport<W>>() {
+        let mut wm = with_windows::<WM>(&[1, 2, 3]);
+        wm.focus_window(Some(2)).unwrap();
+        let layout = wm.get_window_layout();
+
+        wm.switch_workspace(1).unwrap();
+        wm.add_window(tiled(4)).unwrap();
+        wm.switch_workspace(0).unwrap();
+        expect!(wm.get_window_layout()).to(be_equal_to(layout));
+    }
+}
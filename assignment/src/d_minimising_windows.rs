@@ -25,9 +25,15 @@
 use cplwm_api::types::{GapSize, Geometry, PrevOrNext, Screen, Window, WindowLayout, WindowWithInfo};
 use cplwm_api::wm::{FloatSupport, GapSupport, MinimiseSupport, TilingSupport, WindowManager};
 use std::collections::HashMap;
+use std::os::raw::c_uint;
 
 use c_floating_windows::WMName as FloatWM;
+use f_gaps::{ExtendedGapSupport, OuterGaps};
 use fixed_window_manager::RealWindowInfo;
+use layouts::{Layout, LayoutSupport};
+use direction::{Direction, DirectionSupport};
+use struts::{StrutSupport, Struts};
+use window_description::{DescriptionSupport, WindowDescription};
 
 /// Type alias for automated tests
 pub type WMName = MinimisingWM<FloatWM>;
@@ -35,6 +41,7 @@ pub type WMName = MinimisingWM<FloatWM>;
 /// Main struct of the window manager
 /// This WM can minimise windows and uses the WrappedWM for all unminimised windows
 #[derive(RustcDecodable, RustcEncodable, Debug, Clone)]
+#[cfg_attr(feature = "with-serde", derive(Serialize, Deserialize))]
 pub struct MinimisingWM<WrappedWM: RealWindowInfo> {
     /// A vector of minimised windows
     pub minimised_windows: Vec<Window>,
@@ -109,11 +116,10 @@ impl<WrappedWM: RealWindowInfo> WindowManager for MinimisingWM<WrappedWM> {
     }
 
     fn get_window_info(&self, window: Window) -> Result<WindowWithInfo, Self::Error> {
-        if self.is_minimised(window) {
-            // if the window is minimised, it's part of the infos hashmap
-            Ok(self.infos.get(&window).map(|info| *info).unwrap().0)
-        } else {
-            self.wrapped_wm.get_window_info(window)
+        // a window is minimised iff it's part of the infos hashmap
+        match self.infos.get(&window) {
+            Some(&(info, _)) => Ok(info),
+            None => self.wrapped_wm.get_window_info(window),
         }
     }
 
@@ -171,15 +177,15 @@ impl<WrappedWM: FloatSupport + RealWindowInfo> FloatSupport for MinimisingWM<Wra
 
     /// If the passed window is a minimised window, it will remember the geometry for when this window becomes unminimised again
     fn set_window_geometry(&mut self, window: Window, new_geometry: Geometry) -> Result<(), Self::Error> {
-        if self.is_minimised(window) {
-            // self.infos always contains an entry for minimised windows
-            let mut wi = self.infos.get(&window).unwrap().clone().0;
-            wi.geometry = new_geometry;
-            // Both the wi and the real wi are equal to the new wi value
-            self.infos.insert(window, (wi, wi));
-            Ok(())
-        } else {
-            self.wrapped_wm.set_window_geometry(window, new_geometry)
+        // a window is minimised iff it's part of the infos hashmap
+        match self.infos.get(&window).cloned() {
+            Some((mut wi, _)) => {
+                wi.geometry = new_geometry;
+                // Both the wi and the real wi are equal to the new wi value
+                self.infos.insert(window, (wi, wi));
+                Ok(())
+            }
+            None => self.wrapped_wm.set_window_geometry(window, new_geometry),
         }
     }
 }
@@ -189,13 +195,15 @@ impl<WrappedWM: RealWindowInfo> MinimiseSupport for MinimisingWM<WrappedWM> {
         self.minimised_windows.clone()
     }
 
+    /// A minimised fullscreen window is unminimised as a normal window,
+    /// only a wrapper with fullscreen support can make it fullscreen again
     fn toggle_minimised(&mut self, window: Window) -> Result<(), Self::Error> {
         let real_wi = try!(self.get_real_window_info(window));
         let was_minimised = self.is_minimised(window);
 
         if was_minimised {
             try!(self.remove_window(window));
-            self.add_window(real_wi)
+            self.add_window(WindowWithInfo { fullscreen: false, ..real_wi })
         } else {
             let wi = try!(self.get_window_info(window));
 
@@ -222,14 +230,111 @@ impl<WrappedWM: GapSupport + RealWindowInfo> GapSupport for MinimisingWM<Wrapped
     }
 }
 
+impl<WrappedWM: ExtendedGapSupport + RealWindowInfo> ExtendedGapSupport for MinimisingWM<WrappedWM> {
+    fn get_inner_gap(&self) -> GapSize {
+        self.wrapped_wm.get_inner_gap()
+    }
+
+    fn set_inner_gap(&mut self, gap: GapSize) {
+        self.wrapped_wm.set_inner_gap(gap)
+    }
+
+    fn get_outer_gaps(&self) -> OuterGaps {
+        self.wrapped_wm.get_outer_gaps()
+    }
+
+    fn set_outer_gaps(&mut self, gaps: OuterGaps) {
+        self.wrapped_wm.set_outer_gaps(gaps)
+    }
+
+    fn get_smart_gaps(&self) -> bool {
+        self.wrapped_wm.get_smart_gaps()
+    }
+
+    fn set_smart_gaps(&mut self, smart_gaps: bool) {
+        self.wrapped_wm.set_smart_gaps(smart_gaps)
+    }
+
+    fn get_min_window_size(&self) -> c_uint {
+        self.wrapped_wm.get_min_window_size()
+    }
+
+    fn set_min_window_size(&mut self, size: c_uint) {
+        self.wrapped_wm.set_min_window_size(size)
+    }
+
+    fn try_set_gap(&mut self, gapsize: GapSize) -> Result<(), Self::Error> {
+        self.wrapped_wm.try_set_gap(gapsize)
+    }
+}
+
+impl<WrappedWM: LayoutSupport + RealWindowInfo> LayoutSupport for MinimisingWM<WrappedWM> {
+    fn get_layout(&self) -> Layout {
+        self.wrapped_wm.get_layout()
+    }
+
+    fn set_layout(&mut self, layout: Layout) {
+        self.wrapped_wm.set_layout(layout)
+    }
+}
+
+impl<WrappedWM: StrutSupport + RealWindowInfo> StrutSupport for MinimisingWM<WrappedWM> {
+    fn get_struts(&self) -> Struts {
+        self.wrapped_wm.get_struts()
+    }
+
+    fn set_struts(&mut self, struts: Struts) {
+        self.wrapped_wm.set_struts(struts)
+    }
+
+    fn add_dock(&mut self, window: Window, struts: Struts) {
+        self.wrapped_wm.add_dock(window, struts)
+    }
+
+    fn remove_dock(&mut self, window: Window) -> Result<(), Self::Error> {
+        self.wrapped_wm.remove_dock(window)
+    }
+
+    fn get_docks(&self) -> Vec<Window> {
+        self.wrapped_wm.get_docks()
+    }
+
+    fn copy_struts(&mut self, other: &MinimisingWM<WrappedWM>) {
+        self.wrapped_wm.copy_struts(&other.wrapped_wm)
+    }
+}
+
+impl<WrappedWM: DescriptionSupport + RealWindowInfo> DescriptionSupport for MinimisingWM<WrappedWM> {
+    fn add_window_with_description(&mut self, description: WindowDescription) -> Result<(), Self::Error> {
+        if !self.is_minimised(description.info.window) {
+            self.wrapped_wm.add_window_with_description(description)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl<WrappedWM: DirectionSupport + RealWindowInfo> DirectionSupport for MinimisingWM<WrappedWM> {
+    fn focus_direction(&mut self, dir: Direction) {
+        self.wrapped_wm.focus_direction(dir)
+    }
+
+    fn swap_direction(&mut self, dir: Direction) {
+        self.wrapped_wm.swap_direction(dir)
+    }
+}
+
 impl<WrappedWM: RealWindowInfo> RealWindowInfo for MinimisingWM<WrappedWM> {
     fn get_real_window_info(&self, window: Window) -> Result<WindowWithInfo, Self::Error> {
-        if self.is_minimised(window) {
-            Ok(self.infos.get(&window).map(|info| *info).unwrap().1)
-        } else {
-            self.wrapped_wm.get_real_window_info(window)
+        match self.infos.get(&window) {
+            Some(&(_, real_info)) => Ok(real_info),
+            None => self.wrapped_wm.get_real_window_info(window),
         }
     }
+
+    fn get_usable_area(&self) -> Geometry {
+        self.wrapped_wm.get_usable_area()
+    }
 }
 
 #[cfg(test)]
@@ -248,6 +353,8 @@ mod tests {
     // Import expectest names
     pub use expectest::prelude::*;
 
+    conformance_tests!(WMName: window_manager, tiling, floating, minimising, gap);
+
     describe! wm {
         before_each {
             let screen: Screen = Screen {
@@ -0,0 +1,106 @@
+//! This module provides directional focus and swapping based on the geometry of the visible windows
+use std::os::raw::c_int;
+
+use cplwm_api::types::{Geometry, Window, WindowLayout};
+use cplwm_api::wm::WindowManager;
+
+/// A direction on the screen.
+///
+/// Using a simple data type like this instead of an integer is much clearer
+/// and will not lead to confusion.
+#[derive(Copy, Clone, RustcDecodable, RustcEncodable, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    /// Towards the left edge of the screen
+    Left,
+    /// Towards the right edge of the screen
+    Right,
+    /// Towards the top edge of the screen
+    Up,
+    /// Towards the bottom edge of the screen
+    Down,
+}
+
+/// A window manager that can move the focus and swap windows in a direction on the screen.
+///
+/// The window in a direction is the nearest window (as returned by `get_window_layout()`) whose center lies in that direction
+/// of the center of the focused window, see `find_window_in_direction`.
+pub trait DirectionSupport: WindowManager {
+    /// Focus the nearest visible window in the given direction of the focused window.
+    ///
+    /// If there is no focused window or no window in that direction, nothing happens.
+    fn focus_direction(&mut self, dir: Direction) {
+        let layout = self.get_window_layout();
+        let target = layout.focused_window
+            .and_then(|w| find_window_in_direction(&layout, w, dir));
+
+        if let Some(w) = target {
+            // The window is visible so it is managed and we can safely unwrap
+            self.focus_window(Some(w)).ok().unwrap();
+        }
+    }
+
+    /// Swap the focused window with the nearest visible window in the given direction.
+    ///
+    /// The focused window keeps the focus. If there is no focused window or no window in that direction, nothing happens.
+    fn swap_direction(&mut self, dir: Direction);
+}
+
+/// Find the window that is nearest to the given window in the given direction.
+///
+/// Only windows whose center lies in the given direction are considered. Windows that overlap with the given window
+/// on the perpendicular axis are preferred, then the distance between the centers along the direction decides and then the
+/// distance along the perpendicular axis. If there still is a tie, the window that is highest in the stacking order wins.
+///
+/// Returns None if the given window is not in the layout or if there is no window in that direction.
+pub fn find_window_in_direction(layout: &WindowLayout, window: Window, dir: Direction) -> Option<Window> {
+    let geom = match layout.windows.iter().find(|&&(w, _)| w == window) {
+        Some(&(_, geom)) => geom,
+        None => return None,
+    };
+
+    layout.windows
+        .iter()
+        // The top window comes first so it wins a tie
+        .rev()
+        .filter(|&&(w, other)| w != window && is_in_direction(geom, other, dir))
+        .min_by_key(|&&(_, other)| distance(geom, other, dir))
+        .map(|&(w, _)| w)
+}
+
+/// Twice the center of the geometry (to avoid rounding)
+fn double_center(geom: Geometry) -> (i64, i64) {
+    (2 * geom.x as i64 + geom.width as i64, 2 * geom.y as i64 + geom.height as i64)
+}
+
+/// Returns true if the center of other lies in direction dir of the center of geom
+fn is_in_direction(geom: Geometry, other: Geometry, dir: Direction) -> bool {
+    let (x, y) = double_center(geom);
+    let (other_x, other_y) = double_center(other);
+
+    match dir {
+        Direction::Left => other_x < x,
+        Direction::Right => other_x > x,
+        Direction::Up => other_y < y,
+        Direction::Down => other_y > y,
+    }
+}
+
+/// Returns true if the ranges [start1, start1 + length1[ and [start2, start2 + length2[ overlap
+fn overlaps(start1: c_int, length1: u32, start2: c_int, length2: u32) -> bool {
+    (start1 as i64) < start2 as i64 + length2 as i64 && (start2 as i64) < start1 as i64 + length1 as i64
+}
+
+/// The key to sort the candidate windows by, the smallest one is the nearest
+fn distance(geom: Geometry, other: Geometry, dir: Direction) -> (bool, i64, i64) {
+    let (x, y) = double_center(geom);
+    let (other_x, other_y) = double_center(other);
+
+    match dir {
+        Direction::Left | Direction::Right => {
+            (!overlaps(geom.y, geom.height, other.y, other.height), (other_x - x).abs(), (other_y - y).abs())
+        }
+        Direction::Up | Direction::Down => {
+            (!overlaps(geom.x, geom.width, other.x, other.width), (other_y - y).abs(), (other_x - x).abs())
+        }
+    }
+}
@@ -24,11 +24,18 @@
 //! A fullscreen window always has the focus and is the only one rendered. Floating windows are not visible above fullscreen windows
 //!
 
-use cplwm_api::types::{GapSize, Geometry, PrevOrNext, Screen, Window, WindowLayout, WindowWithInfo};
+use std::os::raw::c_uint;
+
+use cplwm_api::types::{FloatOrTile, GapSize, Geometry, PrevOrNext, Screen, Window, WindowLayout, WindowWithInfo};
 use cplwm_api::wm::{FloatSupport, FullscreenSupport, GapSupport, MinimiseSupport, TilingSupport, WindowManager};
 
 use d_minimising_windows::WMName as MinimisingWM;
+use f_gaps::{ExtendedGapSupport, OuterGaps};
 use fixed_window_manager::RealWindowInfo;
+use layouts::{Layout, LayoutSupport};
+use direction::{Direction, DirectionSupport};
+use struts::{StrutSupport, Struts};
+use window_description::{DescriptionSupport, WindowDescription};
 
 /// Type alias for automated tests
 pub type WMName = FullscreenWM<MinimisingWM>;
@@ -36,11 +43,14 @@ pub type WMName = FullscreenWM<MinimisingWM>;
 /// Main struct of the window manager
 /// This WM can make a window fullscreen and uses the WrappedWM for all other windows
 #[derive(RustcDecodable, RustcEncodable, Debug, Clone)]
+#[cfg_attr(feature = "with-serde", derive(Serialize, Deserialize))]
 pub struct FullscreenWM<WrappedWM: RealWindowInfo> {
     /// The WindowWithInfo for the fullscreen window (the real one)
     pub fullscreen_window: Option<WindowWithInfo>,
     /// The wrapped window manager that takes care of all the other windows
     pub wrapped_wm: WrappedWM,
+    /// Whether the fullscreen window covers the reserved edges of the screen (struts) or not
+    pub cover_struts: bool,
 }
 
 impl<WrappedWM: RealWindowInfo> FullscreenWM<WrappedWM> {
@@ -52,6 +62,20 @@ impl<WrappedWM: RealWindowInfo> FullscreenWM<WrappedWM> {
         self.fullscreen_window = None;
     }
 
+    /// Return the geometry of the fullscreen window
+    fn get_fullscreen_geometry(&self) -> Geometry {
+        if self.cover_struts {
+            self.get_screen().to_geometry()
+        } else {
+            self.wrapped_wm.get_usable_area()
+        }
+    }
+
+    /// Let the fullscreen window cover the reserved edges of the screen (the default) or not
+    pub fn set_cover_struts(&mut self, cover_struts: bool) {
+        self.cover_struts = cover_struts;
+    }
+
     /// Returns true if the given window is fullscreen. Will return false if the given window is not managed by this wm
     fn is_fullscreen(&self, window: Window) -> bool {
         self.get_fullscreen_window()
@@ -68,6 +92,7 @@ impl<WrappedWM: RealWindowInfo> WindowManager for FullscreenWM<WrappedWM> {
         FullscreenWM {
             fullscreen_window: None,
             wrapped_wm: WrappedWM::new(screen),
+            cover_struts: true,
         }
     }
 
@@ -111,7 +136,7 @@ impl<WrappedWM: RealWindowInfo> WindowManager for FullscreenWM<WrappedWM> {
         self.get_fullscreen_window()
             .map(|w| {
                 WindowLayout {
-                    windows: vec![(w, self.get_screen().to_geometry())],
+                    windows: vec![(w, self.get_fullscreen_geometry())],
                     focused_window: Some(w),
                 }
             })
@@ -119,11 +144,15 @@ impl<WrappedWM: RealWindowInfo> WindowManager for FullscreenWM<WrappedWM> {
     }
 
     /// If the new focused window is not the fullscreen window, the fullscreen window will be un-fullscreened
+    /// (unless the new focused window is not managed)
     fn focus_window(&mut self, window: Option<Window>) -> Result<(), Self::Error> {
         // If the focus is a new window, remove the fullscreenness
         if window.map(|w| self.is_fullscreen(w)).unwrap_or(false) {
             // Wants to focus the fullscreen window
             Ok(())
+        } else if window.map(|w| !self.is_managed(w)).unwrap_or(false) {
+            // The wrapped wm returns the error
+            self.wrapped_wm.focus_window(window)
         } else {
             self.un_fullscreen();
             self.wrapped_wm.focus_window(window)
@@ -141,7 +170,7 @@ impl<WrappedWM: RealWindowInfo> WindowManager for FullscreenWM<WrappedWM> {
             Ok(self.fullscreen_window
                .map(|info| WindowWithInfo {
                    window: info.window,
-                   geometry: self.get_screen().to_geometry(),
+                   geometry: self.get_fullscreen_geometry(),
                    float_or_tile: info.float_or_tile,
                    fullscreen: true,
                })
@@ -267,6 +296,8 @@ impl<WrappedWM: RealWindowInfo> FullscreenSupport for FullscreenWM<WrappedWM> {
             self.wrapped_wm.add_window(wi)
         } else {
             let wi = try!(self.wrapped_wm.get_real_window_info(window));
+            // There can only be one fullscreen window
+            self.un_fullscreen();
             self.fullscreen_window = Some(wi);
 
             self.wrapped_wm.remove_window(window)
@@ -284,14 +315,139 @@ impl<WrappedWM: GapSupport + RealWindowInfo> GapSupport for FullscreenWM<Wrapped
     }
 }
 
+impl<WrappedWM: ExtendedGapSupport + RealWindowInfo> ExtendedGapSupport for FullscreenWM<WrappedWM> {
+    fn get_inner_gap(&self) -> GapSize {
+        self.wrapped_wm.get_inner_gap()
+    }
+
+    fn set_inner_gap(&mut self, gap: GapSize) {
+        self.wrapped_wm.set_inner_gap(gap)
+    }
+
+    fn get_outer_gaps(&self) -> OuterGaps {
+        self.wrapped_wm.get_outer_gaps()
+    }
+
+    fn set_outer_gaps(&mut self, gaps: OuterGaps) {
+        self.wrapped_wm.set_outer_gaps(gaps)
+    }
+
+    fn get_smart_gaps(&self) -> bool {
+        self.wrapped_wm.get_smart_gaps()
+    }
+
+    fn set_smart_gaps(&mut self, smart_gaps: bool) {
+        self.wrapped_wm.set_smart_gaps(smart_gaps)
+    }
+
+    fn get_min_window_size(&self) -> c_uint {
+        self.wrapped_wm.get_min_window_size()
+    }
+
+    fn set_min_window_size(&mut self, size: c_uint) {
+        self.wrapped_wm.set_min_window_size(size)
+    }
+
+    fn try_set_gap(&mut self, gapsize: GapSize) -> Result<(), Self::Error> {
+        self.wrapped_wm.try_set_gap(gapsize)
+    }
+}
+
+impl<WrappedWM: LayoutSupport + RealWindowInfo> LayoutSupport for FullscreenWM<WrappedWM> {
+    fn get_layout(&self) -> Layout {
+        self.wrapped_wm.get_layout()
+    }
+
+    fn set_layout(&mut self, layout: Layout) {
+        self.wrapped_wm.set_layout(layout)
+    }
+}
+
+impl<WrappedWM: StrutSupport + RealWindowInfo> StrutSupport for FullscreenWM<WrappedWM> {
+    fn get_struts(&self) -> Struts {
+        self.wrapped_wm.get_struts()
+    }
+
+    fn set_struts(&mut self, struts: Struts) {
+        self.wrapped_wm.set_struts(struts)
+    }
+
+    fn add_dock(&mut self, window: Window, struts: Struts) {
+        self.wrapped_wm.add_dock(window, struts)
+    }
+
+    fn remove_dock(&mut self, window: Window) -> Result<(), Self::Error> {
+        self.wrapped_wm.remove_dock(window)
+    }
+
+    fn get_docks(&self) -> Vec<Window> {
+        self.wrapped_wm.get_docks()
+    }
+
+    fn copy_struts(&mut self, other: &FullscreenWM<WrappedWM>) {
+        self.wrapped_wm.copy_struts(&other.wrapped_wm)
+    }
+}
+
+impl<WrappedWM: DescriptionSupport + RealWindowInfo> DescriptionSupport for FullscreenWM<WrappedWM> {
+    /// Removes the current fullscreen window if any, unless the new window is a dock
+    /// (or the window was already added)
+    fn add_window_with_description(&mut self, description: WindowDescription) -> Result<(), Self::Error> {
+        if description.is_dock() {
+            return self.wrapped_wm.add_window_with_description(description);
+        }
+        if self.is_managed(description.info.window) {
+            return Ok(());
+        }
+
+        self.un_fullscreen();
+
+        if description.info.fullscreen {
+            // Remember whether the window floats for when it's not fullscreen anymore
+            let mut info = description.info;
+            if description.should_float() {
+                info.float_or_tile = FloatOrTile::Float;
+            }
+
+            self.fullscreen_window = Some(info);
+            Ok(())
+        } else {
+            self.wrapped_wm.add_window_with_description(description)
+        }
+    }
+}
+
+impl<WrappedWM: DirectionSupport + RealWindowInfo> DirectionSupport for FullscreenWM<WrappedWM> {
+    /// This will unfullscreen
+    fn focus_direction(&mut self, dir: Direction) {
+        self.un_fullscreen();
+
+        self.wrapped_wm.focus_direction(dir)
+    }
+
+    /// This will unfullscreen
+    fn swap_direction(&mut self, dir: Direction) {
+        self.un_fullscreen();
+
+        self.wrapped_wm.swap_direction(dir)
+    }
+}
+
 impl<WrappedWM: RealWindowInfo> RealWindowInfo for FullscreenWM<WrappedWM> {
     fn get_real_window_info(&self, window: Window) -> Result<WindowWithInfo, Self::Error> {
         if self.is_fullscreen(window) {
-            Ok(self.fullscreen_window.unwrap())
+            // The stored info has the geometry from before the window became fullscreen
+            let mut wi = self.fullscreen_window.unwrap();
+            wi.fullscreen = true;
+            Ok(wi)
         } else {
             self.wrapped_wm.get_real_window_info(window)
         }
     }
+
+    fn get_usable_area(&self) -> Geometry {
+        self.wrapped_wm.get_usable_area()
+    }
 }
 
 #[cfg(test)]
@@ -307,10 +463,15 @@ mod tests {
     pub use cplwm_api::types::*;
     pub use cplwm_api::types::PrevOrNext::*;
     pub use cplwm_api::types::FloatOrTile::*;
+    pub use direction::*;
+    pub use struts::*;
+    pub use window_description::*;
 
     // Import expectest names
     pub use expectest::prelude::*;
 
+    conformance_tests!(WMName: window_manager, tiling, floating, minimising, fullscreen, gap);
+
     describe! wm {
         before_each {
             let screen: Screen = Screen {
@@ -734,6 +895,15 @@ mod tests {
                 expect!(wm.focus_window(Some(100))).to(be_err());
             }
 
+            it "should keep the fullscreen window if the window to focus is unknown" {
+                wm.add_window(WindowWithInfo::new_fullscreen(7, some_geom)).unwrap();
+
+                expect!(wm.focus_window(Some(100))).to(be_err());
+
+                expect!(wm.get_fullscreen_window()).to(be_equal_to(Some(7)));
+                expect!(wm.get_focused_window()).to(be_equal_to(Some(7)));
+            }
+
             it "should bring the focused window to the front" {
                 wm.focus_window(Some(3)).unwrap();
 
@@ -752,6 +922,18 @@ mod tests {
                 expect!(wm.get_focused_window()).to(be_equal_to(Some(6)));
             }
 
+            it "should unminimize a minimized fullscreen window as a normal window before focussing" {
+                wm.add_window(WindowWithInfo::new_fullscreen(7, some_geom)).unwrap();
+                wm.toggle_minimised(7).unwrap();
+
+                wm.focus_window(Some(7)).unwrap();
+
+                expect!(wm.is_minimised(7)).to(be_false());
+                expect!(wm.get_fullscreen_window()).to(be_equal_to(None));
+                expect!(wm.get_focused_window()).to(be_equal_to(Some(7)));
+                expect!(wm.get_real_window_info(7).unwrap().fullscreen).to(be_false());
+            }
+
             it "should keep the focus if it wants to refocus on the fullscreen window" {
                 wm.add_window(WindowWithInfo::new_fullscreen(7, some_geom)).unwrap();
 
@@ -1662,6 +1844,21 @@ mod tests {
 
                     expect!(wm.get_fullscreen_window()).to(be_equal_to(Some(2)));
                 }
+
+                it "should keep managing the previous fullscreen window" {
+                    wm.toggle_fullscreen(1).unwrap();
+                    wm.toggle_fullscreen(2).unwrap();
+
+                    expect!(wm.is_managed(1)).to(be_true());
+                    expect!(wm.get_windows()).to(be_equal_to(vec![3, 1, 5, 2]));
+
+                    wm.toggle_fullscreen(2).unwrap();
+
+                    expect!(wm.get_window_layout().windows).to(be_equal_to(vec![(3, left_half),
+                                                                              (1, right_upper_quarter),
+                                                                              (2, right_lower_quarter),
+                                                                              (5, floating_geom)]));
+                }
             }
 
             describe! toggle_fullscreen {
@@ -1701,6 +1898,107 @@ mod tests {
                 expect!(wm.get_window_layout().windows).to(be_equal_to(vec![(2, screen_geom)]));
             }
         }
+
+        describe! strut_support {
+            before_each {
+                wm.add_window(WindowWithInfo::new_tiled(1, some_geom)).unwrap();
+                wm.set_struts(Struts { top: 20, bottom: 0, left: 0, right: 0 });
+
+                let usable_area = Geometry { x: 0, y: 20, width: 800, height: 580 };
+            }
+
+            it "should keep tiled windows out of the reserved edges" {
+                expect!(wm.get_window_layout().windows).to(be_equal_to(vec![(1, usable_area)]));
+            }
+
+            it "should let a fullscreen window cover the reserved edges by default" {
+                wm.toggle_fullscreen(1).unwrap();
+
+                expect!(wm.get_window_layout().windows).to(be_equal_to(vec![(1, screen_geom)]));
+                expect!(wm.get_window_info(1).unwrap().geometry).to(be_equal_to(screen_geom));
+            }
+
+            it "should be able to keep a fullscreen window out of the reserved edges" {
+                wm.set_cover_struts(false);
+                wm.toggle_fullscreen(1).unwrap();
+
+                expect!(wm.get_window_layout().windows).to(be_equal_to(vec![(1, usable_area)]));
+                expect!(wm.get_window_info(1).unwrap().geometry).to(be_equal_to(usable_area));
+            }
+
+            it "should let a dock reserve edges while a window is fullscreen" {
+                wm.set_cover_struts(false);
+                wm.toggle_fullscreen(1).unwrap();
+
+                wm.add_dock(10, Struts { top: 0, bottom: 30, left: 0, right: 0 });
+
+                expect!(wm.get_fullscreen_window()).to(be_equal_to(Some(1)));
+                let geom = Geometry { x: 0, y: 20, width: 800, height: 550 };
+                expect!(wm.get_window_layout().windows).to(be_equal_to(vec![(1, geom)]));
+            }
+        }
+
+        describe! direction_support {
+            before_each {
+                wm.add_window(WindowWithInfo::new_tiled(1, some_geom)).unwrap();
+                wm.add_window(WindowWithInfo::new_tiled(2, some_geom)).unwrap();
+                wm.toggle_fullscreen(2).unwrap();
+            }
+
+            it "should unfullscreen when focusing in a direction" {
+                wm.focus_direction(Direction::Left);
+
+                expect!(wm.get_fullscreen_window()).to(be_equal_to(None));
+                expect!(wm.get_focused_window()).to(be_equal_to(Some(1)));
+            }
+
+            it "should unfullscreen when swapping in a direction" {
+                wm.swap_direction(Direction::Left);
+
+                expect!(wm.get_fullscreen_window()).to(be_equal_to(None));
+                expect!(wm.get_focused_window()).to(be_equal_to(Some(2)));
+                expect!(wm.get_window_layout().windows).to(be_equal_to(vec![(2, left_half), (1, right_half)]));
+            }
+        }
+
+        describe! description_support {
+            before_each {
+                wm.add_window(WindowWithInfo::new_tiled(1, some_geom)).unwrap();
+                wm.toggle_fullscreen(1).unwrap();
+            }
+
+            it "should unfullscreen when adding a dialog" {
+                let mut description = WindowDescription::new(WindowWithInfo::new_tiled(2, floating_geom));
+                description.window_type = WindowType::Dialog;
+
+                wm.add_window_with_description(description).unwrap();
+
+                expect!(wm.get_fullscreen_window()).to(be_equal_to(None));
+                expect!(wm.get_floating_windows()).to(be_equal_to(vec![2]));
+                expect!(wm.get_focused_window()).to(be_equal_to(Some(2)));
+            }
+
+            it "should not unfullscreen when adding a dock" {
+                let struts = Struts { top: 20, bottom: 0, left: 0, right: 0 };
+
+                wm.add_window_with_description(WindowDescription::new_dock(WindowWithInfo::new_tiled(10, some_geom), struts)).unwrap();
+
+                expect!(wm.get_fullscreen_window()).to(be_equal_to(Some(1)));
+                expect!(wm.get_docks()).to(be_equal_to(vec![10]));
+                expect!(wm.get_window_layout().windows).to(be_equal_to(vec![(1, screen_geom)]));
+            }
+
+            it "should remember that a fullscreen dialog floats" {
+                let mut description = WindowDescription::new(WindowWithInfo::new_fullscreen(2, floating_geom));
+                description.window_type = WindowType::Dialog;
+
+                wm.add_window_with_description(description).unwrap();
+                expect!(wm.get_fullscreen_window()).to(be_equal_to(Some(2)));
+                wm.toggle_fullscreen(2).unwrap();
+
+                expect!(wm.get_floating_windows()).to(be_equal_to(vec![2]));
+            }
+        }
     }
 
     describe! integration_test {
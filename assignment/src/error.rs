@@ -3,9 +3,14 @@
 use std::error;
 use std::error::Error;
 use std::fmt;
+use std::io;
+
+use rustc_serialize::json::{DecoderError, EncoderError};
 use std::convert::From;
 
-use cplwm_api::types::{Window, WorkspaceIndex};
+use cplwm_api::types::{GapSize, Window, WorkspaceIndex};
+
+use trace::Divergence;
 
 /// The errors that a window manager can return.
 ///
@@ -14,12 +19,15 @@ use cplwm_api::types::{Window, WorkspaceIndex};
 pub enum WMError {
     /// This window is not known by the window manager.
     UnknownWindow(Window),
+    /// The gap doesn't fit in every tile without making it smaller than the minimum window size.
+    GapTooLarge(GapSize),
 }
 
 impl fmt::Display for WMError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
             WMError::UnknownWindow(ref window) => write!(f, "Unknown window: {}", window),
+            WMError::GapTooLarge(ref gap) => write!(f, "Gap too large: {}", gap),
         }
     }
 }
@@ -28,6 +36,7 @@ impl error::Error for WMError {
     fn description(&self) -> &'static str {
         match *self {
             WMError::UnknownWindow(_) => "Unknown window",
+            WMError::GapTooLarge(_) => "Gap too large",
         }
     }
 }
@@ -39,6 +48,12 @@ impl error::Error for WMError {
 pub enum MultiWMError<MyError: Error + 'static> {
     /// This workspace index is unknown
     UnknownWorkspace(WorkspaceIndex),
+    /// The last workspace cannot be removed
+    LastWorkspace,
+    /// This output index is unknown
+    UnknownOutput(usize),
+    /// The last output cannot be removed
+    LastOutput,
     /// An error from the wrapped WM
     WrappedError(MyError),
 }
@@ -47,6 +62,9 @@ impl<MyError: Error + 'static> fmt::Display for MultiWMError<MyError> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
             MultiWMError::UnknownWorkspace(ref index) => write!(f, "Unknown index: {}", index),
+            MultiWMError::LastWorkspace => write!(f, "Cannot remove the last workspace"),
+            MultiWMError::UnknownOutput(ref index) => write!(f, "Unknown output: {}", index),
+            MultiWMError::LastOutput => write!(f, "Cannot remove the last output"),
             MultiWMError::WrappedError(ref error) => fmt::Display::fmt(&error, f),
         }
     }
@@ -56,6 +74,9 @@ impl<MyError: Error + 'static> error::Error for MultiWMError<MyError> {
     fn description(&self) -> &'static str {
         match *self {
             MultiWMError::UnknownWorkspace(_) => "Unknown index",
+            MultiWMError::LastWorkspace => "Cannot remove the last workspace",
+            MultiWMError::UnknownOutput(_) => "Unknown output",
+            MultiWMError::LastOutput => "Cannot remove the last output",
             MultiWMError::WrappedError(_) => "An error occurred in the wrapped workspace",
         }
     }
@@ -66,3 +87,220 @@ impl<MyError: Error + 'static> From<MyError> for MultiWMError<MyError> {
         MultiWMError::WrappedError(error)
     }
 }
+
+/// The errors that can occur when parsing a command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// The command is empty
+    EmptyCommand,
+    /// This command is unknown
+    UnknownCommand(String),
+    /// The command needs another argument
+    MissingArgument(String),
+    /// This argument is not valid for the command
+    InvalidArgument(String),
+    /// The command has too many arguments
+    TooManyArguments(String),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ParseError::EmptyCommand => write!(f, "Empty command"),
+            ParseError::UnknownCommand(ref command) => write!(f, "Unknown command: {}", command),
+            ParseError::MissingArgument(ref command) => write!(f, "Missing argument for: {}", command),
+            ParseError::InvalidArgument(ref argument) => write!(f, "Invalid argument: {}", argument),
+            ParseError::TooManyArguments(ref command) => write!(f, "Too many arguments for: {}", command),
+        }
+    }
+}
+
+impl error::Error for ParseError {
+    fn description(&self) -> &'static str {
+        match *self {
+            ParseError::EmptyCommand => "Empty command",
+            ParseError::UnknownCommand(_) => "Unknown command",
+            ParseError::MissingArgument(_) => "Missing argument",
+            ParseError::InvalidArgument(_) => "Invalid argument",
+            ParseError::TooManyArguments(_) => "Too many arguments",
+        }
+    }
+}
+
+/// The errors that can occur when running a command.
+#[derive(Debug)]
+pub enum CommandError<MyError: Error + 'static> {
+    /// The command couldn't be parsed
+    ParseError(ParseError),
+    /// The command needs a window but no window was given and no window is focused
+    NoFocusedWindow,
+    /// An error from the window manager (e.g. a `WMError` or a `MultiWMError`)
+    WrappedError(MyError),
+}
+
+impl<MyError: Error + 'static> fmt::Display for CommandError<MyError> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            CommandError::ParseError(ref error) => fmt::Display::fmt(&error, f),
+            CommandError::NoFocusedWindow => write!(f, "No focused window"),
+            CommandError::WrappedError(ref error) => fmt::Display::fmt(&error, f),
+        }
+    }
+}
+
+impl<MyError: Error + 'static> error::Error for CommandError<MyError> {
+    fn description(&self) -> &'static str {
+        match *self {
+            CommandError::ParseError(_) => "The command couldn't be parsed",
+            CommandError::NoFocusedWindow => "No focused window",
+            CommandError::WrappedError(_) => "An error occurred in the window manager",
+        }
+    }
+}
+
+impl<MyError: Error + 'static> From<MyError> for CommandError<MyError> {
+    fn from(error: MyError) -> CommandError<MyError> {
+        CommandError::WrappedError(error)
+    }
+}
+
+/// The errors that an IPC client can get.
+#[derive(Debug)]
+pub enum IpcError {
+    /// Reading from or writing to the socket failed
+    Io(io::Error),
+    /// The server sent a response that isn't valid
+    InvalidResponse(String),
+    /// The server couldn't handle the request
+    ServerError(String),
+}
+
+impl fmt::Display for IpcError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            IpcError::Io(ref error) => fmt::Display::fmt(&error, f),
+            IpcError::InvalidResponse(ref response) => write!(f, "Invalid response: {}", response),
+            IpcError::ServerError(ref error) => write!(f, "Server error: {}", error),
+        }
+    }
+}
+
+impl error::Error for IpcError {
+    fn description(&self) -> &'static str {
+        match *self {
+            IpcError::Io(_) => "An IO error occurred",
+            IpcError::InvalidResponse(_) => "Invalid response",
+            IpcError::ServerError(_) => "Server error",
+        }
+    }
+}
+
+impl From<io::Error> for IpcError {
+    fn from(error: io::Error) -> IpcError {
+        IpcError::Io(error)
+    }
+}
+
+/// The errors that can occur when saving or restoring a session.
+#[derive(Debug)]
+pub enum SessionError {
+    /// Reading or writing the session file failed
+    Io(io::Error),
+    /// The state of the window manager couldn't be encoded
+    EncodeError(EncoderError),
+    /// The session file doesn't contain a valid state
+    DecodeError(DecoderError),
+    /// The restored state couldn't be cleaned up (e.g. a window couldn't be pruned)
+    InvalidState(String),
+    /// The session file was written by a newer version of the window manager
+    UnsupportedVersion(u64),
+}
+
+impl fmt::Display for SessionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SessionError::Io(ref error) => fmt::Display::fmt(&error, f),
+            SessionError::EncodeError(ref error) => fmt::Display::fmt(&error, f),
+            SessionError::DecodeError(ref error) => fmt::Display::fmt(&error, f),
+            SessionError::InvalidState(ref message) => write!(f, "Invalid state: {}", message),
+            SessionError::UnsupportedVersion(version) => write!(f, "Unsupported session version: {}", version),
+        }
+    }
+}
+
+impl error::Error for SessionError {
+    fn description(&self) -> &'static str {
+        match *self {
+            SessionError::Io(_) => "An IO error occurred",
+            SessionError::EncodeError(_) => "The state couldn't be encoded",
+            SessionError::DecodeError(_) => "The state couldn't be decoded",
+            SessionError::InvalidState(_) => "Invalid state",
+            SessionError::UnsupportedVersion(_) => "Unsupported session version",
+        }
+    }
+}
+
+impl From<io::Error> for SessionError {
+    fn from(error: io::Error) -> SessionError {
+        SessionError::Io(error)
+    }
+}
+
+impl From<EncoderError> for SessionError {
+    fn from(error: EncoderError) -> SessionError {
+        SessionError::EncodeError(error)
+    }
+}
+
+impl From<DecoderError> for SessionError {
+    fn from(error: DecoderError) -> SessionError {
+        SessionError::DecodeError(error)
+    }
+}
+
+/// The errors that can occur when recording or replaying a trace.
+#[derive(Debug)]
+pub enum TraceError {
+    /// Reading or writing the trace file failed
+    Io(io::Error),
+    /// A line of the trace file isn't a valid entry
+    DecodeError(DecoderError),
+    /// The trace can't be replayed (e.g. it doesn't start with the creation of the window manager)
+    InvalidTrace(String),
+    /// The replayed window manager has another layout than the recorded one
+    Diverged(Divergence),
+}
+
+impl fmt::Display for TraceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            TraceError::Io(ref error) => fmt::Display::fmt(&error, f),
+            TraceError::DecodeError(ref error) => fmt::Display::fmt(&error, f),
+            TraceError::InvalidTrace(ref message) => write!(f, "Invalid trace: {}", message),
+            TraceError::Diverged(ref divergence) => fmt::Display::fmt(&divergence, f),
+        }
+    }
+}
+
+impl error::Error for TraceError {
+    fn description(&self) -> &'static str {
+        match *self {
+            TraceError::Io(_) => "An IO error occurred",
+            TraceError::DecodeError(_) => "The trace couldn't be decoded",
+            TraceError::InvalidTrace(_) => "Invalid trace",
+            TraceError::Diverged(_) => "The replay diverged from the trace",
+        }
+    }
+}
+
+impl From<io::Error> for TraceError {
+    fn from(error: io::Error) -> TraceError {
+        TraceError::Io(error)
+    }
+}
+
+impl From<DecoderError> for TraceError {
+    fn from(error: DecoderError) -> TraceError {
+        TraceError::DecodeError(error)
+    }
+}
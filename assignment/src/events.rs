@@ -0,0 +1,807 @@
+//! This module provides events for the changes of the state of a window manager
+//!
+//! The `EventWM` wraps a window manager and compares a `Snapshot` of the wrapped window manager before and after
+//! every call that can change it. For every change an `Event` is sent to the subscribers.
+//!
+//! Only the calls that go through the `EventWM` are observed: changes made to a workspace through
+//! `MultiWorkspaceSupport::get_workspace_mut` don't send events.
+//!
+//! Subscribers are registered in-process: a `Sender<Event>` (see `EventWM::subscribe`) or anything else that
+//! implements `Subscriber`, e.g. a `JsonLinesWriter` that writes every event as a line of JSON for external consumers.
+//!
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::io::Write;
+use std::os::raw::c_uint;
+use std::sync::mpsc::{channel, Receiver, Sender};
+
+use rustc_serialize::{Decodable, Decoder, Encodable, Encoder};
+use rustc_serialize::json::{Json, ToJson};
+#[cfg(feature = "with-serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use cplwm_api::types::{GapSize, Geometry, PrevOrNext, Screen, Window, WindowLayout, WindowWithInfo, WorkspaceIndex};
+use cplwm_api::wm::{FloatSupport, FullscreenSupport, GapSupport, MinimiseSupport, MultiWorkspaceSupport, TilingSupport, WindowManager};
+
+use g_multiple_workspaces::{MoveWindowSupport, WMName as WorkspaceWM};
+use direction::{Direction, DirectionSupport};
+use f_gaps::{ExtendedGapSupport, OuterGaps};
+use layouts::{Layout, LayoutSupport};
+use struts::{StrutSupport, Struts};
+use window_description::{DescriptionSupport, WindowDescription};
+
+/// Type alias for automated tests
+pub type WMName = EventWM<WorkspaceWM>;
+
+/// A change of the state of a window manager
+#[derive(RustcDecodable, RustcEncodable, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    /// A window was added
+    WindowAdded(Window),
+    /// A window was removed
+    WindowRemoved(Window),
+    /// Another window (or no window) is focused
+    FocusChanged(Option<Window>),
+    /// Another workspace is the current workspace
+    WorkspaceSwitched(WorkspaceIndex),
+    /// The window started floating (true) or is tiled again (false)
+    FloatingToggled(Window, bool),
+    /// The window was minimised
+    Minimised(Window),
+    /// The window is not minimised anymore
+    Restored(Window),
+    /// The window became fullscreen (true) or is not fullscreen anymore (false)
+    FullscreenToggled(Window, bool),
+    /// The gap of the current workspace changed (see `GapSupport::get_gap`)
+    GapChanged(GapSize),
+    /// The space between two tiles of the current workspace changed
+    InnerGapChanged(GapSize),
+    /// The gaps at the edges of the screen of the current workspace changed
+    OuterGapsChanged(OuterGaps),
+    /// Smart gaps were turned on (true) or off (false) for the current workspace
+    SmartGapsToggled(bool),
+    /// The current workspace uses another layout
+    LayoutChanged(Layout),
+    /// The screen was resized
+    ScreenResized(Screen),
+}
+
+/// Events are written as a JSON object with the name of the event, e.g. `{"event":"window_added","window":1}`
+impl ToJson for Event {
+    fn to_json(&self) -> Json {
+        let mut object = BTreeMap::new();
+        let name = match *self {
+            Event::WindowAdded(window) => {
+                object.insert(String::from("window"), window.to_json());
+                "window_added"
+            }
+            Event::WindowRemoved(window) => {
+                object.insert(String::from("window"), window.to_json());
+                "window_removed"
+            }
+            Event::FocusChanged(window) => {
+                object.insert(String::from("window"), window.to_json());
+                "focus_changed"
+            }
+            Event::WorkspaceSwitched(index) => {
+                object.insert(String::from("workspace"), index.to_json());
+                "workspace_switched"
+            }
+            Event::FloatingToggled(window, floating) => {
+                object.insert(String::from("window"), window.to_json());
+                object.insert(String::from("floating"), floating.to_json());
+                "floating_toggled"
+            }
+            Event::Minimised(window) => {
+                object.insert(String::from("window"), window.to_json());
+                "minimised"
+            }
+            Event::Restored(window) => {
+                object.insert(String::from("window"), window.to_json());
+                "restored"
+            }
+            Event::FullscreenToggled(window, fullscreen) => {
+                object.insert(String::from("window"), window.to_json());
+                object.insert(String::from("fullscreen"), fullscreen.to_json());
+                "fullscreen_toggled"
+            }
+            Event::GapChanged(gap) => {
+                object.insert(String::from("gap"), gap.to_json());
+                "gap_changed"
+            }
+            Event::InnerGapChanged(gap) => {
+                object.insert(String::from("gap"), gap.to_json());
+                "inner_gap_changed"
+            }
+            Event::OuterGapsChanged(gaps) => {
+                object.insert(String::from("top"), gaps.top.to_json());
+                object.insert(String::from("bottom"), gaps.bottom.to_json());
+                object.insert(String::from("left"), gaps.left.to_json());
+                object.insert(String::from("right"), gaps.right.to_json());
+                "outer_gaps_changed"
+            }
+            Event::SmartGapsToggled(smart_gaps) => {
+                object.insert(String::from("smart_gaps"), smart_gaps.to_json());
+                "smart_gaps_toggled"
+            }
+            Event::LayoutChanged(layout) => {
+                object.insert(String::from("layout"), Json::String(String::from(layout.name())));
+                "layout_changed"
+            }
+            Event::ScreenResized(screen) => {
+                object.insert(String::from("width"), screen.width.to_json());
+                object.insert(String::from("height"), screen.height.to_json());
+                "screen_resized"
+            }
+        };
+        object.insert(String::from("event"), Json::String(String::from(name)));
+
+        Json::Object(object)
+    }
+}
+
+/// The state of a window manager that is observed for events.
+///
+/// The sets of windows contain the windows of all workspaces, not just the current one. The gaps and the layout are
+/// the ones of the current workspace.
+#[derive(RustcDecodable, RustcEncodable, Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    /// All managed windows
+    pub windows: BTreeSet<Window>,
+    /// The focused window
+    pub focused_window: Option<Window>,
+    /// The index of the current workspace
+    pub workspace: WorkspaceIndex,
+    /// The floating windows
+    pub floating_windows: BTreeSet<Window>,
+    /// The minimised windows
+    pub minimised_windows: BTreeSet<Window>,
+    /// The fullscreen windows (one per workspace at most)
+    pub fullscreen_windows: BTreeSet<Window>,
+    /// The gap
+    pub gap: GapSize,
+    /// The space between two tiles
+    pub inner_gap: GapSize,
+    /// The gaps at the edges of the screen
+    pub outer_gaps: OuterGaps,
+    /// Whether smart gaps are on
+    pub smart_gaps: bool,
+    /// The layout
+    pub layout: Layout,
+    /// The screen
+    pub screen: Screen,
+}
+
+impl Snapshot {
+    /// Return the events that lead from this snapshot to the new snapshot.
+    ///
+    /// Only windows that are in both snapshots can be toggled (floating, minimised or fullscreen). The gaps and the
+    /// layout are only compared when the current workspace didn't change: every workspace has its own, switching to
+    /// a workspace with other gaps doesn't change them.
+    pub fn diff(&self, new: &Snapshot) -> Vec<Event> {
+        let mut events = Vec::new();
+
+        events.extend(self.windows.difference(&new.windows).map(|w| Event::WindowRemoved(*w)));
+        events.extend(new.windows.difference(&self.windows).map(|w| Event::WindowAdded(*w)));
+
+        if self.workspace != new.workspace {
+            events.push(Event::WorkspaceSwitched(new.workspace));
+        }
+
+        let in_both = |w: &&Window| self.windows.contains(w) && new.windows.contains(w);
+        for window in self.floating_windows.symmetric_difference(&new.floating_windows).filter(&in_both) {
+            events.push(Event::FloatingToggled(*window, new.floating_windows.contains(window)));
+        }
+        for window in self.minimised_windows.symmetric_difference(&new.minimised_windows).filter(&in_both) {
+            events.push(if new.minimised_windows.contains(window) {
+                Event::Minimised(*window)
+            } else {
+                Event::Restored(*window)
+            });
+        }
+        for window in self.fullscreen_windows.symmetric_difference(&new.fullscreen_windows).filter(&in_both) {
+            events.push(Event::FullscreenToggled(*window, new.fullscreen_windows.contains(window)));
+        }
+
+        if self.focused_window != new.focused_window {
+            events.push(Event::FocusChanged(new.focused_window));
+        }
+        if self.workspace == new.workspace {
+            if self.gap != new.gap {
+                events.push(Event::GapChanged(new.gap));
+            }
+            if self.inner_gap != new.inner_gap {
+                events.push(Event::InnerGapChanged(new.inner_gap));
+            }
+            if self.outer_gaps != new.outer_gaps {
+                events.push(Event::OuterGapsChanged(new.outer_gaps));
+            }
+            if self.smart_gaps != new.smart_gaps {
+                events.push(Event::SmartGapsToggled(new.smart_gaps));
+            }
+            if self.layout != new.layout {
+                events.push(Event::LayoutChanged(new.layout));
+            }
+        }
+        if self.screen != new.screen {
+            events.push(Event::ScreenResized(new.screen));
+        }
+
+        events
+    }
+}
+
+/// A window manager of which a snapshot can be taken
+pub trait SnapshotSupport: WindowManager {
+    /// Return the current state of the window manager
+    fn get_snapshot(&self) -> Snapshot;
+}
+
+/// Something that wants to know about the events of a window manager
+pub trait Subscriber {
+    /// Handle an event. Return false if the subscriber doesn't want any more events (e.g. it's closed).
+    fn notify(&mut self, event: &Event) -> bool;
+}
+
+/// The receiver gets all events until it's dropped
+impl Subscriber for Sender<Event> {
+    fn notify(&mut self, event: &Event) -> bool {
+        self.send(*event).is_ok()
+    }
+}
+
+/// Writes every event as a line of JSON (see the `ToJson` implementation of `Event`)
+#[derive(Debug)]
+pub struct JsonLinesWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> JsonLinesWriter<W> {
+    /// Create a writer that writes the events to the given writer
+    pub fn new(writer: W) -> JsonLinesWriter<W> {
+        JsonLinesWriter { writer: writer }
+    }
+
+    /// Return the writer, e.g. to read what was written
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// The writer gets all events until writing fails
+impl<W: Write> Subscriber for JsonLinesWriter<W> {
+    fn notify(&mut self, event: &Event) -> bool {
+        writeln!(self.writer, "{}", event.to_json()).and_then(|_| self.writer.flush()).is_ok()
+    }
+}
+
+/// The subscribers of an `EventWM`.
+///
+/// Subscribers are part of the running process: they're not encoded, a decoded or cloned window manager
+/// starts without subscribers.
+#[derive(Default)]
+pub struct Subscribers {
+    subscribers: Vec<Box<Subscriber + Send>>,
+}
+
+impl Subscribers {
+    /// Send the event to all subscribers and forget the ones that don't want any more events
+    fn notify(&mut self, event: &Event) {
+        let mut i = 0;
+        while i < self.subscribers.len() {
+            if self.subscribers[i].notify(event) {
+                i += 1;
+            } else {
+                self.subscribers.remove(i);
+            }
+        }
+    }
+}
+
+impl fmt::Debug for Subscribers {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Subscribers({})", self.subscribers.len())
+    }
+}
+
+impl Clone for Subscribers {
+    fn clone(&self) -> Subscribers {
+        Subscribers::default()
+    }
+}
+
+impl Encodable for Subscribers {
+    fn encode<S: Encoder>(&self, s: &mut S) -> Result<(), S::Error> {
+        s.emit_nil()
+    }
+}
+
+impl Decodable for Subscribers {
+    fn decode<D: Decoder>(d: &mut D) -> Result<Subscribers, D::Error> {
+        try!(d.read_nil());
+        Ok(Subscribers::default())
+    }
+}
+
+#[cfg(feature = "with-serde")]
+impl Serialize for Subscribers {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_unit()
+    }
+}
+
+#[cfg(feature = "with-serde")]
+impl<'de> Deserialize<'de> for Subscribers {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Subscribers, D::Error> {
+        try!(<()>::deserialize(d));
+        Ok(Subscribers::default())
+    }
+}
+
+/// Main struct of the window manager
+/// This WM sends an event to its subscribers for every change of the WrappedWM
+#[derive(RustcDecodable, RustcEncodable, Debug, Clone)]
+#[cfg_attr(feature = "with-serde", derive(Serialize, Deserialize))]
+pub struct EventWM<WrappedWM: SnapshotSupport> {
+    /// The wrapped window manager
+    pub wrapped_wm: WrappedWM,
+    /// The subscribers
+    pub subscribers: Subscribers,
+}
+
+impl<WrappedWM: SnapshotSupport> EventWM<WrappedWM> {
+    /// Add a subscriber
+    pub fn add_subscriber(&mut self, subscriber: Box<Subscriber + Send>) {
+        self.subscribers.subscribers.push(subscriber);
+    }
+
+    /// Return a receiver that gets all events from now on
+    pub fn subscribe(&mut self) -> Receiver<Event> {
+        let (sender, receiver) = channel();
+        self.add_subscriber(Box::new(sender));
+
+        receiver
+    }
+
+    /// Return the number of subscribers
+    pub fn get_nb_subscribers(&self) -> usize {
+        self.subscribers.subscribers.len()
+    }
+
+    /// Call the function on the wrapped window manager and send the events for the changes it made
+    fn observe<T, F>(&mut self, f: F) -> T
+        where F: FnOnce(&mut WrappedWM) -> T
+    {
+        let before = self.wrapped_wm.get_snapshot();
+        let result = f(&mut self.wrapped_wm);
+        let after = self.wrapped_wm.get_snapshot();
+
+        for event in before.diff(&after) {
+            self.subscribers.notify(&event);
+        }
+
+        result
+    }
+}
+
+impl<WrappedWM: SnapshotSupport> WindowManager for EventWM<WrappedWM> {
+    /// We use the Error from the WrappedWM as our Error type.
+    type Error = WrappedWM::Error;
+
+    fn new(screen: Screen) -> EventWM<WrappedWM> {
+        EventWM {
+            wrapped_wm: WrappedWM::new(screen),
+            subscribers: Subscribers::default(),
+        }
+    }
+
+    fn get_windows(&self) -> Vec<Window> {
+        self.wrapped_wm.get_windows()
+    }
+
+    fn add_window(&mut self, window_with_info: WindowWithInfo) -> Result<(), Self::Error> {
+        self.observe(|wm| wm.add_window(window_with_info))
+    }
+
+    fn remove_window(&mut self, window: Window) -> Result<(), Self::Error> {
+        self.observe(|wm| wm.remove_window(window))
+    }
+
+    fn get_window_layout(&self) -> WindowLayout {
+        self.wrapped_wm.get_window_layout()
+    }
+
+    fn focus_window(&mut self, window: Option<Window>) -> Result<(), Self::Error> {
+        self.observe(|wm| wm.focus_window(window))
+    }
+
+    fn cycle_focus(&mut self, dir: PrevOrNext) {
+        self.observe(|wm| wm.cycle_focus(dir))
+    }
+
+    fn get_window_info(&self, window: Window) -> Result<WindowWithInfo, Self::Error> {
+        self.wrapped_wm.get_window_info(window)
+    }
+
+    fn get_screen(&self) -> Screen {
+        self.wrapped_wm.get_screen()
+    }
+
+    fn resize_screen(&mut self, screen: Screen) {
+        self.observe(|wm| wm.resize_screen(screen))
+    }
+
+    fn get_focused_window(&self) -> Option<Window> {
+        self.wrapped_wm.get_focused_window()
+    }
+
+    fn is_managed(&self, window: Window) -> bool {
+        self.wrapped_wm.is_managed(window)
+    }
+}
+
+impl<WrappedWM: TilingSupport + SnapshotSupport> TilingSupport for EventWM<WrappedWM> {
+    fn get_master_window(&self) -> Option<Window> {
+        self.wrapped_wm.get_master_window()
+    }
+
+    fn swap_with_master(&mut self, window: Window) -> Result<(), Self::Error> {
+        self.observe(|wm| wm.swap_with_master(window))
+    }
+
+    fn swap_windows(&mut self, dir: PrevOrNext) {
+        self.observe(|wm| wm.swap_windows(dir))
+    }
+}
+
+impl<WrappedWM: FloatSupport + SnapshotSupport> FloatSupport for EventWM<WrappedWM> {
+    fn get_floating_windows(&self) -> Vec<Window> {
+        self.wrapped_wm.get_floating_windows()
+    }
+
+    fn toggle_floating(&mut self, window: Window) -> Result<(), Self::Error> {
+        self.observe(|wm| wm.toggle_floating(window))
+    }
+
+    fn set_window_geometry(&mut self, window: Window, new_geometry: Geometry) -> Result<(), Self::Error> {
+        self.observe(|wm| wm.set_window_geometry(window, new_geometry))
+    }
+}
+
+impl<WrappedWM: MinimiseSupport + SnapshotSupport> MinimiseSupport for EventWM<WrappedWM> {
+    fn get_minimised_windows(&self) -> Vec<Window> {
+        self.wrapped_wm.get_minimised_windows()
+    }
+
+    fn toggle_minimised(&mut self, window: Window) -> Result<(), Self::Error> {
+        self.observe(|wm| wm.toggle_minimised(window))
+    }
+}
+
+impl<WrappedWM: FullscreenSupport + SnapshotSupport> FullscreenSupport for EventWM<WrappedWM> {
+    fn get_fullscreen_window(&self) -> Option<Window> {
+        self.wrapped_wm.get_fullscreen_window()
+    }
+
+    fn toggle_fullscreen(&mut self, window: Window) -> Result<(), Self::Error> {
+        self.observe(|wm| wm.toggle_fullscreen(window))
+    }
+}
+
+impl<WrappedWM: GapSupport + SnapshotSupport> GapSupport for EventWM<WrappedWM> {
+    fn get_gap(&self) -> GapSize {
+        self.wrapped_wm.get_gap()
+    }
+
+    fn set_gap(&mut self, gapsize: GapSize) {
+        self.observe(|wm| wm.set_gap(gapsize))
+    }
+}
+
+impl<WrappedWM: ExtendedGapSupport + SnapshotSupport> ExtendedGapSupport for EventWM<WrappedWM> {
+    fn get_inner_gap(&self) -> GapSize {
+        self.wrapped_wm.get_inner_gap()
+    }
+
+    fn set_inner_gap(&mut self, gap: GapSize) {
+        self.observe(|wm| wm.set_inner_gap(gap))
+    }
+
+    fn get_outer_gaps(&self) -> OuterGaps {
+        self.wrapped_wm.get_outer_gaps()
+    }
+
+    fn set_outer_gaps(&mut self, gaps: OuterGaps) {
+        self.observe(|wm| wm.set_outer_gaps(gaps))
+    }
+
+    fn get_smart_gaps(&self) -> bool {
+        self.wrapped_wm.get_smart_gaps()
+    }
+
+    fn set_smart_gaps(&mut self, smart_gaps: bool) {
+        self.observe(|wm| wm.set_smart_gaps(smart_gaps))
+    }
+
+    fn get_min_window_size(&self) -> c_uint {
+        self.wrapped_wm.get_min_window_size()
+    }
+
+    fn set_min_window_size(&mut self, size: c_uint) {
+        self.observe(|wm| wm.set_min_window_size(size))
+    }
+
+    fn try_set_gap(&mut self, gapsize: GapSize) -> Result<(), Self::Error> {
+        self.observe(|wm| wm.try_set_gap(gapsize))
+    }
+}
+
+impl<WrappedWM: LayoutSupport + SnapshotSupport> LayoutSupport for EventWM<WrappedWM> {
+    fn get_layout(&self) -> Layout {
+        self.wrapped_wm.get_layout()
+    }
+
+    fn set_layout(&mut self, layout: Layout) {
+        self.observe(|wm| wm.set_layout(layout))
+    }
+}
+
+impl<WrappedWM: StrutSupport + SnapshotSupport> StrutSupport for EventWM<WrappedWM> {
+    fn get_struts(&self) -> Struts {
+        self.wrapped_wm.get_struts()
+    }
+
+    fn set_struts(&mut self, struts: Struts) {
+        self.observe(|wm| wm.set_struts(struts))
+    }
+
+    fn add_dock(&mut self, window: Window, struts: Struts) {
+        self.observe(|wm| wm.add_dock(window, struts))
+    }
+
+    fn remove_dock(&mut self, window: Window) -> Result<(), Self::Error> {
+        self.observe(|wm| wm.remove_dock(window))
+    }
+
+    fn get_docks(&self) -> Vec<Window> {
+        self.wrapped_wm.get_docks()
+    }
+
+    fn copy_struts(&mut self, other: &EventWM<WrappedWM>) {
+        self.observe(|wm| wm.copy_struts(&other.wrapped_wm))
+    }
+}
+
+impl<WrappedWM: DescriptionSupport + SnapshotSupport> DescriptionSupport for EventWM<WrappedWM> {
+    fn add_window_with_description(&mut self, description: WindowDescription) -> Result<(), Self::Error> {
+        self.observe(|wm| wm.add_window_with_description(description))
+    }
+}
+
+impl<WrappedWM: DirectionSupport + SnapshotSupport> DirectionSupport for EventWM<WrappedWM> {
+    fn focus_direction(&mut self, dir: Direction) {
+        self.observe(|wm| wm.focus_direction(dir))
+    }
+
+    fn swap_direction(&mut self, dir: Direction) {
+        self.observe(|wm| wm.swap_direction(dir))
+    }
+}
+
+impl<WrappedWM: MoveWindowSupport + SnapshotSupport> MoveWindowSupport for EventWM<WrappedWM> {
+    fn move_window_to_workspace(&mut self, window: Window, index: WorkspaceIndex) -> Result<(), Self::Error> {
+        self.observe(|wm| wm.move_window_to_workspace(window, index))
+    }
+
+    fn add_window_to_workspace(&mut self, description: WindowDescription, index: WorkspaceIndex, minimised: bool) -> Result<(), Self::Error> {
+        self.observe(|wm| wm.add_window_to_workspace(description, index, minimised))
+    }
+}
+
+impl<WM, WrappedWM> MultiWorkspaceSupport<WM> for EventWM<WrappedWM>
+    where WM: WindowManager,
+          WrappedWM: MultiWorkspaceSupport<WM> + SnapshotSupport
+{
+    fn get_current_workspace_index(&self) -> WorkspaceIndex {
+        self.wrapped_wm.get_current_workspace_index()
+    }
+
+    fn get_workspace(&self, index: WorkspaceIndex) -> Result<&WM, Self::Error> {
+        self.wrapped_wm.get_workspace(index)
+    }
+
+    /// Changes made through the returned window manager don't send events
+    fn get_workspace_mut(&mut self, index: WorkspaceIndex) -> Result<&mut WM, Self::Error> {
+        self.wrapped_wm.get_workspace_mut(index)
+    }
+
+    fn switch_workspace(&mut self, index: WorkspaceIndex) -> Result<(), Self::Error> {
+        self.observe(|wm| wm.switch_workspace(index))
+    }
+}
+
+impl<WrappedWM: SnapshotSupport> SnapshotSupport for EventWM<WrappedWM> {
+    fn get_snapshot(&self) -> Snapshot {
+        self.wrapped_wm.get_snapshot()
+    }
+}
+
+#[cfg(test)]
+#[allow(unused_mut)]
+#[allow(unused_variables)]
+mod tests {
+    pub use super::*;
+
+    pub use std::sync::mpsc::Receiver;
+    pub use rustc_serialize::json;
+
+    pub use cplwm_api::wm::*;
+    pub use cplwm_api::types::*;
+
+    // Import expectest names
+    pub use expectest::prelude::*;
+
+    conformance_tests!(WMName: window_manager, tiling, floating, minimising, fullscreen, gap, workspaces);
+
+    /// Return the events that were received until now
+    pub fn received(receiver: &Receiver<Event>) -> Vec<Event> {
+        receiver.try_iter().collect()
+    }
+
+    describe! wm {
+        before_each {
+            let screen: Screen = Screen {
+                width: 800,
+                height: 600,
+            };
+
+            let some_geom: Geometry = Geometry {
+                x: 10,
+                y: 10,
+                width: 100,
+                height: 100,
+            };
+
+            let mut wm = WMName::new(screen);
+            wm.add_window(WindowWithInfo::new_tiled(1, some_geom)).unwrap();
+            let events = wm.subscribe();
+        }
+
+        it "should send an event when a window is added or removed" {
+            wm.add_window(WindowWithInfo::new_tiled(2, some_geom)).unwrap();
+            expect!(received(&events)).to(be_equal_to(vec![Event::WindowAdded(2), Event::FocusChanged(Some(2))]));
+
+            wm.remove_window(2).unwrap();
+            expect!(received(&events)).to(be_equal_to(vec![Event::WindowRemoved(2), Event::FocusChanged(Some(1))]));
+        }
+
+        it "should send an event when the focus changes" {
+            wm.focus_window(None).unwrap();
+            wm.focus_window(None).unwrap();
+
+            expect!(received(&events)).to(be_equal_to(vec![Event::FocusChanged(None)]));
+        }
+
+        it "should send an event when switching workspaces" {
+            wm.switch_workspace(1).unwrap();
+
+            expect!(received(&events)).to(be_equal_to(vec![Event::WorkspaceSwitched(1), Event::FocusChanged(None)]));
+        }
+
+        it "should send an event when a window floats or tiles" {
+            wm.toggle_floating(1).unwrap();
+            wm.toggle_floating(1).unwrap();
+
+            expect!(received(&events)).to(be_equal_to(vec![Event::FloatingToggled(1, true), Event::FloatingToggled(1, false)]));
+        }
+
+        it "should send an event when a window is minimised or restored" {
+            wm.toggle_minimised(1).unwrap();
+            wm.toggle_minimised(1).unwrap();
+
+            expect!(received(&events)).to(be_equal_to(vec![Event::Minimised(1),
+                                                           Event::FocusChanged(None),
+                                                           Event::Restored(1),
+                                                           Event::FocusChanged(Some(1))]));
+        }
+
+        it "should send an event when a window becomes fullscreen" {
+            wm.toggle_fullscreen(1).unwrap();
+            wm.add_window(WindowWithInfo::new_tiled(2, some_geom)).unwrap();
+
+            expect!(received(&events)).to(be_equal_to(vec![Event::FullscreenToggled(1, true),
+                                                           Event::WindowAdded(2),
+                                                           Event::FullscreenToggled(1, false),
+                                                           Event::FocusChanged(Some(2))]));
+        }
+
+        it "should send an event when the gap changes" {
+            wm.set_gap(10);
+            wm.set_gap(10);
+
+            expect!(received(&events)).to(be_equal_to(vec![Event::GapChanged(10),
+                                                           Event::InnerGapChanged(20),
+                                                           Event::OuterGapsChanged(OuterGaps::uniform(10))]));
+        }
+
+        it "should send an event when the inner gap, the outer gaps or smart gaps change" {
+            wm.set_inner_gap(4);
+            wm.set_outer_gaps(OuterGaps { top: 20, ..OuterGaps::new() });
+            wm.set_smart_gaps(true);
+
+            expect!(received(&events)).to(be_equal_to(vec![Event::GapChanged(2),
+                                                           Event::InnerGapChanged(4),
+                                                           Event::OuterGapsChanged(OuterGaps { top: 20, ..OuterGaps::new() }),
+                                                           Event::SmartGapsToggled(true)]));
+        }
+
+        it "should send an event when the layout changes" {
+            wm.set_layout(Layout::Spiral);
+            wm.set_layout(Layout::Spiral);
+
+            expect!(received(&events)).to(be_equal_to(vec![Event::LayoutChanged(Layout::Spiral)]));
+        }
+
+        it "should not send gap events when switching between workspaces with different gaps" {
+            wm.set_gap(10);
+            wm.set_layout(Layout::Spiral);
+            received(&events);
+
+            wm.switch_workspace(1).unwrap();
+            wm.switch_workspace(0).unwrap();
+
+            expect!(received(&events)).to(be_equal_to(vec![Event::WorkspaceSwitched(1),
+                                                           Event::FocusChanged(None),
+                                                           Event::WorkspaceSwitched(0),
+                                                           Event::FocusChanged(Some(1))]));
+        }
+
+        it "should send an event when the screen is resized" {
+            let new_screen = Screen { width: 1024, height: 768 };
+            wm.resize_screen(new_screen);
+
+            expect!(received(&events)).to(be_equal_to(vec![Event::ScreenResized(new_screen)]));
+        }
+
+        it "should not send events for a failed call" {
+            expect!(wm.toggle_floating(5)).to(be_err());
+
+            expect!(received(&events)).to(be_equal_to(vec![]));
+        }
+
+        it "should not send events for changes made through get_workspace_mut" {
+            wm.get_workspace_mut(0).unwrap().toggle_floating(1).unwrap();
+
+            expect!(received(&events)).to(be_equal_to(vec![]));
+        }
+
+        it "should forget subscribers that are gone" {
+            drop(events);
+            wm.focus_window(None).unwrap();
+
+            expect!(wm.get_nb_subscribers()).to(be_equal_to(0));
+        }
+
+        it "should write the events as JSON lines" {
+            let mut writer = JsonLinesWriter::new(Vec::new());
+            writer.notify(&Event::WindowAdded(2));
+            writer.notify(&Event::FocusChanged(None));
+            writer.notify(&Event::FloatingToggled(2, true));
+            writer.notify(&Event::LayoutChanged(Layout::Spiral));
+
+            let lines = String::from_utf8(writer.into_inner()).unwrap();
+            expect!(lines).to(be_equal_to(String::from("{\"event\":\"window_added\",\"window\":2}\n\
+                                                        {\"event\":\"focus_changed\",\"window\":null}\n\
+                                                        {\"event\":\"floating_toggled\",\"floating\":true,\"window\":2}\n\
+                                                        {\"event\":\"layout_changed\",\"layout\":\"spiral\"}\n")));
+        }
+
+        it "should encode without its subscribers" {
+            let decoded: WMName = json::decode(&json::encode(&wm).unwrap()).unwrap();
+
+            expect!(decoded.get_nb_subscribers()).to(be_equal_to(0));
+            expect!(decoded.get_windows()).to(be_equal_to(vec![1]));
+        }
+    }
+}
@@ -18,57 +18,263 @@
 //!
 //! The tests were copied from assignment b except for the geometries
 //!
+//! Besides the gap of `GapSupport` the layouter has an inner gap between the tiles and an outer gap for every edge
+//! of the screen, which can be set separately (see `ExtendedGapSupport`). `set_gap(g)` sets an inner gap of `2 * g`
+//! and an outer gap of `g`, the layout of `GapSupport`.
+//!
+//! A gap never makes a tile smaller than the minimum window size: the gaps of a tile that is too small shrink.
+//!
 
-use std::os::raw::c_int;
+use std::os::raw::{c_int, c_uint};
 use cplwm_api::types::{GapSize, Geometry, Screen};
 pub use cplwm_api::types::FloatOrTile::*;
 use cplwm_api::wm::GapSupport;
 
+use error::WMError;
+
+pub use layouter::OuterGaps;
 use layouter::Layouter;
 use layouter::GapSupport as GenericGapSupport;
+use layouter::{LayoutSupport, MasterSupport};
+use layouter::Layout;
 use b_tiling_wm::{SimpleLayouter, TilingWM};
 
 /// Type alias for automated tests
 pub type WMName = TilingWM<GappedLayouter<SimpleLayouter>>;
 
+/// The minimum window size of a new gapped layouter
+pub static DEFAULT_MIN_WINDOW_SIZE: c_uint = 1;
+
+/// A window manager with separate gaps between the tiles and at the edges of the screen.
+///
+/// The inner gap is the space between two tiles, the outer gaps are the spaces between the tiles and the edges of
+/// the screen. With smart gaps a single tiled window gets no gaps at all.
+///
+/// The gaps never make a tile smaller than the minimum window size: the gaps of a tile that is too small shrink,
+/// down to no gaps at all when the tile itself is smaller than the minimum window size. The width and the height of a
+/// tile are handled separately.
+///
+/// The gap of `GapSupport` is the gap around every tile: `set_gap(g)` sets an inner gap of `2 * g` and an outer gap
+/// of `g` on every side, `get_gap()` returns half of the inner gap.
+pub trait ExtendedGapSupport: GapSupport {
+    /// Return the space between two tiles.
+    ///
+    /// Initially 0.
+    fn get_inner_gap(&self) -> GapSize;
+
+    /// Set the space between two tiles.
+    ///
+    /// When the inner gap is odd, the tile on the right or at the bottom gets the larger half.
+    fn set_inner_gap(&mut self, gap: GapSize);
+
+    /// Return the spaces between the tiles and the edges of the screen.
+    ///
+    /// Initially 0 on every side.
+    fn get_outer_gaps(&self) -> OuterGaps;
+
+    /// Set the spaces between the tiles and the edges of the screen.
+    fn set_outer_gaps(&mut self, gaps: OuterGaps);
+
+    /// Return whether a single tiled window gets no gaps.
+    ///
+    /// Initially false.
+    fn get_smart_gaps(&self) -> bool;
+
+    /// Turn smart gaps on or off.
+    fn set_smart_gaps(&mut self, smart_gaps: bool);
+
+    /// Return the minimum width and height of a tile after taking the gaps.
+    ///
+    /// Initially `DEFAULT_MIN_WINDOW_SIZE`.
+    fn get_min_window_size(&self) -> c_uint;
+
+    /// Set the minimum window size.
+    fn set_min_window_size(&mut self, size: c_uint);
+
+    /// Set the gap size like `set_gap` and report whether the gap can be honoured.
+    ///
+    /// The gap is set in any case, so `get_gap() == gapsize` afterwards. This function *should* return an
+    /// appropriate error when the gaps don't fit in a tile of the current layout without making it smaller than
+    /// the minimum window size. Such a tile gets smaller gaps.
+    fn try_set_gap(&mut self, gapsize: GapSize) -> Result<(), Self::Error>;
+}
+
 /// The struct for a simple tiled layouter with gaps
 #[derive(RustcDecodable, RustcEncodable, Debug, Clone)]
+#[cfg_attr(feature = "with-serde", derive(Serialize, Deserialize))]
 pub struct GappedLayouter<WrappedLayouter: Layouter> {
-    /// The size of the gap
-    pub gap_size: GapSize,
+    /// The space between two tiles
+    pub inner_gap: GapSize,
+    /// The spaces between the tiles and the edges of the screen
+    pub outer_gaps: OuterGaps,
+    /// Whether a single tile gets no gaps
+    pub smart_gaps: bool,
+    /// The minimum width and height of a tile after taking the gaps
+    pub min_window_size: c_uint,
     /// The wrapped layouter
     pub wrapped_layouter: WrappedLayouter,
 }
 
 impl<WrappedLayouter: Layouter> Layouter for GappedLayouter<WrappedLayouter> {
     fn get_geom(&self, i: usize, screen: Screen, nb_windows: usize) -> Geometry {
-        let geom = self.wrapped_layouter.get_geom(i, screen, nb_windows);
-
-        let signed_gap = self.gap_size as c_int;
+        self.add_gaps(self.wrapped_layouter.get_geom(i, screen, nb_windows), screen, nb_windows)
+    }
 
-        Geometry {
-            x: geom.x + signed_gap,
-            y: geom.y + signed_gap,
-            width: geom.width - 2 * self.gap_size,
-            height: geom.height - 2 * self.gap_size,
-        }
+    fn get_geoms(&self, screen: Screen, nb_windows: usize) -> Vec<Geometry> {
+        self.wrapped_layouter
+            .get_geoms(screen, nb_windows)
+            .into_iter()
+            .map(|geom| self.add_gaps(geom, screen, nb_windows))
+            .collect()
     }
 
     fn new() -> GappedLayouter<WrappedLayouter> {
         GappedLayouter {
-            gap_size: 0,
+            inner_gap: 0,
+            outer_gaps: OuterGaps::new(),
+            smart_gaps: false,
+            min_window_size: DEFAULT_MIN_WINDOW_SIZE,
             wrapped_layouter: WrappedLayouter::new(),
         }
     }
 }
 
+impl<WrappedLayouter: Layouter> GappedLayouter<WrappedLayouter> {
+    /// Shrink the geometry of a tile by its gaps
+    fn add_gaps(&self, geom: Geometry, screen: Screen, nb_windows: usize) -> Geometry {
+        let gaps = self.fitting_gaps(geom, screen, nb_windows);
+
+        Geometry {
+            x: geom.x + gaps.left as c_int,
+            y: geom.y + gaps.top as c_int,
+            width: geom.width - gaps.left - gaps.right,
+            height: geom.height - gaps.top - gaps.bottom,
+        }
+    }
+
+    /// Return the gaps around a tile: the outer gap on the sides at the edges of the screen and half of the inner gap
+    /// on the other sides. With smart gaps a single tile gets no gaps.
+    fn tile_gaps(&self, geom: Geometry, screen: Screen, nb_windows: usize) -> OuterGaps {
+        if self.smart_gaps && nb_windows == 1 {
+            return OuterGaps::new();
+        }
+
+        // The tile on the left or at the top gets the smaller half of an odd inner gap
+        let (smaller_half, larger_half) = (self.inner_gap / 2, self.inner_gap - self.inner_gap / 2);
+        let at_right = geom.x as i64 + geom.width as i64 >= screen.width as i64;
+        let at_bottom = geom.y as i64 + geom.height as i64 >= screen.height as i64;
+
+        OuterGaps {
+            top: if geom.y <= 0 { self.outer_gaps.top } else { larger_half },
+            bottom: if at_bottom { self.outer_gaps.bottom } else { smaller_half },
+            left: if geom.x <= 0 { self.outer_gaps.left } else { larger_half },
+            right: if at_right { self.outer_gaps.right } else { smaller_half },
+        }
+    }
+
+    /// Return the gaps around a tile that fit in it without making it smaller than the minimum window size
+    fn fitting_gaps(&self, geom: Geometry, screen: Screen, nb_windows: usize) -> OuterGaps {
+        let gaps = self.tile_gaps(geom, screen, nb_windows);
+        let (left, right) = self.fit(gaps.left, gaps.right, geom.width);
+        let (top, bottom) = self.fit(gaps.top, gaps.bottom, geom.height);
+
+        OuterGaps {
+            top: top,
+            bottom: bottom,
+            left: left,
+            right: right,
+        }
+    }
+
+    /// Shrink the gaps on both sides of a tile of the given size in proportion until the tile keeps the minimum
+    /// window size
+    fn fit(&self, before: GapSize, after: GapSize, size: c_uint) -> (GapSize, GapSize) {
+        let available = size.saturating_sub(self.min_window_size) as u64;
+        let total = before as u64 + after as u64;
+
+        if total <= available {
+            (before, after)
+        } else {
+            ((before as u64 * available / total) as GapSize, (after as u64 * available / total) as GapSize)
+        }
+    }
+}
+
 impl<WrappedLayouter: Layouter> GenericGapSupport for GappedLayouter<WrappedLayouter> {
     fn get_gap(&self) -> GapSize {
-        self.gap_size
+        self.inner_gap / 2
     }
 
     fn set_gap(&mut self, gapsize: GapSize) {
-        self.gap_size = gapsize;
+        self.inner_gap = gapsize.saturating_mul(2);
+        self.outer_gaps = OuterGaps::uniform(gapsize);
+    }
+
+    fn get_inner_gap(&self) -> GapSize {
+        self.inner_gap
+    }
+
+    fn set_inner_gap(&mut self, gap: GapSize) {
+        self.inner_gap = gap;
+    }
+
+    fn get_outer_gaps(&self) -> OuterGaps {
+        self.outer_gaps
+    }
+
+    fn set_outer_gaps(&mut self, gaps: OuterGaps) {
+        self.outer_gaps = gaps;
+    }
+
+    fn get_smart_gaps(&self) -> bool {
+        self.smart_gaps
+    }
+
+    fn set_smart_gaps(&mut self, smart_gaps: bool) {
+        self.smart_gaps = smart_gaps;
+    }
+
+    fn get_min_window_size(&self) -> c_uint {
+        self.min_window_size
+    }
+
+    fn set_min_window_size(&mut self, size: c_uint) {
+        self.min_window_size = size;
+    }
+
+    fn honours_gap(&self, screen: Screen, nb_windows: usize) -> bool {
+        self.wrapped_layouter
+            .get_geoms(screen, nb_windows)
+            .into_iter()
+            .all(|geom| self.fitting_gaps(geom, screen, nb_windows) == self.tile_gaps(geom, screen, nb_windows))
+    }
+}
+
+impl<WrappedLayouter: Layouter + MasterSupport> MasterSupport for GappedLayouter<WrappedLayouter> {
+    fn get_master_ratio(&self) -> f64 {
+        self.wrapped_layouter.get_master_ratio()
+    }
+
+    fn set_master_ratio(&mut self, ratio: f64) {
+        self.wrapped_layouter.set_master_ratio(ratio)
+    }
+
+    fn get_nb_masters(&self) -> usize {
+        self.wrapped_layouter.get_nb_masters()
+    }
+
+    fn set_nb_masters(&mut self, nb_masters: usize) {
+        self.wrapped_layouter.set_nb_masters(nb_masters)
+    }
+}
+
+impl<WrappedLayouter: Layouter + LayoutSupport> LayoutSupport for GappedLayouter<WrappedLayouter> {
+    fn get_layout(&self) -> Layout {
+        self.wrapped_layouter.get_layout()
+    }
+
+    fn set_layout(&mut self, layout: Layout) {
+        self.wrapped_layouter.set_layout(layout)
     }
 }
 
@@ -82,6 +288,55 @@ impl<MyLayouter: GenericGapSupport + Layouter> GapSupport for TilingWM<MyLayoute
     }
 }
 
+impl<MyLayouter: GenericGapSupport + Layouter> ExtendedGapSupport for TilingWM<MyLayouter> {
+    fn get_inner_gap(&self) -> GapSize {
+        self.layouter.get_inner_gap()
+    }
+
+    fn set_inner_gap(&mut self, gap: GapSize) {
+        self.layouter.set_inner_gap(gap)
+    }
+
+    fn get_outer_gaps(&self) -> OuterGaps {
+        self.layouter.get_outer_gaps()
+    }
+
+    fn set_outer_gaps(&mut self, gaps: OuterGaps) {
+        self.layouter.set_outer_gaps(gaps)
+    }
+
+    fn get_smart_gaps(&self) -> bool {
+        self.layouter.get_smart_gaps()
+    }
+
+    fn set_smart_gaps(&mut self, smart_gaps: bool) {
+        self.layouter.set_smart_gaps(smart_gaps)
+    }
+
+    fn get_min_window_size(&self) -> c_uint {
+        self.layouter.get_min_window_size()
+    }
+
+    fn set_min_window_size(&mut self, size: c_uint) {
+        self.layouter.set_min_window_size(size)
+    }
+
+    fn try_set_gap(&mut self, gapsize: GapSize) -> Result<(), Self::Error> {
+        self.set_gap(gapsize);
+
+        let area = self.get_usable_area();
+        let screen = Screen {
+            width: area.width,
+            height: area.height,
+        };
+        if self.layouter.honours_gap(screen, self.windows.len()) {
+            Ok(())
+        } else {
+            Err(WMError::GapTooLarge(gapsize))
+        }
+    }
+}
+
 #[cfg(test)]
 #[allow(unused_mut)]
 #[allow(unused_variables)]
@@ -97,6 +352,8 @@ mod tests {
     // Import expectest names
     pub use expectest::prelude::*;
 
+    conformance_tests!(WMName: window_manager, tiling, gap);
+
     describe! wm {
         before_each {
             let screen: Screen = Screen {
@@ -659,6 +916,104 @@ mod tests {
                     height: 560,
                 }));
             }
+
+            it "should add the gap to the geometries calculated in one pass" {
+                for nb_windows in 0..6 {
+                    let geoms: Vec<Geometry> =
+                        (0..nb_windows).map(|i| wm.layouter.get_geom(i, screen, nb_windows)).collect();
+                    expect!(wm.layouter.get_geoms(screen, nb_windows)).to(be_equal_to(geoms));
+                }
+            }
+
+            it "should shrink the gap of the tiles that are too small for it" {
+                for i in 0..20 {
+                    wm.add_window(WindowWithInfo::new_tiled(i, some_geom)).unwrap();
+                }
+                wm.set_gap(100);
+
+                let layout = wm.get_window_layout();
+                expect!(layout.windows[0].1).to(be_equal_to(Geometry { x: 100, y: 100, width: 200, height: 400 }));
+                expect!(layout.windows[1].1).to(be_equal_to(Geometry { x: 500, y: 15, width: 200, height: 1 }));
+            }
+
+            it "should keep the minimum window size" {
+                wm.set_min_window_size(50);
+                for i in 0..20 {
+                    wm.add_window(WindowWithInfo::new_tiled(i, some_geom)).unwrap();
+                }
+
+                expect!(wm.get_min_window_size()).to(be_equal_to(50));
+                expect!(wm.get_window_info(1).unwrap().geometry).to(be_equal_to(Geometry {
+                    x: 410,
+                    y: 0,
+                    width: 380,
+                    height: 31,
+                }));
+            }
+
+            it "should set the inner and outer gaps" {
+                expect!(wm.get_inner_gap()).to(be_equal_to(20));
+                expect!(wm.get_outer_gaps()).to(be_equal_to(OuterGaps::uniform(10)));
+
+                wm.set_inner_gap(7);
+                expect!(wm.get_inner_gap()).to(be_equal_to(7));
+                expect!(wm.get_gap()).to(be_equal_to(3));
+            }
+
+            it "should use the inner gap between the tiles and the outer gaps at the edges" {
+                wm.add_window(WindowWithInfo::new_tiled(1, some_geom)).unwrap();
+                wm.add_window(WindowWithInfo::new_tiled(2, some_geom)).unwrap();
+                wm.add_window(WindowWithInfo::new_tiled(3, some_geom)).unwrap();
+
+                wm.set_inner_gap(10);
+                wm.set_outer_gaps(OuterGaps {
+                    top: 5,
+                    bottom: 15,
+                    left: 20,
+                    right: 0,
+                });
+
+                let windows = vec![(1, Geometry { x: 20, y: 5, width: 375, height: 580 }),
+                                   (2, Geometry { x: 405, y: 5, width: 395, height: 290 }),
+                                   (3, Geometry { x: 405, y: 305, width: 395, height: 280 })];
+                expect!(wm.get_window_layout().windows).to(be_equal_to(windows));
+            }
+
+            it "should give the larger half of an odd inner gap to the tile on the right" {
+                wm.add_window(WindowWithInfo::new_tiled(1, some_geom)).unwrap();
+                wm.add_window(WindowWithInfo::new_tiled(2, some_geom)).unwrap();
+
+                wm.set_inner_gap(5);
+                wm.set_outer_gaps(OuterGaps::new());
+
+                let windows = vec![(1, Geometry { x: 0, y: 0, width: 398, height: 600 }),
+                                   (2, Geometry { x: 403, y: 0, width: 397, height: 600 })];
+                expect!(wm.get_window_layout().windows).to(be_equal_to(windows));
+            }
+
+            it "should drop the gaps of a single tile with smart gaps" {
+                wm.set_smart_gaps(true);
+                wm.add_window(WindowWithInfo::new_tiled(1, some_geom)).unwrap();
+
+                expect!(wm.get_smart_gaps()).to(be_true());
+                expect!(wm.get_window_info(1).unwrap().geometry).to(be_equal_to(screen.to_geometry()));
+                expect!(wm.try_set_gap(1000)).to(be_ok());
+
+                wm.set_gap(10);
+                wm.add_window(WindowWithInfo::new_tiled(2, some_geom)).unwrap();
+                expect!(wm.get_window_info(1).unwrap().geometry).to(be_equal_to(left_half));
+            }
+
+            it "should report a gap that can't be honoured" {
+                wm.add_window(WindowWithInfo::new_tiled(1, some_geom)).unwrap();
+                wm.add_window(WindowWithInfo::new_tiled(2, some_geom)).unwrap();
+                wm.add_window(WindowWithInfo::new_tiled(3, some_geom)).unwrap();
+
+                expect!(wm.try_set_gap(50)).to(be_ok());
+                expect!(wm.try_set_gap(150)).to(be_err());
+                expect!(wm.get_gap()).to(be_equal_to(150));
+                expect!(wm.try_set_gap(0)).to(be_ok());
+            }
         }
     }
 }
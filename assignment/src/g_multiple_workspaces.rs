@@ -22,7 +22,9 @@
 //! COMMENTS:
 //!
 //! Most methods only operate on the current workspace. swap_with_master will first move the window to the current workspace.
-//! (I find it hard to believe it would be used in practice but there is currently no other way to move a window after the window has been added to the WM).
+//! Every workspace has its own gap settings and its own layout, chosen at runtime with LayoutSupport.
+//! Windows can be sent to another workspace with move_window_to_workspace (and move_window_to_workspace_and_follow).
+//! The number of workspaces can be chosen with new_with_workspaces and changed at runtime with add_workspace and remove_workspace.
 //!
 //! A lot of tests were copied from e_fullscreen_windows
 //!
@@ -41,20 +43,52 @@
 //!
 //!
 
+use std::cmp;
+use std::os::raw::c_uint;
+
 use cplwm_api::types::{GapSize, Geometry, MAX_WORKSPACE_INDEX, PrevOrNext, Screen, Window, WindowLayout, WindowWithInfo, WorkspaceIndex};
 use cplwm_api::wm::{FloatSupport, FullscreenSupport, GapSupport, MinimiseSupport, MultiWorkspaceSupport, TilingSupport, WindowManager};
 
-use e_fullscreen_windows::WMName as FullscreenWM;
+use c_floating_windows::FloatingWM;
+use d_minimising_windows::MinimisingWM;
+use e_fullscreen_windows::FullscreenWM;
 use error::MultiWMError;
 use error::MultiWMError::*;
+use f_gaps::{ExtendedGapSupport, GappedLayouter, OuterGaps};
 use fixed_window_manager::RealWindowInfo;
+use layouts::{Layout, LayoutSupport, SwitchableLayouter};
+use direction::{Direction, DirectionSupport};
+use struts::{StrutSupport, Struts};
+use window_description::{DescriptionSupport, WindowDescription};
+use events::{Snapshot, SnapshotSupport};
+
+/// The window manager of a workspace: the window manager of `e_fullscreen_windows` with a layout that can be
+/// chosen at runtime (see `layouts`)
+pub type SwitchableWM = FullscreenWM<MinimisingWM<FloatingWM<GappedLayouter<SwitchableLayouter>>>>;
 
 /// Type alias for automated tests
-pub type WMName = WorkspaceWM<FullscreenWM>;
+pub type WMName = WorkspaceWM<SwitchableWM>;
+
+/// A window manager that can move windows to another workspace
+pub trait MoveWindowSupport: WindowManager {
+    /// Move the given window to the workspace at the given index without switching to that workspace.
+    ///
+    /// This function *should* return an appropriate error when the window is not managed by the window manager
+    /// or when the workspace doesn't exist.
+    fn move_window_to_workspace(&mut self, window: Window, index: WorkspaceIndex) -> Result<(), Self::Error>;
+
+    /// Add a window with the given description to the workspace at the given index without switching to that
+    /// workspace, minimised if `minimised` is true. The other workspaces don't change.
+    ///
+    /// This function *should* return an appropriate error, without adding the window, when the workspace doesn't
+    /// exist.
+    fn add_window_to_workspace(&mut self, description: WindowDescription, index: WorkspaceIndex, minimised: bool) -> Result<(), Self::Error>;
+}
 
 /// Main struct of the window manager
 /// This WM has multiple workspaces. Each workspace uses a different WM
 #[derive(RustcDecodable, RustcEncodable, Debug, Clone)]
+#[cfg_attr(feature = "with-serde", derive(Serialize, Deserialize))]
 pub struct WorkspaceWM<WrappedWM: RealWindowInfo> {
     /// The index of the current workspace
     pub current_workspace: WorkspaceIndex,
@@ -63,6 +97,27 @@ pub struct WorkspaceWM<WrappedWM: RealWindowInfo> {
 }
 
 impl<WrappedWM: RealWindowInfo> WorkspaceWM<WrappedWM> {
+    /// Create a new window manager with the given number of workspaces (there is always at least one workspace).
+    ///
+    /// `WindowManager::new` creates `MAX_WORKSPACE_INDEX + 1` workspaces.
+    pub fn new_with_workspaces(screen: Screen, nb_workspaces: usize) -> WorkspaceWM<WrappedWM> {
+        WorkspaceWM {
+            current_workspace: 0,
+            wrapped_wms: (0..cmp::max(nb_workspaces, 1)).map(|_| WrappedWM::new(screen)).collect(),
+        }
+    }
+
+    /// Return the number of workspaces
+    pub fn get_nb_workspaces(&self) -> usize {
+        self.wrapped_wms.len()
+    }
+
+    /// Returns true if there is a workspace at the given index
+    fn is_valid_workspace(&self, index: WorkspaceIndex) -> bool {
+        // No test for index < 0 because of type limits and warnings should be avoided
+        index < self.wrapped_wms.len()
+    }
+
     /// Get the wm for the current workspace
     fn get_current_wm(&self) -> &WrappedWM {
         &self.wrapped_wms[self.current_workspace]
@@ -111,15 +166,107 @@ impl<WrappedWM: RealWindowInfo> WorkspaceWM<WrappedWM> {
 
     /// Moves the given window to the current workspace
     fn move_window_to_current_workspace(&mut self, window: Window) -> Result<(), WrappedWM::Error> {
+        let index = self.current_workspace;
+        self.move_window(window, index)
+    }
+
+    /// Moves the given window to the workspace at the given (valid) index.
+    /// Does nothing if the window already is on that workspace.
+    fn move_window(&mut self, window: Window, index: WorkspaceIndex) -> Result<(), WrappedWM::Error> {
         let info = try!(self.get_wm_for_window(window)
             .get_real_window_info(window));
 
+        if self.get_index_for_window(window) == index {
+            return Ok(());
+        }
+
         try!(self.get_mutable_wm_for_window(window)
             .remove_window(window));
 
-        let current_wm = self.get_current_mutable_wm();
+        self.wrapped_wms[index].add_window(info)
+    }
+}
+
+impl<WrappedWM: StrutSupport + RealWindowInfo> WorkspaceWM<WrappedWM> {
+    /// Add a new empty workspace after the existing ones and return its index.
+    ///
+    /// The new workspace reserves the same edges as the other workspaces (see `StrutSupport`).
+    pub fn add_workspace(&mut self) -> WorkspaceIndex {
+        let screen = self.get_screen();
+        let mut wm = WrappedWM::new(screen);
+        wm.copy_struts(self.get_current_wm());
+        self.wrapped_wms.push(wm);
+
+        self.wrapped_wms.len() - 1
+    }
+}
+
+impl<WrappedWM: MinimiseSupport + RealWindowInfo> WorkspaceWM<WrappedWM> {
+    /// Move the given window to the workspace at the given index.
+    ///
+    /// The window keeps its real info (e.g. the floating geometry or whether it's fullscreen)
+    /// and stays minimised if it was minimised. The current workspace doesn't change.
+    ///
+    /// Returns an `UnknownWorkspace` error if the index is not valid.
+    pub fn move_window_to_workspace(&mut self, window: Window, index: WorkspaceIndex) -> Result<(), MultiWMError<WrappedWM::Error>> {
+        if !self.is_valid_workspace(index) {
+            return Err(UnknownWorkspace(index));
+        }
+
+        let was_minimised = self.get_wm_for_window(window).is_minimised(window);
+
+        try!(self.move_window(window, index));
+
+        if was_minimised && !self.wrapped_wms[index].is_minimised(window) {
+            try!(self.wrapped_wms[index].toggle_minimised(window));
+        }
+
+        Ok(())
+    }
+
+    /// Move the given window to the workspace at the given index and switch to that workspace.
+    ///
+    /// See `move_window_to_workspace`.
+    pub fn move_window_to_workspace_and_follow(&mut self, window: Window, index: WorkspaceIndex) -> Result<(), MultiWMError<WrappedWM::Error>> {
+        try!(self.move_window_to_workspace(window, index));
+
+        self.switch_workspace(index)
+    }
+
+    /// Remove the workspace at the given index.
+    ///
+    /// Its windows are moved to the previous workspace (or the next one when removing the first workspace),
+    /// keeping the focus of that workspace. If the current workspace is removed, that workspace becomes the current one
+    /// and the focused window stays focused.
+    ///
+    /// Returns an `UnknownWorkspace` error if the index is not valid and a `LastWorkspace` error when trying
+    /// to remove the only workspace.
+    pub fn remove_workspace(&mut self, index: WorkspaceIndex) -> Result<(), MultiWMError<WrappedWM::Error>> {
+        if !self.is_valid_workspace(index) {
+            return Err(UnknownWorkspace(index));
+        }
+        if self.wrapped_wms.len() == 1 {
+            return Err(LastWorkspace);
+        }
+
+        let neighbour = if index > 0 { index - 1 } else { index + 1 };
+        let focused_window = match self.wrapped_wms[index].get_focused_window() {
+            Some(window) if index == self.current_workspace => Some(window),
+            _ => self.wrapped_wms[neighbour].get_focused_window(),
+        };
+
+        for window in self.wrapped_wms[index].get_windows() {
+            try!(self.move_window_to_workspace(window, neighbour));
+        }
+        try!(self.wrapped_wms[neighbour].focus_window(focused_window));
+
+        self.wrapped_wms.remove(index);
+
+        if self.current_workspace > index || (self.current_workspace == index && index > 0) {
+            self.current_workspace -= 1;
+        }
 
-        current_wm.add_window(info)
+        Ok(())
     }
 }
 
@@ -129,10 +276,7 @@ impl<WrappedWM: RealWindowInfo> WindowManager for WorkspaceWM<WrappedWM> {
     type Error = MultiWMError<WrappedWM::Error>;
 
     fn new(screen: Screen) -> WorkspaceWM<WrappedWM> {
-        WorkspaceWM {
-            current_workspace: 0,
-            wrapped_wms: (0..(MAX_WORKSPACE_INDEX + 1)).map(|_| WrappedWM::new(screen)).collect(),
-        }
+        WorkspaceWM::new_with_workspaces(screen, MAX_WORKSPACE_INDEX + 1)
     }
 
     fn get_windows(&self) -> Vec<Window> {
@@ -146,14 +290,35 @@ impl<WrappedWM: RealWindowInfo> WindowManager for WorkspaceWM<WrappedWM> {
         windows
     }
 
+    /// A window that is already managed (by any workspace) is not added again
     fn add_window(&mut self, window_with_info: WindowWithInfo) -> Result<(), Self::Error> {
+        if self.is_managed(window_with_info.window) {
+            return Ok(());
+        }
+
         Ok(self.get_current_mutable_wm()
             .add_window(window_with_info)?)
     }
 
+    /// A window that is not managed (e.g. a dock) is removed from every workspace
     fn remove_window(&mut self, window: Window) -> Result<(), Self::Error> {
-        Ok(self.get_mutable_wm_for_window(window)
-            .remove_window(window)?)
+        if self.is_managed(window) {
+            return Ok(self.get_mutable_wm_for_window(window)
+                .remove_window(window)?);
+        }
+
+        let current_workspace = self.current_workspace;
+        let mut removed = false;
+        for (i, wm) in self.wrapped_wms.iter_mut().enumerate() {
+            if i != current_workspace {
+                removed = wm.remove_window(window).is_ok() || removed;
+            }
+        }
+
+        match self.get_current_mutable_wm().remove_window(window) {
+            Err(_) if removed => Ok(()),
+            result => Ok(result?),
+        }
     }
 
     fn get_window_layout(&self) -> WindowLayout {
@@ -269,6 +434,7 @@ impl<WrappedWM: FullscreenSupport + RealWindowInfo> FullscreenSupport for Worksp
     }
 }
 
+/// Every workspace has its own gap, the setters only change the gap of the current workspace
 impl<WrappedWM: GapSupport + RealWindowInfo> GapSupport for WorkspaceWM<WrappedWM> {
     fn get_gap(&self) -> GapSize {
         self.get_current_wm()
@@ -276,9 +442,203 @@ impl<WrappedWM: GapSupport + RealWindowInfo> GapSupport for WorkspaceWM<WrappedW
     }
 
     fn set_gap(&mut self, gap_size: GapSize) {
+        self.get_current_mutable_wm()
+            .set_gap(gap_size)
+    }
+}
+
+/// The settings only apply to the current workspace (see `GapSupport`)
+impl<WrappedWM: ExtendedGapSupport + RealWindowInfo> ExtendedGapSupport for WorkspaceWM<WrappedWM> {
+    fn get_inner_gap(&self) -> GapSize {
+        self.get_current_wm()
+            .get_inner_gap()
+    }
+
+    fn set_inner_gap(&mut self, gap: GapSize) {
+        self.get_current_mutable_wm()
+            .set_inner_gap(gap)
+    }
+
+    fn get_outer_gaps(&self) -> OuterGaps {
+        self.get_current_wm()
+            .get_outer_gaps()
+    }
+
+    fn set_outer_gaps(&mut self, gaps: OuterGaps) {
+        self.get_current_mutable_wm()
+            .set_outer_gaps(gaps)
+    }
+
+    fn get_smart_gaps(&self) -> bool {
+        self.get_current_wm()
+            .get_smart_gaps()
+    }
+
+    fn set_smart_gaps(&mut self, smart_gaps: bool) {
+        self.get_current_mutable_wm()
+            .set_smart_gaps(smart_gaps)
+    }
+
+    fn get_min_window_size(&self) -> c_uint {
+        self.get_current_wm()
+            .get_min_window_size()
+    }
+
+    fn set_min_window_size(&mut self, size: c_uint) {
+        self.get_current_mutable_wm()
+            .set_min_window_size(size)
+    }
+
+    fn try_set_gap(&mut self, gap_size: GapSize) -> Result<(), Self::Error> {
+        Ok(self.get_current_mutable_wm()
+            .try_set_gap(gap_size)?)
+    }
+}
+
+/// Every workspace has its own layout, like its gap
+impl<WrappedWM: LayoutSupport + RealWindowInfo> LayoutSupport for WorkspaceWM<WrappedWM> {
+    fn get_layout(&self) -> Layout {
+        self.get_current_wm()
+            .get_layout()
+    }
+
+    fn set_layout(&mut self, layout: Layout) {
+        self.get_current_mutable_wm()
+            .set_layout(layout)
+    }
+}
+
+/// The struts and docks are the same on every workspace
+impl<WrappedWM: StrutSupport + RealWindowInfo> StrutSupport for WorkspaceWM<WrappedWM> {
+    fn get_struts(&self) -> Struts {
+        self.get_current_wm()
+            .get_struts()
+    }
+
+    fn set_struts(&mut self, struts: Struts) {
         let ref mut wms = self.wrapped_wms;
         for wm in wms {
-            wm.set_gap(gap_size)
+            wm.set_struts(struts)
+        }
+    }
+
+    fn add_dock(&mut self, window: Window, struts: Struts) {
+        let ref mut wms = self.wrapped_wms;
+        for wm in wms {
+            wm.add_dock(window, struts)
+        }
+    }
+
+    /// Workspaces without the dock are skipped, the error of the current workspace is returned when no workspace has
+    /// the dock
+    fn remove_dock(&mut self, window: Window) -> Result<(), Self::Error> {
+        if !self.wrapped_wms.iter().any(|wm| wm.get_docks().contains(&window)) {
+            return Ok(self.get_current_mutable_wm()
+                .remove_dock(window)?);
+        }
+
+        let ref mut wms = self.wrapped_wms;
+        for wm in wms.iter_mut().filter(|wm| wm.get_docks().contains(&window)) {
+            try!(wm.remove_dock(window));
+        }
+
+        Ok(())
+    }
+
+    fn get_docks(&self) -> Vec<Window> {
+        self.get_current_wm()
+            .get_docks()
+    }
+
+    /// Every workspace copies the edges of the current workspace of the other window manager
+    fn copy_struts(&mut self, other: &WorkspaceWM<WrappedWM>) {
+        let ref mut wms = self.wrapped_wms;
+        for wm in wms {
+            wm.copy_struts(other.get_current_wm())
+        }
+    }
+}
+
+impl<WrappedWM: DescriptionSupport + RealWindowInfo> DescriptionSupport for WorkspaceWM<WrappedWM> {
+    /// A transient window is added to the workspace of its parent, a dock is added to every workspace
+    fn add_window_with_description(&mut self, description: WindowDescription) -> Result<(), Self::Error> {
+        if description.is_dock() {
+            let ref mut wms = self.wrapped_wms;
+            for wm in wms {
+                try!(wm.add_window_with_description(description.clone()));
+            }
+            return Ok(());
+        }
+
+        if self.is_managed(description.info.window) {
+            return Ok(());
+        }
+
+        let index = match description.transient_for {
+            Some(parent) if self.is_managed(parent) => self.get_index_for_window(parent),
+            _ => self.current_workspace,
+        };
+
+        Ok(self.wrapped_wms[index].add_window_with_description(description)?)
+    }
+}
+
+impl<WrappedWM: DirectionSupport + RealWindowInfo> DirectionSupport for WorkspaceWM<WrappedWM> {
+    fn focus_direction(&mut self, dir: Direction) {
+        self.get_current_mutable_wm()
+            .focus_direction(dir)
+    }
+
+    fn swap_direction(&mut self, dir: Direction) {
+        self.get_current_mutable_wm()
+            .swap_direction(dir)
+    }
+}
+
+impl<WrappedWM: MinimiseSupport + DescriptionSupport + RealWindowInfo> MoveWindowSupport for WorkspaceWM<WrappedWM> {
+    /// See the inherent `WorkspaceWM::move_window_to_workspace`
+    fn move_window_to_workspace(&mut self, window: Window, index: WorkspaceIndex) -> Result<(), Self::Error> {
+        WorkspaceWM::move_window_to_workspace(self, window, index)
+    }
+
+    /// Docks and windows that are already managed are added like with `add_window_with_description`
+    fn add_window_to_workspace(&mut self, description: WindowDescription, index: WorkspaceIndex, minimised: bool) -> Result<(), Self::Error> {
+        if !self.is_valid_workspace(index) {
+            return Err(UnknownWorkspace(index));
+        }
+        if description.is_dock() || self.is_managed(description.info.window) {
+            return self.add_window_with_description(description);
+        }
+
+        let window = description.info.window;
+        let ref mut wm = self.wrapped_wms[index];
+        try!(wm.add_window_with_description(description));
+        if minimised {
+            try!(wm.toggle_minimised(window));
+        }
+
+        Ok(())
+    }
+}
+
+impl<WrappedWM> SnapshotSupport for WorkspaceWM<WrappedWM>
+    where WrappedWM: FloatSupport + MinimiseSupport + FullscreenSupport + ExtendedGapSupport + LayoutSupport + RealWindowInfo
+{
+    /// The floating, minimised and fullscreen windows of all workspaces, the gaps and the layout of the current one
+    fn get_snapshot(&self) -> Snapshot {
+        Snapshot {
+            windows: self.get_windows().into_iter().collect(),
+            focused_window: self.get_focused_window(),
+            workspace: self.current_workspace,
+            floating_windows: self.wrapped_wms.iter().flat_map(|wm| wm.get_floating_windows()).collect(),
+            minimised_windows: self.wrapped_wms.iter().flat_map(|wm| wm.get_minimised_windows()).collect(),
+            fullscreen_windows: self.wrapped_wms.iter().filter_map(|wm| wm.get_fullscreen_window()).collect(),
+            gap: self.get_gap(),
+            inner_gap: self.get_inner_gap(),
+            outer_gaps: self.get_outer_gaps(),
+            smart_gaps: self.get_smart_gaps(),
+            layout: self.get_layout(),
+            screen: self.get_screen(),
         }
     }
 }
@@ -289,8 +649,7 @@ impl<WrappedWM: RealWindowInfo> MultiWorkspaceSupport<WrappedWM> for WorkspaceWM
     }
 
     fn get_workspace(&self, index: WorkspaceIndex) -> Result<&WrappedWM, Self::Error> {
-        // No test for index < 0 because of type limits and warnings should be avoided
-        if index > MAX_WORKSPACE_INDEX {
+        if !self.is_valid_workspace(index) {
             Err(UnknownWorkspace(index))
         } else {
             Ok(&self.wrapped_wms[index])
@@ -298,7 +657,7 @@ impl<WrappedWM: RealWindowInfo> MultiWorkspaceSupport<WrappedWM> for WorkspaceWM
     }
 
     fn get_workspace_mut(&mut self, index: WorkspaceIndex) -> Result<&mut WrappedWM, Self::Error> {
-        if index > MAX_WORKSPACE_INDEX {
+        if !self.is_valid_workspace(index) {
             Err(UnknownWorkspace(index))
         } else {
             Ok(&mut self.wrapped_wms[index])
@@ -313,10 +672,10 @@ impl<WrappedWM: RealWindowInfo> MultiWorkspaceSupport<WrappedWM> for WorkspaceWM
     /// and then switching back to the original workspace should be the same
     /// as before.
     ///
-    /// This function *should* return an appropriate error when `0 <= index <=
-    /// MAX_WORKSPACE_INDEX` is not true.
+    /// This function returns an `UnknownWorkspace` error when `0 <= index <
+    /// get_nb_workspaces()` is not true.
     fn switch_workspace(&mut self, index: WorkspaceIndex) -> Result<(), Self::Error> {
-        if index > MAX_WORKSPACE_INDEX {
+        if !self.is_valid_workspace(index) {
             Err(UnknownWorkspace(index))
         } else {
             self.current_workspace = index;
@@ -337,10 +696,15 @@ mod tests {
     pub use cplwm_api::types::*;
     pub use cplwm_api::types::PrevOrNext::*;
     pub use cplwm_api::types::FloatOrTile::*;
+    pub use direction::*;
+    pub use struts::*;
+    pub use window_description::*;
 
     // Import expectest names
     pub use expectest::prelude::*;
 
+    conformance_tests!(WMName: window_manager, tiling, floating, minimising, fullscreen, gap, workspaces);
+
     describe! wm {
         before_each {
             let screen: Screen = Screen {
@@ -468,6 +832,20 @@ mod tests {
                 expect!(wl.windows).to(be_equal_to(vec![]));
             }
 
+            it "shouldn't add a window twice if it's managed by another workspace" {
+                wm.add_window(WindowWithInfo::new_tiled(1, some_geom)).unwrap();
+                wm.switch_workspace(1).unwrap();
+                wm.add_window(WindowWithInfo::new_tiled(1, some_geom)).unwrap();
+
+                let wl = wm.get_window_layout();
+
+                expect!(wm.is_managed(1)).to(be_true());
+                expect!(wm.get_windows()).to(be_equal_to(vec![1]));
+                expect!(wm.get_workspace(1).unwrap().get_windows()).to(be_equal_to(vec![]));
+                expect!(wl.focused_window).to(be_equal_to(None));
+                expect!(wl.windows).to(be_equal_to(vec![]));
+            }
+
             it "should add a fullscreen window correctly" {
                 wm.add_window(WindowWithInfo::new_tiled(2, some_geom)).unwrap();
                 wm.add_window(WindowWithInfo::new_float(3, some_geom)).unwrap();
@@ -1718,6 +2096,347 @@ mod tests {
                 expect!(wm.get_window_layout().windows).to(be_equal_to(vec![(2, screen_geom)]));
             }
         }
+
+        describe! gap_support {
+            before_each {
+                wm.add_window(WindowWithInfo::new_tiled(1, some_geom)).unwrap();
+                wm.switch_workspace(1).unwrap();
+                wm.add_window(WindowWithInfo::new_tiled(2, some_geom)).unwrap();
+
+                let gapped_screen = Geometry { x: 10, y: 10, width: 780, height: 580 };
+            }
+
+            it "should only change the gap of the current workspace" {
+                wm.set_gap(10);
+
+                expect!(wm.get_gap()).to(be_equal_to(10));
+                expect!(wm.get_window_layout().windows).to(be_equal_to(vec![(2, gapped_screen)]));
+                wm.switch_workspace(0).unwrap();
+                expect!(wm.get_gap()).to(be_equal_to(0));
+                expect!(wm.get_window_layout().windows).to(be_equal_to(vec![(1, screen_geom)]));
+            }
+
+            it "should only change the gap settings of the current workspace" {
+                wm.set_smart_gaps(true);
+                wm.set_min_window_size(50);
+
+                wm.switch_workspace(0).unwrap();
+                expect!(wm.get_smart_gaps()).to(be_false());
+                expect!(wm.get_min_window_size()).to(be_equal_to(1));
+                expect!(wm.get_workspace(1).unwrap().get_smart_gaps()).to(be_true());
+            }
+
+            it "should only report a gap that doesn't fit on the current workspace" {
+                expect!(wm.try_set_gap(1000)).to(be_err());
+                expect!(wm.get_workspace(0).unwrap().get_gap()).to(be_equal_to(0));
+
+                wm.switch_workspace(2).unwrap();
+                expect!(wm.try_set_gap(1000)).to(be_ok());
+            }
+        }
+
+        describe! strut_support {
+            before_each {
+                wm.add_window(WindowWithInfo::new_tiled(1, some_geom)).unwrap();
+                wm.switch_workspace(1).unwrap();
+                wm.add_window(WindowWithInfo::new_tiled(2, some_geom)).unwrap();
+
+                let struts = Struts { top: 20, bottom: 0, left: 0, right: 0 };
+                let usable_area = Geometry { x: 0, y: 20, width: 800, height: 580 };
+            }
+
+            it "should reserve the edges on every workspace" {
+                wm.add_dock(10, struts);
+
+                expect!(wm.get_window_layout().windows).to(be_equal_to(vec![(2, usable_area)]));
+                wm.switch_workspace(0).unwrap();
+                expect!(wm.get_window_layout().windows).to(be_equal_to(vec![(1, usable_area)]));
+                expect!(wm.get_docks()).to(be_equal_to(vec![10]));
+            }
+
+            it "should release the edges on every workspace" {
+                wm.set_struts(struts);
+                wm.set_struts(Struts::new());
+
+                expect!(wm.get_window_layout().windows).to(be_equal_to(vec![(2, screen_geom)]));
+                wm.switch_workspace(0).unwrap();
+                expect!(wm.get_window_layout().windows).to(be_equal_to(vec![(1, screen_geom)]));
+            }
+
+            it "should error when removing an unknown dock" {
+                expect!(wm.remove_dock(10)).to(be_err());
+            }
+
+            it "should remove a dock from the workspaces that have it" {
+                wm.get_workspace_mut(0).unwrap().add_dock(10, struts);
+                wm.get_workspace_mut(2).unwrap().add_dock(10, struts);
+
+                expect!(wm.remove_dock(10)).to(be_ok());
+                expect!(wm.get_workspace(0).unwrap().get_docks()).to(be_equal_to(vec![]));
+                expect!(wm.get_workspace(2).unwrap().get_docks()).to(be_equal_to(vec![]));
+            }
+        }
+
+        describe! direction_support {
+            before_each {
+                wm.add_window(WindowWithInfo::new_tiled(1, some_geom)).unwrap();
+                wm.add_window(WindowWithInfo::new_tiled(2, some_geom)).unwrap();
+                wm.switch_workspace(1).unwrap();
+                wm.add_window(WindowWithInfo::new_tiled(3, some_geom)).unwrap();
+                wm.switch_workspace(0).unwrap();
+            }
+
+            it "should focus in a direction on the current workspace" {
+                wm.focus_direction(Direction::Left);
+
+                expect!(wm.get_focused_window()).to(be_equal_to(Some(1)));
+            }
+
+            it "should swap in a direction on the current workspace" {
+                wm.swap_direction(Direction::Left);
+
+                expect!(wm.get_focused_window()).to(be_equal_to(Some(2)));
+                expect!(wm.get_window_layout().windows).to(be_equal_to(vec![(2, left_half), (1, right_half)]));
+                expect!(wm.get_workspace(1).unwrap().get_windows()).to(be_equal_to(vec![3]));
+            }
+        }
+
+        describe! description_support {
+            before_each {
+                wm.add_window(WindowWithInfo::new_tiled(1, some_geom)).unwrap();
+                wm.switch_workspace(1).unwrap();
+                wm.add_window(WindowWithInfo::new_tiled(2, some_geom)).unwrap();
+
+                let struts = Struts { top: 20, bottom: 0, left: 0, right: 0 };
+                let usable_area = Geometry { x: 0, y: 20, width: 800, height: 580 };
+            }
+
+            it "should add a transient window to the workspace of its parent" {
+                let mut description = WindowDescription::new(WindowWithInfo::new_tiled(3, floating_geom));
+                description.transient_for = Some(1);
+
+                wm.add_window_with_description(description).unwrap();
+
+                expect!(wm.get_current_workspace_index()).to(be_equal_to(1));
+                expect!(wm.get_workspace(0).unwrap().get_windows()).to(be_equal_to(vec![1, 3]));
+                expect!(wm.get_workspace(0).unwrap().get_floating_windows()).to(be_equal_to(vec![3]));
+            }
+
+            it "should add a transient window of an unknown parent to the current workspace" {
+                let mut description = WindowDescription::new(WindowWithInfo::new_tiled(3, floating_geom));
+                description.transient_for = Some(100);
+
+                wm.add_window_with_description(description).unwrap();
+
+                expect!(wm.get_workspace(1).unwrap().get_windows()).to(be_equal_to(vec![2, 3]));
+            }
+
+            it "should add a dock to every workspace" {
+                wm.add_window_with_description(WindowDescription::new_dock(WindowWithInfo::new_tiled(10, some_geom), struts)).unwrap();
+
+                expect!(wm.get_window_layout().windows).to(be_equal_to(vec![(2, usable_area)]));
+                wm.switch_workspace(0).unwrap();
+                expect!(wm.get_window_layout().windows).to(be_equal_to(vec![(1, usable_area)]));
+            }
+
+            it "should remove a dock from every workspace" {
+                wm.add_window_with_description(WindowDescription::new_dock(WindowWithInfo::new_tiled(10, some_geom), struts)).unwrap();
+
+                expect!(wm.remove_window(10)).to(be_ok());
+
+                expect!(wm.get_docks()).to(be_equal_to(vec![]));
+                expect!(wm.get_window_layout().windows).to(be_equal_to(vec![(2, screen_geom)]));
+                wm.switch_workspace(0).unwrap();
+                expect!(wm.get_window_layout().windows).to(be_equal_to(vec![(1, screen_geom)]));
+            }
+
+            it "should still error when removing an unknown window" {
+                expect!(wm.remove_window(10)).to(be_err());
+            }
+        }
+
+        describe! move_window_to_workspace {
+            before_each {
+                wm.add_window(WindowWithInfo::new_tiled(1, some_geom)).unwrap();
+                wm.add_window(WindowWithInfo::new_tiled(2, some_geom)).unwrap();
+                wm.add_window(WindowWithInfo::new_float(3, floating_geom)).unwrap();
+            }
+
+            it "should move a window to another workspace" {
+                wm.move_window_to_workspace(2, 1).unwrap();
+
+                expect!(wm.get_current_workspace_index()).to(be_equal_to(0));
+                expect!(wm.get_workspace(0).unwrap().get_windows()).to(be_equal_to(vec![1, 3]));
+                expect!(wm.get_workspace(1).unwrap().get_windows()).to(be_equal_to(vec![2]));
+                expect!(wm.get_window_layout().windows).to(be_equal_to(vec![(1, screen_geom), (3, floating_geom)]));
+                expect!(wm.get_workspace(1).unwrap().get_window_layout().windows).to(be_equal_to(vec![(2, screen_geom)]));
+            }
+
+            it "should keep the geometry of a floating window" {
+                wm.move_window_to_workspace(3, 2).unwrap();
+
+                let ws = wm.get_workspace(2).unwrap();
+                expect!(ws.get_floating_windows()).to(be_equal_to(vec![3]));
+                expect!(ws.get_window_layout().windows).to(be_equal_to(vec![(3, floating_geom)]));
+            }
+
+            it "should keep a window fullscreen" {
+                wm.toggle_fullscreen(2).unwrap();
+
+                wm.move_window_to_workspace(2, 1).unwrap();
+
+                expect!(wm.get_fullscreen_window()).to(be_equal_to(None));
+                expect!(wm.get_workspace(1).unwrap().get_fullscreen_window()).to(be_equal_to(Some(2)));
+            }
+
+            it "should keep a window minimised" {
+                wm.toggle_minimised(2).unwrap();
+
+                wm.move_window_to_workspace(2, 1).unwrap();
+
+                expect!(wm.get_minimised_windows()).to(be_equal_to(vec![]));
+                expect!(wm.get_workspace(1).unwrap().get_minimised_windows()).to(be_equal_to(vec![2]));
+
+                wm.switch_workspace(1).unwrap();
+                wm.toggle_minimised(2).unwrap();
+                expect!(wm.get_window_layout().windows).to(be_equal_to(vec![(2, screen_geom)]));
+            }
+
+            it "shouldn't do anything if the window is already on that workspace" {
+                wm.move_window_to_workspace(1, 0).unwrap();
+
+                expect!(wm.get_windows()).to(be_equal_to(vec![1, 2, 3]));
+                expect!(wm.get_window_layout().windows).to(be_equal_to(vec![(1, left_half), (2, right_half), (3, floating_geom)]));
+            }
+
+            it "should error on an unknown workspace" {
+                expect!(wm.move_window_to_workspace(2, MAX_WORKSPACE_INDEX + 1)).to(be_err());
+                expect!(wm.get_workspace(0).unwrap().get_windows()).to(be_equal_to(vec![1, 2, 3]));
+            }
+
+            it "should error on an unknown window" {
+                expect!(wm.move_window_to_workspace(4, 1)).to(be_err());
+            }
+
+            it "should be able to follow the window" {
+                wm.move_window_to_workspace_and_follow(2, 1).unwrap();
+
+                expect!(wm.get_current_workspace_index()).to(be_equal_to(1));
+                expect!(wm.get_focused_window()).to(be_equal_to(Some(2)));
+                expect!(wm.get_window_layout().windows).to(be_equal_to(vec![(2, screen_geom)]));
+            }
+        }
+
+        describe! nb_workspaces {
+            it "should have MAX_WORKSPACE_INDEX + 1 workspaces by default" {
+                expect!(wm.get_nb_workspaces()).to(be_equal_to(MAX_WORKSPACE_INDEX + 1));
+                expect!(wm.get_workspace(MAX_WORKSPACE_INDEX)).to(be_ok());
+                expect!(wm.get_workspace(MAX_WORKSPACE_INDEX + 1)).to(be_err());
+            }
+
+            it "should be able to choose the number of workspaces" {
+                let mut wm = WMName::new_with_workspaces(screen, 6);
+
+                expect!(wm.get_nb_workspaces()).to(be_equal_to(6));
+                expect!(wm.switch_workspace(5)).to(be_ok());
+                expect!(wm.switch_workspace(6)).to(be_err());
+            }
+
+            it "should have at least one workspace" {
+                let wm = WMName::new_with_workspaces(screen, 0);
+
+                expect!(wm.get_nb_workspaces()).to(be_equal_to(1));
+            }
+
+            it "should be able to add a workspace" {
+                let index = wm.add_workspace();
+
+                expect!(index).to(be_equal_to(MAX_WORKSPACE_INDEX + 1));
+                expect!(wm.get_nb_workspaces()).to(be_equal_to(MAX_WORKSPACE_INDEX + 2));
+                expect!(wm.switch_workspace(index)).to(be_ok());
+                expect!(wm.get_screen()).to(be_equal_to(screen));
+            }
+
+            it "should reserve the same edges on an added workspace" {
+                let struts = Struts { top: 20, bottom: 0, left: 0, right: 0 };
+                wm.set_struts(Struts { top: 0, bottom: 10, left: 0, right: 0 });
+                wm.add_dock(10, struts);
+
+                let index = wm.add_workspace();
+                wm.switch_workspace(index).unwrap();
+                wm.add_window(WindowWithInfo::new_tiled(1, some_geom)).unwrap();
+
+                expect!(wm.get_docks()).to(be_equal_to(vec![10]));
+                expect!(wm.get_window_layout().windows)
+                    .to(be_equal_to(vec![(1, Geometry { x: 0, y: 20, width: 800, height: 570 })]));
+            }
+
+            describe! remove_workspace {
+                before_each {
+                    wm.add_window(WindowWithInfo::new_tiled(1, some_geom)).unwrap();
+                    wm.switch_workspace(1).unwrap();
+                    wm.add_window(WindowWithInfo::new_tiled(2, some_geom)).unwrap();
+                    wm.add_window(WindowWithInfo::new_float(3, floating_geom)).unwrap();
+                    wm.toggle_minimised(3).unwrap();
+                    wm.switch_workspace(2).unwrap();
+                    wm.add_window(WindowWithInfo::new_tiled(4, some_geom)).unwrap();
+                }
+
+                it "should move the windows to the previous workspace" {
+                    wm.remove_workspace(1).unwrap();
+
+                    expect!(wm.get_nb_workspaces()).to(be_equal_to(MAX_WORKSPACE_INDEX));
+                    let ws = wm.get_workspace(0).unwrap();
+                    expect!(ws.get_windows()).to(be_equal_to(vec![1, 2, 3]));
+                    expect!(ws.get_minimised_windows()).to(be_equal_to(vec![3]));
+                    expect!(ws.get_focused_window()).to(be_equal_to(Some(1)));
+                    expect!(ws.get_window_layout().windows).to(be_equal_to(vec![(1, left_half), (2, right_half)]));
+                }
+
+                it "should move the windows of the first workspace to the next workspace" {
+                    wm.remove_workspace(0).unwrap();
+
+                    expect!(wm.get_workspace(0).unwrap().get_windows()).to(be_equal_to(vec![2, 1, 3]));
+                    expect!(wm.get_workspace(1).unwrap().get_windows()).to(be_equal_to(vec![4]));
+                }
+
+                it "should keep the current workspace if a workspace before it is removed" {
+                    wm.remove_workspace(0).unwrap();
+
+                    expect!(wm.get_current_workspace_index()).to(be_equal_to(1));
+                    expect!(wm.get_windows().len()).to(be_equal_to(4));
+                    expect!(wm.get_window_layout().windows).to(be_equal_to(vec![(4, screen_geom)]));
+                }
+
+                it "should switch to the neighbouring workspace if the current workspace is removed" {
+                    wm.remove_workspace(2).unwrap();
+
+                    expect!(wm.get_current_workspace_index()).to(be_equal_to(1));
+                    expect!(wm.get_window_layout().windows).to(be_equal_to(vec![(2, left_half), (4, right_half)]));
+                    expect!(wm.get_focused_window()).to(be_equal_to(Some(4)));
+                }
+
+                it "should keep the focus of the workspace the windows move to" {
+                    wm.switch_workspace(0).unwrap();
+                    wm.focus_window(None).unwrap();
+                    wm.remove_workspace(1).unwrap();
+
+                    expect!(wm.get_focused_window()).to(be_equal_to(None));
+                }
+
+                it "should error on an unknown workspace" {
+                    expect!(wm.remove_workspace(MAX_WORKSPACE_INDEX + 1)).to(be_err());
+                    expect!(wm.get_nb_workspaces()).to(be_equal_to(MAX_WORKSPACE_INDEX + 1));
+                }
+
+                it "shouldn't remove the last workspace" {
+                    let mut wm = WMName::new_with_workspaces(screen, 1);
+
+                    expect!(wm.remove_workspace(0)).to(be_err());
+                    expect!(wm.get_nb_workspaces()).to(be_equal_to(1));
+                }
+            }
+        }
     }
 
     describe! integration_test {
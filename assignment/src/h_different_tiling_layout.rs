@@ -42,6 +42,7 @@ pub type WMName = TilingWM<SpiralLayouter>;
 
 /// The struct for a simple tiled layouter with gaps
 #[derive(RustcDecodable, RustcEncodable, Debug, Clone)]
+#[cfg_attr(feature = "with-serde", derive(Serialize, Deserialize))]
 pub struct SpiralLayouter {
 }
 
@@ -50,6 +51,24 @@ impl Layouter for SpiralLayouter {
         self.get_geom_for_window(screen.to_geometry(), 0, i, nb_windows)
     }
 
+    /// Walk along the spiral once instead of starting from the screen for every window
+    fn get_geoms(&self, screen: Screen, nb_windows: usize) -> Vec<Geometry> {
+        let mut geoms = Vec::with_capacity(nb_windows);
+        let mut rest_screen = screen.to_geometry();
+        for nb_windows_tiled in 0..nb_windows {
+            if nb_windows_tiled + 1 == nb_windows {
+                // The last window gets the rest of the screen
+                geoms.push(rest_screen);
+            } else {
+                let (geom_for_next_tile, new_rest_screen) = self.split(rest_screen, nb_windows_tiled);
+                geoms.push(geom_for_next_tile);
+                rest_screen = new_rest_screen;
+            }
+        }
+
+        geoms
+    }
+
     fn new() -> SpiralLayouter {
         SpiralLayouter {}
     }
@@ -63,8 +82,19 @@ impl SpiralLayouter {
             return rest_screen;
         }
 
+        let (geom_for_next_tile, new_rest_screen) = self.split(rest_screen, nb_windows_tiled);
+
+        if nb_windows_tiled == i {
+            geom_for_next_tile
+        } else {
+            self.get_geom_for_window(new_rest_screen, nb_windows_tiled + 1, i, nb_windows)
+        }
+    }
+
+    /// Split the rest of the screen after tiling nb_windows_tiled into the tile for the next window and the new rest
+    fn split(&self, rest_screen: Geometry, nb_windows_tiled: usize) -> (Geometry, Geometry) {
         // Calculate split based on which direction of the spiral
-        let (geom_for_next_tile, new_rest_screen) = match (nb_windows_tiled) % 4 {
+        match nb_windows_tiled % 4 {
             0 => self.split_vertically(rest_screen),
             1 => self.split_horizontally(rest_screen),
             2 => {
@@ -77,15 +107,10 @@ impl SpiralLayouter {
             }
             // unreachable branch but Rust doesn't know this
             _ => (rest_screen, rest_screen),
-        };
-
-        if nb_windows_tiled == i {
-            geom_for_next_tile
-        } else {
-            self.get_geom_for_window(new_rest_screen, nb_windows_tiled + 1, i, nb_windows)
         }
     }
 
+    /// Split the geometry in a left and a right half, the right half gets the remaining pixel of an odd width
     fn split_vertically(&self, geom: Geometry) -> (Geometry, Geometry) {
         (Geometry {
             x: geom.x,
@@ -96,11 +121,12 @@ impl SpiralLayouter {
          Geometry {
             x: geom.x + (geom.width / 2) as c_int,
             y: geom.y,
-            width: geom.width / 2,
+            width: geom.width - geom.width / 2,
             height: geom.height,
         })
     }
 
+    /// Split the geometry in a top and a bottom half, the bottom half gets the remaining pixel of an odd height
     fn split_horizontally(&self, geom: Geometry) -> (Geometry, Geometry) {
         (Geometry {
             x: geom.x,
@@ -112,7 +138,7 @@ impl SpiralLayouter {
             x: geom.x,
             y: geom.y + (geom.height / 2) as c_int,
             width: geom.width,
-            height: geom.height / 2,
+            height: geom.height - geom.height / 2,
         })
     }
 }
@@ -132,6 +158,8 @@ mod tests {
     // Import expectest names
     pub use expectest::prelude::*;
 
+    conformance_tests!(WMName: window_manager, tiling);
+
     describe! wm {
         before_each {
             let screen: Screen = Screen {
@@ -708,4 +736,40 @@ mod tests {
             }
         }
     }
+
+    describe! layouter {
+        before_each {
+            let screen: Screen = Screen {
+                width: 1920,
+                height: 1080,
+            };
+
+            let layouter = SpiralLayouter::new();
+        }
+
+        it "should calculate the same geometries in one pass as one by one" {
+            for nb_windows in 0..20 {
+                let geoms: Vec<Geometry> = (0..nb_windows).map(|i| layouter.get_geom(i, screen, nb_windows)).collect();
+                expect!(layouter.get_geoms(screen, nb_windows)).to(be_equal_to(geoms));
+            }
+        }
+
+        bench "calculating the geometries of 500 windows one by one" (bencher) {
+            bencher.iter(|| (0..500).map(|i| layouter.get_geom(i, screen, 500)).collect::<Vec<_>>());
+        }
+
+        bench "calculating the geometries of 500 windows in one pass" (bencher) {
+            bencher.iter(|| layouter.get_geoms(screen, 500));
+        }
+
+        bench "getting the layout of 500 windows" (bencher) {
+            let some_geom = Geometry { x: 0, y: 0, width: 100, height: 100 };
+            let mut wm = WMName::new(screen);
+            for window in 0..500 {
+                wm.add_window(WindowWithInfo::new_tiled(window, some_geom)).unwrap();
+            }
+
+            bencher.iter(|| wm.get_window_layout());
+        }
+    }
 }
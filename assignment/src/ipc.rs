@@ -0,0 +1,641 @@
+//! This module exposes a window manager over a Unix domain socket
+//!
+//! Status bars and scripts connect to the socket and send one JSON request per line. For every request the server
+//! answers with one JSON line: `{"result": ...}` or `{"error": "..."}`.
+//!
+//! The requests:
+//!
+//! ```text
+//! {"request": "get_window_layout"}
+//! {"request": "get_windows"}
+//! {"request": "get_window_info", "window": 12}
+//! {"request": "get_focused_window"}
+//! {"request": "get_workspaces"}
+//! {"request": "get_floating_windows"}
+//! {"request": "get_minimised_windows"}
+//! {"request": "get_fullscreen_window"}
+//! {"request": "command", "command": "focus next"}
+//! ```
+//!
+//! The results are encoded like the types of `cplwm_api` (e.g. a `WindowLayout` or a `WindowWithInfo`),
+//! `get_workspaces` returns the index of the current workspace and the windows of every workspace,
+//! a command (see the `command` module) returns `null`.
+//!
+
+use std::collections::BTreeMap;
+use std::io;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::fs;
+use std::os::unix::fs::FileTypeExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+
+use rustc_serialize::Encodable;
+use rustc_serialize::json::{self, Json, ToJson};
+
+use cplwm_api::types::Window;
+use cplwm_api::wm::WindowManager;
+
+use command::{self, Command, CommandSupport};
+use error::IpcError;
+
+/// A request of an IPC client
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Request {
+    /// See `WindowManager::get_window_layout`
+    GetWindowLayout,
+    /// See `WindowManager::get_windows`
+    GetWindows,
+    /// See `WindowManager::get_window_info`
+    GetWindowInfo(Window),
+    /// See `WindowManager::get_focused_window`
+    GetFocusedWindow,
+    /// The index of the current workspace and the windows of every workspace
+    GetWorkspaces,
+    /// See `FloatSupport::get_floating_windows`
+    GetFloatingWindows,
+    /// See `MinimiseSupport::get_minimised_windows`
+    GetMinimisedWindows,
+    /// See `FullscreenSupport::get_fullscreen_window`
+    GetFullscreenWindow,
+    /// Run a command (see `command::execute`)
+    Command(Command),
+}
+
+impl Request {
+    /// Decode a request, returns a message for the client if the request isn't valid
+    pub fn from_json(json: &Json) -> Result<Request, String> {
+        let name = match json.find("request").and_then(|request| request.as_string()) {
+            Some(name) => name,
+            None => return Err(String::from("Missing request")),
+        };
+
+        match name {
+            "get_window_layout" => Ok(Request::GetWindowLayout),
+            "get_windows" => Ok(Request::GetWindows),
+            "get_window_info" => {
+                json.find("window")
+                    .and_then(|window| window.as_u64())
+                    .map(|window| Request::GetWindowInfo(window as Window))
+                    .ok_or_else(|| String::from("Missing window"))
+            }
+            "get_focused_window" => Ok(Request::GetFocusedWindow),
+            "get_workspaces" => Ok(Request::GetWorkspaces),
+            "get_floating_windows" => Ok(Request::GetFloatingWindows),
+            "get_minimised_windows" => Ok(Request::GetMinimisedWindows),
+            "get_fullscreen_window" => Ok(Request::GetFullscreenWindow),
+            "command" => {
+                match json.find("command").and_then(|command| command.as_string()) {
+                    Some(line) => line.parse().map(Request::Command).map_err(|error| error.to_string()),
+                    None => Err(String::from("Missing command")),
+                }
+            }
+            _ => Err(format!("Unknown request: {}", name)),
+        }
+    }
+}
+
+impl ToJson for Request {
+    fn to_json(&self) -> Json {
+        let mut object = BTreeMap::new();
+        let name = match *self {
+            Request::GetWindowLayout => "get_window_layout",
+            Request::GetWindows => "get_windows",
+            Request::GetWindowInfo(window) => {
+                object.insert(String::from("window"), Json::U64(window as u64));
+                "get_window_info"
+            }
+            Request::GetFocusedWindow => "get_focused_window",
+            Request::GetWorkspaces => "get_workspaces",
+            Request::GetFloatingWindows => "get_floating_windows",
+            Request::GetMinimisedWindows => "get_minimised_windows",
+            Request::GetFullscreenWindow => "get_fullscreen_window",
+            Request::Command(command) => {
+                object.insert(String::from("command"), Json::String(command.to_string()));
+                "command"
+            }
+        };
+        object.insert(String::from("request"), Json::String(String::from(name)));
+
+        Json::Object(object)
+    }
+}
+
+/// The windows of a workspace in a `get_workspaces` result
+#[derive(RustcEncodable, Debug)]
+struct WorkspaceWindows {
+    index: usize,
+    windows: Vec<Window>,
+}
+
+/// The result of a `get_workspaces` request
+#[derive(RustcEncodable, Debug)]
+struct Workspaces {
+    current: usize,
+    workspaces: Vec<WorkspaceWindows>,
+}
+
+/// Encode a value with its `Encodable` implementation
+fn encode<T: Encodable>(value: &T) -> Json {
+    json::encode(value)
+        .ok()
+        .and_then(|encoded| Json::from_str(&encoded).ok())
+        .unwrap_or(Json::Null)
+}
+
+/// Handle a request, returns the result or a message for the client
+pub fn handle_request<W, WM>(wm: &mut WM, request: Request) -> Result<Json, String>
+    where W: WindowManager,
+          WM: CommandSupport<W>
+{
+    match request {
+        Request::GetWindowLayout => Ok(encode(&wm.get_window_layout())),
+        Request::GetWindows => Ok(encode(&wm.get_windows())),
+        Request::GetWindowInfo(window) => {
+            wm.get_window_info(window)
+                .map(|info| encode(&info))
+                .map_err(|error| error.to_string())
+        }
+        Request::GetFocusedWindow => Ok(encode(&wm.get_focused_window())),
+        Request::GetWorkspaces => {
+            let workspaces = (0..)
+                .map(|index| wm.get_workspace(index).map(|ws| WorkspaceWindows { index: index, windows: ws.get_windows() }))
+                .take_while(Result::is_ok)
+                .filter_map(Result::ok)
+                .collect();
+
+            Ok(encode(&Workspaces {
+                current: wm.get_current_workspace_index(),
+                workspaces: workspaces,
+            }))
+        }
+        Request::GetFloatingWindows => Ok(encode(&wm.get_floating_windows())),
+        Request::GetMinimisedWindows => Ok(encode(&wm.get_minimised_windows())),
+        Request::GetFullscreenWindow => Ok(encode(&wm.get_fullscreen_window())),
+        Request::Command(command) => {
+            command::execute(wm, command)
+                .map(|_| Json::Null)
+                .map_err(|error| error.to_string())
+        }
+    }
+}
+
+/// Handle one line of the protocol and return the response line (without a newline)
+pub fn handle_line<W, WM>(wm: &mut WM, line: &str) -> String
+    where W: WindowManager,
+          WM: CommandSupport<W>
+{
+    let response = Json::from_str(line)
+        .map_err(|error| format!("Invalid JSON: {}", error))
+        .and_then(|json| Request::from_json(&json))
+        .and_then(|request| handle_request(wm, request));
+
+    let mut object = BTreeMap::new();
+    match response {
+        Ok(result) => object.insert(String::from("result"), result),
+        Err(error) => object.insert(String::from("error"), Json::String(error)),
+    };
+
+    Json::Object(object).to_string()
+}
+
+/// A client that is served without blocking
+#[derive(Debug)]
+struct PendingClient {
+    /// The non-blocking stream of the client
+    stream: UnixStream,
+    /// The bytes after the last complete line the client sent
+    buffer: Vec<u8>,
+}
+
+impl PendingClient {
+    /// Read what the client has sent so far and answer every complete line.
+    ///
+    /// Returns false when the client has closed the connection.
+    fn serve<W, WM>(&mut self, wm: &mut WM) -> io::Result<bool>
+        where W: WindowManager,
+              WM: CommandSupport<W>
+    {
+        let mut open = true;
+        let mut chunk = [0; 1024];
+        loop {
+            match self.stream.read(&mut chunk) {
+                Ok(0) => {
+                    open = false;
+                    break;
+                }
+                Ok(size) => self.buffer.extend_from_slice(&chunk[..size]),
+                Err(ref error) if error.kind() == io::ErrorKind::WouldBlock => break,
+                Err(ref error) if error.kind() == io::ErrorKind::Interrupted => continue,
+                Err(error) => return Err(error),
+            }
+        }
+
+        while let Some(end) = self.buffer.iter().position(|&byte| byte == b'\n') {
+            let bytes: Vec<u8> = self.buffer.drain(..end + 1).collect();
+            let line = String::from_utf8_lossy(&bytes[..end]);
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let response = handle_line(wm, &line);
+            try!(writeln!(self.stream, "{}", response));
+        }
+
+        Ok(open)
+    }
+}
+
+/// A server that listens on a Unix domain socket.
+///
+/// The window manager is not shared with the server, it's passed in every time clients are served.
+/// The socket file is removed when the server is dropped.
+#[derive(Debug)]
+pub struct IpcServer {
+    /// The socket
+    listener: UnixListener,
+    /// The path of the socket
+    path: PathBuf,
+    /// The clients that are connected, see `serve_pending_clients`
+    clients: Vec<PendingClient>,
+}
+
+impl IpcServer {
+    /// Listen on the socket at the given path.
+    ///
+    /// A stale socket (one that nobody listens on anymore) is replaced. Any other file at the path, including the
+    /// socket of a running server, is left alone and an `AddrInUse` error is returned.
+    pub fn bind<P: AsRef<Path>>(path: P) -> io::Result<IpcServer> {
+        let path = path.as_ref().to_path_buf();
+        if is_stale_socket(&path) {
+            try!(fs::remove_file(&path));
+        }
+
+        let listener = try!(UnixListener::bind(&path));
+
+        Ok(IpcServer {
+            listener: listener,
+            path: path,
+            clients: Vec::new(),
+        })
+    }
+
+    /// Return the path of the socket
+    pub fn get_path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Wait for a client and handle its requests until it closes the connection
+    pub fn serve_client<W, WM>(&self, wm: &mut WM) -> io::Result<()>
+        where W: WindowManager,
+              WM: CommandSupport<W>
+    {
+        let (stream, _) = try!(self.listener.accept());
+        serve_stream(wm, stream)
+    }
+
+    /// Accept the clients that are waiting to connect and answer the complete requests of all connected clients
+    /// without blocking. The clients stay connected until they close the connection.
+    ///
+    /// This can be called from the event loop of the window manager. A client that fails (e.g. because it doesn't
+    /// read its responses) is logged and dropped, the other clients are still served. Only an error of the socket
+    /// itself is returned.
+    pub fn serve_pending_clients<W, WM>(&mut self, wm: &mut WM) -> io::Result<()>
+        where W: WindowManager,
+              WM: CommandSupport<W>
+    {
+        try!(self.listener.set_nonblocking(true));
+        let result = self.accept_pending_clients();
+        try!(self.listener.set_nonblocking(false));
+
+        let clients: Vec<PendingClient> = self.clients
+            .drain(..)
+            .filter_map(|mut client| match client.serve(wm) {
+                Ok(true) => Some(client),
+                Ok(false) => None,
+                Err(error) => {
+                    log_client_error(&error);
+                    None
+                }
+            })
+            .collect();
+        self.clients = clients;
+
+        result
+    }
+
+    /// Accept the clients until accepting a client would block
+    fn accept_pending_clients(&mut self) -> io::Result<()> {
+        loop {
+            match self.listener.accept() {
+                Ok((stream, _)) => {
+                    match stream.set_nonblocking(true) {
+                        Ok(()) => {
+                            self.clients.push(PendingClient {
+                                stream: stream,
+                                buffer: Vec::new(),
+                            })
+                        }
+                        Err(error) => log_client_error(&error),
+                    }
+                }
+                Err(ref error) if error.kind() == io::ErrorKind::WouldBlock => return Ok(()),
+                Err(error) => return Err(error),
+            }
+        }
+    }
+}
+
+impl Drop for IpcServer {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Returns true if the path is a socket that refuses connections
+fn is_stale_socket(path: &Path) -> bool {
+    let is_socket = fs::symlink_metadata(path).map(|metadata| metadata.file_type().is_socket()).unwrap_or(false);
+    is_socket && UnixStream::connect(path).is_err()
+}
+
+/// Report a client that is dropped because of an error
+fn log_client_error(error: &io::Error) {
+    let _ = writeln!(io::stderr(), "cplwm: dropped an IPC client: {}", error);
+}
+
+/// Handle the requests on the stream until it's closed
+fn serve_stream<W, WM>(wm: &mut WM, stream: UnixStream) -> io::Result<()>
+    where W: WindowManager,
+          WM: CommandSupport<W>
+{
+    let mut writer = try!(stream.try_clone());
+    let reader = BufReader::new(stream);
+
+    for line in reader.lines() {
+        let line = try!(line);
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = handle_line(wm, &line);
+        try!(writeln!(writer, "{}", response));
+    }
+
+    Ok(())
+}
+
+/// A client for the IPC server
+#[derive(Debug)]
+pub struct IpcClient {
+    /// The stream to write the requests to
+    writer: UnixStream,
+    /// The stream to read the responses from
+    reader: BufReader<UnixStream>,
+}
+
+impl IpcClient {
+    /// Connect to the server listening on the socket at the given path
+    pub fn connect<P: AsRef<Path>>(path: P) -> io::Result<IpcClient> {
+        let stream = try!(UnixStream::connect(path));
+        let reader = BufReader::new(try!(stream.try_clone()));
+
+        Ok(IpcClient {
+            writer: stream,
+            reader: reader,
+        })
+    }
+
+    /// Send a request and wait for the result
+    pub fn request(&mut self, request: Request) -> Result<Json, IpcError> {
+        self.send(request.to_json())
+    }
+
+    /// Run a command, see the `command` module
+    pub fn command(&mut self, line: &str) -> Result<(), IpcError> {
+        let mut request = BTreeMap::new();
+        request.insert(String::from("request"), Json::String(String::from("command")));
+        request.insert(String::from("command"), Json::String(String::from(line)));
+
+        self.send(Json::Object(request)).map(|_| ())
+    }
+
+    /// Send a request line and wait for the response line
+    fn send(&mut self, request: Json) -> Result<Json, IpcError> {
+        try!(writeln!(self.writer, "{}", request));
+
+        let mut line = String::new();
+        try!(self.reader.read_line(&mut line));
+
+        let mut response = match Json::from_str(&line) {
+            Ok(Json::Object(object)) => object,
+            _ => return Err(IpcError::InvalidResponse(line)),
+        };
+
+        if let Some(result) = response.remove("result") {
+            return Ok(result);
+        }
+        match response.remove("error") {
+            Some(Json::String(error)) => Err(IpcError::ServerError(error)),
+            _ => Err(IpcError::InvalidResponse(line)),
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(unused_mut)]
+#[allow(unused_variables)]
+mod tests {
+    pub use super::*;
+
+    pub use std::env;
+    pub use std::io::{BufRead, BufReader, Write};
+    pub use std::fs::{self, File};
+    pub use std::os::unix::net::{UnixListener, UnixStream};
+    pub use std::sync::atomic::{AtomicUsize, ATOMIC_USIZE_INIT, Ordering};
+    pub use std::thread;
+
+    /// Every test uses its own socket
+    static SOCKET_COUNTER: AtomicUsize = ATOMIC_USIZE_INIT;
+
+    pub use rustc_serialize::json::{Json, ToJson};
+
+    pub use cplwm_api::wm::*;
+    pub use cplwm_api::types::*;
+    pub use command::Command;
+    pub use error::IpcError;
+    pub use g_multiple_workspaces::WMName;
+
+    // Import expectest names
+    pub use expectest::prelude::*;
+
+    describe! requests {
+        before_each {
+            let screen: Screen = Screen {
+                width: 800,
+                height: 600,
+            };
+            let screen_geom = screen.to_geometry();
+
+            let some_geom: Geometry = Geometry {
+                x: 10,
+                y: 10,
+                width: 100,
+                height: 100,
+            };
+
+            let mut wm = WMName::new(screen);
+            wm.add_window(WindowWithInfo::new_tiled(1, some_geom)).unwrap();
+        }
+
+        it "should encode and decode requests" {
+            let requests = vec![Request::GetWindowLayout,
+                                Request::GetWindowInfo(12),
+                                Request::GetWorkspaces,
+                                Request::Command(Command::SwitchWorkspace(2))];
+
+            for request in requests {
+                expect!(Request::from_json(&request.to_json())).to(be_equal_to(Ok(request)));
+            }
+        }
+
+        it "should return the result of a query" {
+            let response = handle_line(&mut wm, r#"{"request": "get_windows"}"#);
+
+            expect!(response).to(be_equal_to(String::from(r#"{"result":[1]}"#)));
+        }
+
+        it "should return the window layout" {
+            let response = handle_line(&mut wm, r#"{"request": "get_window_layout"}"#);
+
+            let expected = r#"{"result":{"focused_window":1,"windows":[[1,{"height":600,"width":800,"x":0,"y":0}]]}}"#;
+            expect!(response).to(be_equal_to(String::from(expected)));
+        }
+
+        it "should run a command" {
+            let response = handle_line(&mut wm, r#"{"request": "command", "command": "workspace 1"}"#);
+
+            expect!(response).to(be_equal_to(String::from(r#"{"result":null}"#)));
+            expect!(wm.get_current_workspace_index()).to(be_equal_to(1));
+        }
+
+        it "should return an error for invalid requests" {
+            expect!(handle_line(&mut wm, "{").starts_with(r#"{"error":"Invalid JSON"#)).to(be_true());
+            expect!(handle_line(&mut wm, r#"{"request": "jump"}"#)).to(be_equal_to(String::from(r#"{"error":"Unknown request: jump"}"#)));
+            expect!(handle_line(&mut wm, r#"{"request": "get_window_info"}"#)).to(be_equal_to(String::from(r#"{"error":"Missing window"}"#)));
+            expect!(handle_line(&mut wm, r#"{"request": "get_window_info", "window": 2}"#)).to(be_equal_to(String::from(r#"{"error":"Unknown window: 2"}"#)));
+        }
+    }
+
+    describe! socket {
+        before_each {
+            let screen: Screen = Screen {
+                width: 800,
+                height: 600,
+            };
+            let screen_geom = screen.to_geometry();
+
+            let some_geom: Geometry = Geometry {
+                x: 10,
+                y: 10,
+                width: 100,
+                height: 100,
+            };
+
+            let socket = SOCKET_COUNTER.fetch_add(1, Ordering::SeqCst);
+            let path = env::temp_dir().join(format!("cplwm-ipc-test-{}.sock", socket));
+            let mut server = IpcServer::bind(&path).unwrap();
+
+            let mut wm = WMName::new(screen);
+            wm.add_window(WindowWithInfo::new_tiled(1, some_geom)).unwrap();
+            wm.add_window(WindowWithInfo::new_tiled(2, some_geom)).unwrap();
+        }
+
+        it "should answer the requests of a client" {
+            let handle = thread::spawn(move || {
+                server.serve_client(&mut wm).unwrap();
+                wm
+            });
+
+            let mut client = IpcClient::connect(&path).unwrap();
+            expect!(client.request(Request::GetFocusedWindow).unwrap()).to(be_equal_to(Json::U64(2)));
+            client.command("toggle floating").unwrap();
+            expect!(client.request(Request::GetFloatingWindows).unwrap()).to(be_equal_to(Json::Array(vec![Json::U64(2)])));
+            client.command("move 1 1").unwrap();
+
+            let workspaces = client.request(Request::GetWorkspaces).unwrap();
+            expect!(workspaces.find("current")).to(be_equal_to(Some(&Json::U64(0))));
+            let second = workspaces.find("workspaces").and_then(|ws| ws.as_array()).map(|ws| ws[1].clone());
+            expect!(second.as_ref().and_then(|ws| ws.find("windows")).cloned()).to(be_equal_to(Some(Json::Array(vec![Json::U64(1)]))));
+
+            match client.request(Request::GetWindowInfo(3)) {
+                Err(IpcError::ServerError(error)) => expect!(error).to(be_equal_to(String::from("Unknown window: 3"))),
+                result => panic!("Expected a server error, got {:?}", result),
+            }
+
+            drop(client);
+            let wm = handle.join().unwrap();
+            expect!(wm.get_workspace(1).unwrap().get_windows()).to(be_equal_to(vec![1]));
+        }
+
+        it "should not block without clients" {
+            expect!(server.serve_pending_clients(&mut wm)).to(be_ok());
+        }
+
+        it "should answer complete requests without waiting for the client" {
+            let mut stream = UnixStream::connect(&path).unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+            write!(stream, "{}\n{}", r#"{"request": "get_windows"}"#, r#"{"request": "get_focused"#).unwrap();
+            server.serve_pending_clients(&mut wm).unwrap();
+            let mut response = String::new();
+            reader.read_line(&mut response).unwrap();
+            expect!(response).to(be_equal_to(String::from("{\"result\":[1,2]}\n")));
+
+            write!(stream, "{}\n", r#"_window"}"#).unwrap();
+            server.serve_pending_clients(&mut wm).unwrap();
+            let mut response = String::new();
+            reader.read_line(&mut response).unwrap();
+            expect!(response).to(be_equal_to(String::from("{\"result\":2}\n")));
+            expect!(server.clients.len()).to(be_equal_to(1));
+        }
+
+        it "should drop the clients that close the connection" {
+            let stream = UnixStream::connect(&path).unwrap();
+            server.serve_pending_clients(&mut wm).unwrap();
+            expect!(server.clients.len()).to(be_equal_to(1));
+
+            drop(stream);
+            server.serve_pending_clients(&mut wm).unwrap();
+
+            expect!(server.clients.len()).to(be_equal_to(0));
+        }
+
+        it "should replace a stale socket" {
+            drop(server);
+            let listener = UnixListener::bind(&path).unwrap();
+            drop(listener);
+
+            expect!(IpcServer::bind(&path)).to(be_ok());
+        }
+
+        it "should not replace the socket of a running server" {
+            expect!(IpcServer::bind(&path)).to(be_err());
+            expect!(UnixStream::connect(&path)).to(be_ok());
+        }
+
+        it "should not replace other files" {
+            drop(server);
+            File::create(&path).unwrap();
+
+            expect!(IpcServer::bind(&path)).to(be_err());
+            expect!(path.is_file()).to(be_true());
+            fs::remove_file(&path).unwrap();
+        }
+
+        it "should remove the socket when the server is dropped" {
+            drop(server);
+
+            expect!(path.exists()).to(be_false());
+        }
+    }
+}
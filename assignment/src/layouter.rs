@@ -1,11 +1,65 @@
 //! This module provides the Layouter interface for layouting tiled windows
 use rustc_serialize::{Decodable, Encodable};
 use std::fmt::Debug;
+use std::os::raw::c_uint;
 
 use cplwm_api::types::{GapSize, Geometry, Screen};
 
+/// The gaps at the edges of the screen, or the gaps around a single tile
+#[derive(RustcDecodable, RustcEncodable, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "with-serde", derive(Serialize, Deserialize))]
+pub struct OuterGaps {
+    /// The gap at the top
+    pub top: GapSize,
+    /// The gap at the bottom
+    pub bottom: GapSize,
+    /// The gap at the left
+    pub left: GapSize,
+    /// The gap at the right
+    pub right: GapSize,
+}
+
+impl OuterGaps {
+    /// No gaps
+    pub fn new() -> OuterGaps {
+        OuterGaps::default()
+    }
+
+    /// The same gap on every side
+    pub fn uniform(gap: GapSize) -> OuterGaps {
+        OuterGaps {
+            top: gap,
+            bottom: gap,
+            left: gap,
+            right: gap,
+        }
+    }
+}
+
+/// The layouts to choose from
+#[derive(RustcDecodable, RustcEncodable, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "with-serde", derive(Serialize, Deserialize))]
+pub enum Layout {
+    /// A master area on the left and the slaves stacked on the right (see `SimpleLayouter`)
+    Simple,
+    /// Every tile takes half of the space that is left (see `SpiralLayouter`)
+    Spiral,
+}
+
+impl Layout {
+    /// Return the name of the layout as commands and events write it, e.g. `simple`
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Layout::Simple => "simple",
+            Layout::Spiral => "spiral",
+        }
+    }
+}
+
 /// A trait to layout tiling windows
 /// One must implement get_geom OR (get_master_geom AND get_slave_geom)
+/// A layouter that can calculate all geometries in one pass should implement get_geoms as well
+/// The geometries of the windows cover the screen exactly: every pixel belongs to exactly one window
 pub trait Layouter: Encodable + Decodable + Debug + Clone {
     /// Return the geometry for the window at position i
     /// in the given screen and with the given number of windows
@@ -28,21 +82,361 @@ pub trait Layouter: Encodable + Decodable + Debug + Clone {
         self.get_geom(i + 1, screen, nb_windows)
     }
 
+    /// Return the geometries of all windows in the given screen, in the order of their positions.
+    ///
+    /// The window managers lay out all windows at once with this method. By default it calls get_geom for every
+    /// position.
+    fn get_geoms(&self, screen: Screen, nb_windows: usize) -> Vec<Geometry> {
+        (0..nb_windows).map(|i| self.get_geom(i, screen, nb_windows)).collect()
+    }
+
     /// Create a new instance of the layouter
     fn new() -> Self;
 }
 
 
-/// GapSupport in the Layouter. Similar to GapSupport for WindowManager but the upper trait bound is not enforced.
+/// GapSupport in the Layouter. Similar to GapSupport and ExtendedGapSupport for WindowManager but the upper trait
+/// bound is not enforced.
 pub trait GapSupport {
-    /// Return the current gap size.
+    /// Return the gap around every tile: half of the inner gap (rounded down), whatever the outer gaps are.
     ///
     /// Initially 0.
     fn get_gap(&self) -> GapSize;
 
-    /// Set the gap size.
+    /// Set the gap around every tile: an inner gap of `2 * g` and an outer gap of `g` on every side.
     ///
     /// **Invariant**: after setting `set_gap(g)` with some gap size `g`,
     /// `get_gap() == g`.
     fn set_gap(&mut self, GapSize);
+
+    /// Return the space between two tiles.
+    fn get_inner_gap(&self) -> GapSize;
+
+    /// Set the space between two tiles.
+    fn set_inner_gap(&mut self, gap: GapSize);
+
+    /// Return the spaces between the tiles and the edges of the screen.
+    fn get_outer_gaps(&self) -> OuterGaps;
+
+    /// Set the spaces between the tiles and the edges of the screen.
+    fn set_outer_gaps(&mut self, gaps: OuterGaps);
+
+    /// Return whether a single tile gets no gaps.
+    fn get_smart_gaps(&self) -> bool;
+
+    /// Turn smart gaps on or off.
+    fn set_smart_gaps(&mut self, smart_gaps: bool);
+
+    /// Return the minimum width and height of a tile after taking the gaps.
+    ///
+    /// The gaps of a tile shrink when the tile is too small for them, down to no gaps at all when the tile itself is
+    /// smaller than the minimum window size.
+    fn get_min_window_size(&self) -> c_uint;
+
+    /// Set the minimum window size.
+    fn set_min_window_size(&mut self, size: c_uint);
+
+    /// Return whether the full gaps fit in every tile of the given screen with the given number of windows
+    fn honours_gap(&self, screen: Screen, nb_windows: usize) -> bool;
+}
+
+
+/// LayoutSupport in the Layouter. Similar to LayoutSupport for WindowManager but the upper trait bound is not
+/// enforced.
+pub trait LayoutSupport {
+    /// Return the layout that is used.
+    fn get_layout(&self) -> Layout;
+
+    /// Set the layout that is used.
+    fn set_layout(&mut self, layout: Layout);
+}
+
+
+/// MasterSupport in the Layouter. Allows configuring the size of the master area and the number of windows in it.
+pub trait MasterSupport {
+    /// Return the fraction of the screen width that is used by the master area.
+    ///
+    /// Initially 0.5.
+    fn get_master_ratio(&self) -> f64;
+
+    /// Set the fraction of the screen width that is used by the master area.
+    ///
+    /// The ratio may be clamped by the layouter.
+    fn set_master_ratio(&mut self, ratio: f64);
+
+    /// Return the number of windows in the master area.
+    ///
+    /// Initially 1.
+    fn get_nb_masters(&self) -> usize;
+
+    /// Set the number of windows in the master area.
+    ///
+    /// The number may be clamped by the layouter.
+    fn set_nb_masters(&mut self, nb_masters: usize);
+}
+
+#[cfg(test)]
+#[allow(unused_mut)]
+#[allow(unused_variables)]
+mod tests {
+    pub use super::*;
+
+    pub use std::cmp;
+    pub use std::os::raw::{c_int, c_uint};
+    pub use quickcheck::{QuickCheck, TestResult};
+
+    pub use b_tiling_wm::SimpleLayouter;
+    pub use f_gaps::{GappedLayouter, OuterGaps};
+    pub use h_different_tiling_layout::SpiralLayouter;
+
+    // Import expectest names
+    pub use expectest::prelude::*;
+
+    /// Check that the geometries cover the screen exactly: they lie in the screen, they don't overlap and together
+    /// they're as large as the screen
+    pub fn check_coverage(geoms: &[Geometry], screen: Screen) -> Result<(), String> {
+        let area = |geom: &Geometry| geom.width as u64 * geom.height as u64;
+
+        for (i, geom) in geoms.iter().enumerate() {
+            if geom.x < 0 || geom.y < 0 || geom.x as u64 + geom.width as u64 > screen.width as u64 ||
+               geom.y as u64 + geom.height as u64 > screen.height as u64 {
+                return Err(format!("geometry {} ({}) doesn't lie in the screen {:?}", i, geom, screen));
+            }
+
+            for (j, other) in geoms.iter().enumerate().skip(i + 1) {
+                let overlap = area(geom) > 0 && area(other) > 0 &&
+                              geom.x < other.x + other.width as c_int &&
+                              other.x < geom.x + geom.width as c_int &&
+                              geom.y < other.y + other.height as c_int &&
+                              other.y < geom.y + geom.height as c_int;
+                if overlap {
+                    return Err(format!("geometry {} ({}) overlaps with geometry {} ({})", i, geom, j, other));
+                }
+            }
+        }
+
+        let covered: u64 = geoms.iter().map(area).sum();
+        if covered != screen.width as u64 * screen.height as u64 {
+            return Err(format!("the geometries cover {} of the {} pixels of the screen {:?}",
+                               covered,
+                               screen.width as u64 * screen.height as u64,
+                               screen));
+        }
+
+        Ok(())
+    }
+
+    /// Turn the result of a check into the result of a property
+    pub fn to_test_result(result: Result<(), String>) -> TestResult {
+        match result {
+            Ok(()) => TestResult::passed(),
+            Err(error) => TestResult::error(error),
+        }
+    }
+
+    /// A screen of at most 4000 by 4000 pixels and 1 to 50 windows
+    pub fn screen_and_nb_windows(width: u16, height: u16, nb_windows: u8) -> (Screen, usize) {
+        (Screen {
+            width: width as u32 % 4000 + 1,
+            height: height as u32 % 4000 + 1,
+        },
+         nb_windows as usize % 50 + 1)
+    }
+
+    /// The simple layouter covers the screen for any master ratio and number of masters
+    pub fn simple_layouter_covers_the_screen(width: u16, height: u16, nb_windows: u8, ratio: u8, nb_masters: u8)
+                                             -> TestResult {
+        let (screen, nb_windows) = screen_and_nb_windows(width, height, nb_windows);
+        let mut layouter = SimpleLayouter::new();
+        layouter.set_master_ratio(ratio as f64 / 255.0);
+        layouter.set_nb_masters(nb_masters as usize % 5 + 1);
+
+        to_test_result(check_coverage(&layouter.get_geoms(screen, nb_windows), screen))
+    }
+
+    /// The spiral layouter covers the screen
+    pub fn spiral_layouter_covers_the_screen(width: u16, height: u16, nb_windows: u8) -> TestResult {
+        let (screen, nb_windows) = screen_and_nb_windows(width, height, nb_windows);
+
+        to_test_result(check_coverage(&SpiralLayouter::new().get_geoms(screen, nb_windows), screen))
+    }
+
+    /// The gapped layouter leaves the gap around every tile: with the gaps the tiles cover the screen
+    pub fn gapped_layouter_covers_the_screen_with_the_gaps(width: u16, height: u16, nb_windows: u8, gap: u8)
+                                                           -> TestResult {
+        let (screen, nb_windows) = screen_and_nb_windows(width, height, nb_windows);
+        let gap = gap as GapSize % 20;
+        let mut layouter: GappedLayouter<SimpleLayouter> = GappedLayouter::new();
+        layouter.set_gap(gap);
+
+        // The gap shrinks in tiles that are too small
+        if !layouter.honours_gap(screen, nb_windows) {
+            return TestResult::discard();
+        }
+
+        let signed_gap = gap as c_int;
+        let with_gaps: Vec<Geometry> = layouter.get_geoms(screen, nb_windows)
+            .into_iter()
+            .map(|geom| {
+                Geometry {
+                    x: geom.x - signed_gap,
+                    y: geom.y - signed_gap,
+                    width: geom.width + 2 * gap,
+                    height: geom.height + 2 * gap,
+                }
+            })
+            .collect();
+
+        to_test_result(check_coverage(&with_gaps, screen))
+    }
+
+    /// The gapped layouter centres every window in its tile, with at most the gap around it and at least the minimum
+    /// window size (unless the tile itself is smaller)
+    pub fn gapped_layouter_keeps_the_minimum_window_size(width: u16,
+                                                         height: u16,
+                                                         nb_windows: u8,
+                                                         gap: u16,
+                                                         min_window_size: u8)
+                                                         -> TestResult {
+        let (screen, nb_windows) = screen_and_nb_windows(width, height, nb_windows);
+        let (gap, min_window_size) = (gap as GapSize, min_window_size as c_uint);
+        let mut layouter: GappedLayouter<SimpleLayouter> = GappedLayouter::new();
+        layouter.set_gap(gap);
+        layouter.set_min_window_size(min_window_size);
+
+        let tiles = layouter.wrapped_layouter.get_geoms(screen, nb_windows);
+        let geoms = layouter.get_geoms(screen, nb_windows);
+        let mut honoured = true;
+        for (i, (tile, geom)) in tiles.iter().zip(geoms.iter()).enumerate() {
+            let horizontal_gap = (geom.x - tile.x) as i64;
+            let vertical_gap = (geom.y - tile.y) as i64;
+            let centred = tile.width as i64 == geom.width as i64 + 2 * horizontal_gap &&
+                          tile.height as i64 == geom.height as i64 + 2 * vertical_gap;
+            let gaps_fit = 0 <= horizontal_gap && horizontal_gap <= gap as i64 && 0 <= vertical_gap &&
+                           vertical_gap <= gap as i64;
+            let large_enough = geom.width >= cmp::min(tile.width, min_window_size) &&
+                               geom.height >= cmp::min(tile.height, min_window_size);
+            if !centred || !gaps_fit || !large_enough {
+                return TestResult::error(format!("geometry {} ({}) doesn't fit in tile {} with gap {} and minimum \
+                                                  window size {}",
+                                                 i,
+                                                 geom,
+                                                 tile,
+                                                 gap,
+                                                 min_window_size));
+            }
+            honoured = honoured && horizontal_gap == gap as i64 && vertical_gap == gap as i64;
+        }
+
+        TestResult::from_bool(honoured == layouter.honours_gap(screen, nb_windows))
+    }
+
+    /// The gapped layouter leaves the outer gaps at the edges of the screen and the inner gap between the tiles, when
+    /// the gaps fit
+    pub fn gapped_layouter_leaves_the_inner_and_outer_gaps(width: u16,
+                                                           height: u16,
+                                                           nb_windows: u8,
+                                                           inner_gap: u8,
+                                                           outer_gaps: (u8, u8, u8, u8))
+                                                           -> TestResult {
+        let (screen, nb_windows) = screen_and_nb_windows(width, height, nb_windows);
+        let (inner_gap, (top, bottom, left, right)) = (inner_gap as GapSize, outer_gaps);
+        let mut layouter: GappedLayouter<SimpleLayouter> = GappedLayouter::new();
+        layouter.set_inner_gap(inner_gap);
+        layouter.set_outer_gaps(OuterGaps {
+            top: top as GapSize,
+            bottom: bottom as GapSize,
+            left: left as GapSize,
+            right: right as GapSize,
+        });
+        if !layouter.honours_gap(screen, nb_windows) {
+            return TestResult::discard();
+        }
+
+        // Half of the inner gap on both sides of an edge between 2 tiles, the larger half after the edge
+        let (before_edge, after_edge) = (inner_gap / 2, inner_gap - inner_gap / 2);
+        let tiles = layouter.wrapped_layouter.get_geoms(screen, nb_windows);
+        let geoms = layouter.get_geoms(screen, nb_windows);
+        for (i, (tile, geom)) in tiles.iter().zip(geoms.iter()).enumerate() {
+            let expected = OuterGaps {
+                top: if tile.y == 0 { top as GapSize } else { after_edge },
+                bottom: if tile.y as u32 + tile.height == screen.height { bottom as GapSize } else { before_edge },
+                left: if tile.x == 0 { left as GapSize } else { after_edge },
+                right: if tile.x as u32 + tile.width == screen.width { right as GapSize } else { before_edge },
+            };
+            let actual = OuterGaps {
+                top: (geom.y - tile.y) as GapSize,
+                bottom: (tile.y + tile.height as c_int - geom.y - geom.height as c_int) as GapSize,
+                left: (geom.x - tile.x) as GapSize,
+                right: (tile.x + tile.width as c_int - geom.x - geom.width as c_int) as GapSize,
+            };
+            if actual != expected {
+                return TestResult::error(format!("geometry {} ({}) in tile {} has gaps {:?} instead of {:?}",
+                                                 i,
+                                                 geom,
+                                                 tile,
+                                                 actual,
+                                                 expected));
+            }
+        }
+
+        TestResult::passed()
+    }
+
+    describe! coverage {
+        before_each {
+            let odd_screen: Screen = Screen {
+                width: 801,
+                height: 601,
+            };
+        }
+
+        it "should cover the screen with the simple layouter" {
+            QuickCheck::new()
+                .tests(500)
+                .quickcheck(simple_layouter_covers_the_screen as fn(u16, u16, u8, u8, u8) -> TestResult);
+        }
+
+        it "should cover the screen with the spiral layouter" {
+            QuickCheck::new()
+                .tests(500)
+                .quickcheck(spiral_layouter_covers_the_screen as fn(u16, u16, u8) -> TestResult);
+        }
+
+        it "should cover the screen with the gapped layouter and its gaps" {
+            QuickCheck::new()
+                .tests(500)
+                .quickcheck(gapped_layouter_covers_the_screen_with_the_gaps as fn(u16, u16, u8, u8) -> TestResult);
+        }
+
+        it "should leave the inner and outer gaps with the gapped layouter" {
+            QuickCheck::new()
+                .tests(500)
+                .quickcheck(gapped_layouter_leaves_the_inner_and_outer_gaps as
+                            fn(u16, u16, u8, u8, (u8, u8, u8, u8)) -> TestResult);
+        }
+
+        it "should keep the minimum window size with the gapped layouter" {
+            QuickCheck::new()
+                .tests(500)
+                .quickcheck(gapped_layouter_keeps_the_minimum_window_size as fn(u16, u16, u8, u16, u8) -> TestResult);
+        }
+
+        it "should spread the remaining pixels over the slaves of the simple layouter" {
+            let geoms = SimpleLayouter::new().get_geoms(odd_screen, 4);
+
+            expect!(geoms).to(be_equal_to(vec![Geometry { x: 0, y: 0, width: 400, height: 601 },
+                                               Geometry { x: 400, y: 0, width: 401, height: 200 },
+                                               Geometry { x: 400, y: 200, width: 401, height: 200 },
+                                               Geometry { x: 400, y: 400, width: 401, height: 201 }]));
+        }
+
+        it "should give the remaining pixel to the rest of the screen in the spiral layouter" {
+            let geoms = SpiralLayouter::new().get_geoms(odd_screen, 3);
+
+            expect!(geoms).to(be_equal_to(vec![Geometry { x: 0, y: 0, width: 400, height: 601 },
+                                               Geometry { x: 400, y: 0, width: 401, height: 300 },
+                                               Geometry { x: 400, y: 300, width: 401, height: 301 }]));
+        }
+    }
 }
@@ -0,0 +1,175 @@
+//! This module provides a layouter whose layout is chosen at runtime
+//!
+//! The window managers are generic over their layouter, so the layouter type is fixed when the window manager is
+//! compiled. `SwitchableLayouter` contains a layouter for every `Layout` and tiles the windows with the chosen one,
+//! which lets every workspace use its own layout. The layouters that aren't chosen keep their settings, e.g. the
+//! master ratio of the simple layout is still the same after switching to the spiral layout and back.
+//!
+
+use cplwm_api::types::{Geometry, Screen};
+use cplwm_api::wm::WindowManager;
+
+use b_tiling_wm::{SimpleLayouter, TilingWM};
+use h_different_tiling_layout::SpiralLayouter;
+pub use layouter::Layout;
+use layouter::{Layouter, MasterSupport};
+use layouter::LayoutSupport as GenericLayoutSupport;
+
+/// A window manager whose tiling layout can be chosen at runtime
+pub trait LayoutSupport: WindowManager {
+    /// Return the layout of the tiled windows.
+    ///
+    /// Initially `Layout::Simple`.
+    fn get_layout(&self) -> Layout;
+
+    /// Tile the windows with the given layout.
+    ///
+    /// The order of the windows and the focus don't change.
+    fn set_layout(&mut self, layout: Layout);
+}
+
+/// The struct for a layouter that tiles with the chosen layout
+#[derive(RustcDecodable, RustcEncodable, Debug, Clone)]
+#[cfg_attr(feature = "with-serde", derive(Serialize, Deserialize))]
+pub struct SwitchableLayouter {
+    /// The chosen layout
+    pub layout: Layout,
+    /// The layouter of `Layout::Simple`
+    pub simple: SimpleLayouter,
+    /// The layouter of `Layout::Spiral`
+    pub spiral: SpiralLayouter,
+}
+
+impl Layouter for SwitchableLayouter {
+    fn get_geom(&self, i: usize, screen: Screen, nb_windows: usize) -> Geometry {
+        match self.layout {
+            Layout::Simple => self.simple.get_geom(i, screen, nb_windows),
+            Layout::Spiral => self.spiral.get_geom(i, screen, nb_windows),
+        }
+    }
+
+    fn get_geoms(&self, screen: Screen, nb_windows: usize) -> Vec<Geometry> {
+        match self.layout {
+            Layout::Simple => self.simple.get_geoms(screen, nb_windows),
+            Layout::Spiral => self.spiral.get_geoms(screen, nb_windows),
+        }
+    }
+
+    fn new() -> SwitchableLayouter {
+        SwitchableLayouter {
+            layout: Layout::Simple,
+            simple: SimpleLayouter::new(),
+            spiral: SpiralLayouter::new(),
+        }
+    }
+}
+
+impl GenericLayoutSupport for SwitchableLayouter {
+    fn get_layout(&self) -> Layout {
+        self.layout
+    }
+
+    fn set_layout(&mut self, layout: Layout) {
+        self.layout = layout;
+    }
+}
+
+/// The master settings are the ones of the simple layout, the spiral layout has no master area
+impl MasterSupport for SwitchableLayouter {
+    fn get_master_ratio(&self) -> f64 {
+        self.simple.get_master_ratio()
+    }
+
+    fn set_master_ratio(&mut self, ratio: f64) {
+        self.simple.set_master_ratio(ratio)
+    }
+
+    fn get_nb_masters(&self) -> usize {
+        self.simple.get_nb_masters()
+    }
+
+    fn set_nb_masters(&mut self, nb_masters: usize) {
+        self.simple.set_nb_masters(nb_masters)
+    }
+}
+
+impl<MyLayouter: GenericLayoutSupport + Layouter> LayoutSupport for TilingWM<MyLayouter> {
+    fn get_layout(&self) -> Layout {
+        self.layouter.get_layout()
+    }
+
+    fn set_layout(&mut self, layout: Layout) {
+        self.layouter.set_layout(layout)
+    }
+}
+
+#[cfg(test)]
+#[allow(unused_mut)]
+#[allow(unused_variables)]
+mod tests {
+    pub use super::*;
+
+    pub use cplwm_api::wm::*;
+    pub use cplwm_api::types::*;
+    pub use g_multiple_workspaces::WMName;
+
+    // Import expectest names
+    pub use expectest::prelude::*;
+
+    describe! layouts {
+        before_each {
+            let screen: Screen = Screen {
+                width: 800,
+                height: 600,
+            };
+
+            let some_geom: Geometry = Geometry {
+                x: 10,
+                y: 10,
+                width: 100,
+                height: 100,
+            };
+
+            let mut wm = WMName::new(screen);
+            for window in 1..5 {
+                wm.add_window(WindowWithInfo::new_tiled(window, some_geom)).unwrap();
+            }
+        }
+
+        it "should use the simple layout initially" {
+            expect!(wm.get_layout()).to(be_equal_to(Layout::Simple));
+            expect!(wm.get_window_info(2).unwrap().geometry)
+                .to(be_equal_to(Geometry { x: 400, y: 0, width: 400, height: 200 }));
+        }
+
+        it "should tile the windows with the chosen layout" {
+            wm.set_layout(Layout::Spiral);
+
+            expect!(wm.get_layout()).to(be_equal_to(Layout::Spiral));
+            expect!(wm.get_window_layout().windows)
+                .to(be_equal_to(vec![(1, Geometry { x: 0, y: 0, width: 400, height: 600 }),
+                                     (2, Geometry { x: 400, y: 0, width: 400, height: 300 }),
+                                     (3, Geometry { x: 600, y: 300, width: 200, height: 300 }),
+                                     (4, Geometry { x: 400, y: 300, width: 200, height: 300 })]));
+            expect!(wm.get_focused_window()).to(be_equal_to(Some(4)));
+        }
+
+        it "should give every workspace its own layout" {
+            wm.set_layout(Layout::Spiral);
+            wm.switch_workspace(1).unwrap();
+
+            expect!(wm.get_layout()).to(be_equal_to(Layout::Simple));
+            expect!(wm.get_workspace(0).unwrap().get_layout()).to(be_equal_to(Layout::Spiral));
+        }
+
+        it "should keep the settings of a layout while it isn't chosen" {
+            let mut layouter = SwitchableLayouter::new();
+            layouter.set_master_ratio(0.75);
+            layouter.set_layout(Layout::Spiral);
+            layouter.set_layout(Layout::Simple);
+
+            expect!(layouter.get_master_ratio()).to(be_equal_to(0.75));
+            expect!(layouter.get_geom(0, screen, 2)).to(be_equal_to(Geometry { x: 0, y: 0, width: 600, height: 600 }));
+        }
+    }
+}
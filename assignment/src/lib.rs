@@ -70,13 +70,25 @@ extern crate rustc_serialize;
 
 // Add any dependencies below:
 extern crate test;
+#[cfg(feature = "with-serde")]
+extern crate serde;
+#[cfg(feature = "with-serde")]
+#[macro_use]
+extern crate serde_derive;
 #[cfg(test)]
 #[macro_use(expect)]
 extern crate expectest;
+#[cfg(test)]
+extern crate quickcheck;
 
 
 // Declare the modules of which this project consists:
 
+// The conformance tests of the traits of the API, declared first so its macro can be used by the other modules
+#[cfg(test)]
+#[macro_use]
+pub mod conformance;
+
 // Mandatory assignments
 pub mod a_fullscreen_wm;
 pub mod b_tiling_wm;
@@ -92,4 +104,22 @@ pub mod h_different_tiling_layout;
 // Declare additional modules below or declare them in other modules.
 pub mod error;
 pub mod layouter;
+pub mod layouts;
 pub mod fixed_window_manager;
+pub mod direction;
+pub mod outputs;
+pub mod struts;
+pub mod window_description;
+pub mod rules;
+pub mod command;
+pub mod ipc;
+pub mod events;
+pub mod session;
+pub mod migrations;
+pub mod trace;
+pub mod render;
+pub mod svg;
+#[cfg(test)]
+mod properties;
+#[cfg(test)]
+mod reference;
@@ -0,0 +1,246 @@
+//! This module upgrades the encoded state of older session files to the current version
+//!
+//! Every change to the encoded state of the window managers bumps `SESSION_VERSION` and adds a migration to
+//! `MIGRATIONS`. `MIGRATIONS[i]` upgrades a state of version `i` to version `i + 1`. A migration works on the JSON
+//! tree of the state, so it doesn't depend on the current structs: the values it inserts are literal values of the
+//! version it upgrades to.
+//!
+//! Session files that were written before the versioned envelope existed are version 0.
+//!
+
+use std::cmp;
+use std::collections::BTreeMap;
+
+use rustc_serialize::json::Json;
+
+use error::SessionError;
+
+/// The version of the encoded state written by this version of the window manager
+pub const SESSION_VERSION: u64 = 4;
+
+/// The migrations of the encoded state, `MIGRATIONS[i]` upgrades version `i` to version `i + 1`
+const MIGRATIONS: &'static [fn(Json) -> Json] = &[migrate_v0_to_v1, migrate_v1_to_v2, migrate_v2_to_v3, migrate_v3_to_v4];
+
+/// Upgrade the encoded state of the given version to `SESSION_VERSION`.
+///
+/// Returns `UnsupportedVersion` if the state is newer than `SESSION_VERSION`.
+pub fn migrate(state: Json, version: u64) -> Result<Json, SessionError> {
+    if version > SESSION_VERSION {
+        return Err(SessionError::UnsupportedVersion(version));
+    }
+
+    Ok(MIGRATIONS[version as usize..].iter().fold(state, |state, migration| migration(state)))
+}
+
+/// Call `f` on every object in the JSON tree, parents before their children
+fn visit_objects<F: Fn(&mut BTreeMap<String, Json>)>(json: &mut Json, f: &F) {
+    match *json {
+        Json::Object(ref mut object) => {
+            f(object);
+            for value in object.values_mut() {
+                visit_objects(value, f);
+            }
+        }
+        Json::Array(ref mut array) => {
+            for value in array.iter_mut() {
+                visit_objects(value, f);
+            }
+        }
+        _ => (),
+    }
+}
+
+/// Version 1 added struts and docks, fullscreen windows covering the struts and the master settings of the
+/// tiling layout.
+///
+/// * A `TilingWM` gets no reserved space and no docks.
+/// * A `FullscreenWM` covers the struts (the behaviour of version 0).
+/// * A `SimpleLayouter` (which used to be encoded as an empty object) gets 1 master window taking half of the
+///   screen (the layout of version 0).
+fn migrate_v0_to_v1(mut state: Json) -> Json {
+    visit_objects(&mut state, &|object| {
+        if object.contains_key("focused_index") && object.contains_key("layouter") &&
+           !object.contains_key("struts") {
+            let mut struts = BTreeMap::new();
+            for side in &["top", "bottom", "left", "right"] {
+                struts.insert(side.to_string(), Json::U64(0));
+            }
+            object.insert("struts".to_string(), Json::Object(struts));
+            object.insert("docks".to_string(), Json::Array(vec![]));
+        }
+
+        if object.contains_key("fullscreen_window") && !object.contains_key("cover_struts") {
+            object.insert("cover_struts".to_string(), Json::Boolean(true));
+        }
+
+        for key in &["layouter", "wrapped_layouter"] {
+            if let Some(layouter) = object.get_mut(*key) {
+                if *layouter == Json::Object(BTreeMap::new()) {
+                    let mut simple_layouter = BTreeMap::new();
+                    simple_layouter.insert("master_ratio".to_string(), Json::F64(0.5));
+                    simple_layouter.insert("nb_masters".to_string(), Json::U64(1));
+                    *layouter = Json::Object(simple_layouter);
+                }
+            }
+        }
+    });
+
+    state
+}
+
+/// Version 2 added the minimum window size of the gapped layouter.
+///
+/// * A `GappedLayouter` gets a minimum window size of 1 pixel, the smallest size a gap could leave in version 1.
+fn migrate_v1_to_v2(mut state: Json) -> Json {
+    visit_objects(&mut state, &|object| {
+        if object.contains_key("gap_size") && object.contains_key("wrapped_layouter") &&
+           !object.contains_key("min_window_size") {
+            object.insert("min_window_size".to_string(), Json::U64(1));
+        }
+    });
+
+    state
+}
+
+/// Version 3 split the gap of the gapped layouter into an inner gap and outer gaps and added smart gaps.
+///
+/// * A `GappedLayouter` gets twice its gap as the inner gap and its gap as the outer gap on every side, the layout
+///   of version 2, and no smart gaps.
+fn migrate_v2_to_v3(mut state: Json) -> Json {
+    visit_objects(&mut state, &|object| {
+        if !object.contains_key("wrapped_layouter") {
+            return;
+        }
+
+        if let Some(gap) = object.remove("gap_size").and_then(|gap| gap.as_u64()) {
+            let mut outer_gaps = BTreeMap::new();
+            for side in &["top", "bottom", "left", "right"] {
+                outer_gaps.insert(side.to_string(), Json::U64(gap));
+            }
+            object.insert("inner_gap".to_string(), Json::U64(cmp::min(2 * gap, u32::max_value() as u64)));
+            object.insert("outer_gaps".to_string(), Json::Object(outer_gaps));
+            object.insert("smart_gaps".to_string(), Json::Boolean(false));
+        }
+    });
+
+    state
+}
+
+/// Version 4 let every workspace choose its layout at runtime. The gaps were already encoded per workspace.
+///
+/// * A `GappedLayouter` wrapping a `SimpleLayouter` in a workspace (an element of the `wrapped_wms` of a
+///   `WorkspaceWM`) wraps a `SwitchableLayouter` instead, which uses the simple layout with the same settings.
+/// * The window managers without workspaces (e.g. `f_gaps::WMName`) still use a `SimpleLayouter`, their state
+///   doesn't change.
+fn migrate_v3_to_v4(mut state: Json) -> Json {
+    visit_objects(&mut state, &|object| {
+        if let Some(workspaces) = object.get_mut("wrapped_wms") {
+            visit_objects(workspaces, &|object| {
+                if let Some(layouter) = object.get_mut("wrapped_layouter") {
+                    if layouter.find("master_ratio").is_some() {
+                        let mut switchable_layouter = BTreeMap::new();
+                        switchable_layouter.insert("layout".to_string(), Json::String("Simple".to_string()));
+                        switchable_layouter.insert("simple".to_string(), layouter.clone());
+                        switchable_layouter.insert("spiral".to_string(), Json::Object(BTreeMap::new()));
+                        *layouter = Json::Object(switchable_layouter);
+                    }
+                }
+            });
+        }
+    });
+
+    state
+}
+
+#[cfg(test)]
+#[allow(unused_mut)]
+#[allow(unused_variables)]
+mod tests {
+    pub use super::*;
+
+    pub use rustc_serialize::json::Json;
+
+    pub use error::SessionError;
+
+    // Import expectest names
+    pub use expectest::prelude::*;
+
+    /// Return the state of a `WorkspaceWM` with the given state as its only workspace
+    pub fn workspaces(workspace: &Json) -> Json {
+        Json::from_str(&format!(r#"{{"current_workspace":0,"wrapped_wms":[{}]}}"#, workspace)).unwrap()
+    }
+
+    describe! migrations {
+        before_each {
+            let v0 = Json::from_str(r#"{"windows":[],"screen":{"width":800,"height":600},"focused_index":null,
+                                       "layouter":{"gap_size":10,"wrapped_layouter":{}}}"#).unwrap();
+            let v1 = Json::from_str(r#"{"windows":[],"screen":{"width":800,"height":600},"focused_index":null,
+                                       "layouter":{"gap_size":10,
+                                                   "wrapped_layouter":{"master_ratio":0.5,"nb_masters":1}},
+                                       "struts":{"top":0,"bottom":0,"left":0,"right":0},"docks":[]}"#).unwrap();
+            let v2 = Json::from_str(r#"{"windows":[],"screen":{"width":800,"height":600},"focused_index":null,
+                                       "layouter":{"gap_size":10,"min_window_size":1,
+                                                   "wrapped_layouter":{"master_ratio":0.5,"nb_masters":1}},
+                                       "struts":{"top":0,"bottom":0,"left":0,"right":0},"docks":[]}"#).unwrap();
+            let v3 = Json::from_str(r#"{"windows":[],"screen":{"width":800,"height":600},"focused_index":null,
+                                       "layouter":{"inner_gap":20,
+                                                   "outer_gaps":{"top":10,"bottom":10,"left":10,"right":10},
+                                                   "smart_gaps":false,"min_window_size":1,
+                                                   "wrapped_layouter":{"master_ratio":0.5,"nb_masters":1}},
+                                       "struts":{"top":0,"bottom":0,"left":0,"right":0},"docks":[]}"#).unwrap();
+            let v4_workspace = Json::from_str(r#"{"windows":[],"screen":{"width":800,"height":600},"focused_index":null,
+                                       "layouter":{"inner_gap":20,
+                                                   "outer_gaps":{"top":10,"bottom":10,"left":10,"right":10},
+                                                   "smart_gaps":false,"min_window_size":1,
+                                                   "wrapped_layouter":{"layout":"Simple",
+                                                                       "simple":{"master_ratio":0.5,"nb_masters":1},
+                                                                       "spiral":{}}},
+                                       "struts":{"top":0,"bottom":0,"left":0,"right":0},"docks":[]}"#).unwrap();
+            let v4 = workspaces(&v4_workspace);
+        }
+
+        it "should upgrade a version 0 state" {
+            expect!(migrate(workspaces(&v0), 0).unwrap()).to(be_equal_to(v4));
+        }
+
+        it "should upgrade a version 1 state" {
+            expect!(migrate(workspaces(&v1), 1).unwrap()).to(be_equal_to(v4));
+        }
+
+        it "should upgrade a version 2 state" {
+            expect!(migrate(workspaces(&v2), 2).unwrap()).to(be_equal_to(v4));
+        }
+
+        it "should upgrade a version 3 state" {
+            expect!(migrate(workspaces(&v3), 3).unwrap()).to(be_equal_to(v4));
+        }
+
+        it "should keep the simple layouter of a window manager without workspaces" {
+            expect!(migrate(v0, 0).unwrap()).to(be_equal_to(v3.clone()));
+            expect!(migrate(v3.clone(), 3).unwrap()).to(be_equal_to(v3));
+        }
+
+        it "should not change a current state" {
+            expect!(migrate(v4.clone(), SESSION_VERSION).unwrap()).to(be_equal_to(v4));
+        }
+
+        it "should only add the missing fields" {
+            let fullscreen = Json::from_str(r#"{"fullscreen_window":null,"cover_struts":false}"#).unwrap();
+            expect!(migrate(fullscreen.clone(), 0).unwrap()).to(be_equal_to(fullscreen));
+
+            let fullscreen = Json::from_str(r#"{"fullscreen_window":null}"#).unwrap();
+            let expected = Json::from_str(r#"{"fullscreen_window":null,"cover_struts":true}"#).unwrap();
+            expect!(migrate(fullscreen, 0).unwrap()).to(be_equal_to(expected));
+
+            let layouter = Json::from_str(r#"{"gap_size":10,"min_window_size":50,"wrapped_layouter":{}}"#).unwrap();
+            expect!(migrate(layouter, 1).unwrap().find("min_window_size").cloned()).to(be_equal_to(Some(Json::U64(50))));
+        }
+
+        it "should refuse a newer version" {
+            match migrate(v4, SESSION_VERSION + 1) {
+                Err(SessionError::UnsupportedVersion(version)) => expect!(version).to(be_equal_to(SESSION_VERSION + 1)),
+                result => panic!("Expected an unsupported version, got {:?}", result),
+            }
+        }
+    }
+}
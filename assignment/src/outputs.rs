@@ -0,0 +1,722 @@
+//! This module provides a window manager for multiple outputs (monitors)
+//!
+//! Every output has a position (in global coordinates) and a size and shows one workspace of the wrapped `WorkspaceWM`.
+//! Two outputs never show the same workspace. The window managers of the workspaces work with local coordinates,
+//! the OutputWM translates all geometries from and to global coordinates.
+//!
+//! The current workspace of the `WorkspaceWM` is always the workspace on the focused output.
+//! Switching to a workspace that is shown on another output will swap the workspaces of both outputs.
+//!
+
+use std::os::raw::{c_int, c_uint};
+
+use cplwm_api::types::{GapSize, Geometry, PrevOrNext, Screen, Window, WindowLayout, WindowWithInfo, WorkspaceIndex};
+use cplwm_api::types::PrevOrNext::*;
+use cplwm_api::wm::{FloatSupport, FullscreenSupport, GapSupport, MinimiseSupport, MultiWorkspaceSupport, TilingSupport, WindowManager};
+
+use g_multiple_workspaces::{MoveWindowSupport, SwitchableWM, WorkspaceWM};
+use error::MultiWMError;
+use error::MultiWMError::*;
+use f_gaps::{ExtendedGapSupport, OuterGaps};
+use fixed_window_manager::RealWindowInfo;
+use layouts::{Layout, LayoutSupport};
+use direction::{Direction, DirectionSupport};
+use struts::StrutSupport;
+use window_description::{DescriptionSupport, WindowDescription};
+use events::{Snapshot, SnapshotSupport};
+
+/// Type alias for automated tests
+pub type WMName = OutputWM<SwitchableWM>;
+
+/// An output (e.g. a monitor)
+#[derive(RustcDecodable, RustcEncodable, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "with-serde", derive(Serialize, Deserialize))]
+pub struct Output {
+    /// The x-coordinate of the top left corner of the output (in global coordinates)
+    pub x: c_int,
+    /// The y-coordinate of the top left corner of the output (in global coordinates)
+    pub y: c_int,
+    /// The size of the output
+    pub screen: Screen,
+    /// The index of the workspace shown on this output
+    pub workspace: WorkspaceIndex,
+}
+
+/// Main struct of the window manager
+/// This WM shows a different workspace on each output
+#[derive(RustcDecodable, RustcEncodable, Debug, Clone)]
+#[cfg_attr(feature = "with-serde", derive(Serialize, Deserialize))]
+pub struct OutputWM<WrappedWM: RealWindowInfo> {
+    /// The outputs, there is always at least one
+    pub outputs: Vec<Output>,
+    /// The index of the focused output
+    pub focused_output: usize,
+    /// The wrapped window manager that manages the workspaces
+    pub workspace_wm: WorkspaceWM<WrappedWM>,
+}
+
+impl<WrappedWM: StrutSupport + RealWindowInfo> OutputWM<WrappedWM> {
+    /// Add an output at the given position (in global coordinates) with the given size and return its index.
+    ///
+    /// The new output shows the first workspace that isn't shown yet (a new workspace is added if necessary).
+    pub fn add_output(&mut self, x: c_int, y: c_int, screen: Screen) -> usize {
+        let nb_workspaces = self.workspace_wm.get_nb_workspaces();
+        let workspace = (0..nb_workspaces)
+            .find(|index| self.get_output_for_workspace(*index).is_none())
+            .unwrap_or_else(|| self.workspace_wm.add_workspace());
+
+        self.outputs.push(Output {
+            x: x,
+            y: y,
+            screen: screen,
+            workspace: workspace,
+        });
+
+        let index = self.outputs.len() - 1;
+        self.resize_workspace(index);
+
+        index
+    }
+}
+
+impl<WrappedWM: RealWindowInfo> OutputWM<WrappedWM> {
+    /// Remove the output at the given index. Its workspace won't be shown anymore.
+    ///
+    /// If the focused output is removed, the first output gets the focus.
+    pub fn remove_output(&mut self, index: usize) -> Result<(), MultiWMError<WrappedWM::Error>> {
+        if index >= self.outputs.len() {
+            return Err(UnknownOutput(index));
+        }
+        if self.outputs.len() == 1 {
+            return Err(LastOutput);
+        }
+
+        self.outputs.remove(index);
+
+        if self.focused_output == index {
+            self.focused_output = 0;
+        } else if self.focused_output > index {
+            self.focused_output -= 1;
+        }
+        self.update_current_workspace();
+
+        Ok(())
+    }
+
+    /// Return the outputs
+    pub fn get_outputs(&self) -> Vec<Output> {
+        self.outputs.clone()
+    }
+
+    /// Return the index of the focused output
+    pub fn get_focused_output(&self) -> usize {
+        self.focused_output
+    }
+
+    /// Focus the output at the given index
+    pub fn focus_output(&mut self, index: usize) -> Result<(), MultiWMError<WrappedWM::Error>> {
+        if index >= self.outputs.len() {
+            return Err(UnknownOutput(index));
+        }
+
+        self.focused_output = index;
+        self.update_current_workspace();
+
+        Ok(())
+    }
+
+    /// Focus the next or previous output
+    pub fn cycle_output_focus(&mut self, dir: PrevOrNext) {
+        let nb_outputs = self.outputs.len();
+        self.focused_output = match dir {
+            Prev => (self.focused_output + nb_outputs - 1) % nb_outputs,
+            Next => (self.focused_output + 1) % nb_outputs,
+        };
+        self.update_current_workspace();
+    }
+
+    /// Show the workspace at the given index on the output at the given index.
+    ///
+    /// If the workspace is shown on another output, that output will show the workspace of the given output.
+    pub fn move_workspace_to_output(&mut self, workspace: WorkspaceIndex, output: usize) -> Result<(), MultiWMError<WrappedWM::Error>> {
+        if output >= self.outputs.len() {
+            return Err(UnknownOutput(output));
+        }
+        try!(self.workspace_wm.get_workspace(workspace));
+
+        let old_workspace = self.outputs[output].workspace;
+        if let Some(other_output) = self.get_output_for_workspace(workspace) {
+            self.outputs[other_output].workspace = old_workspace;
+            self.resize_workspace(other_output);
+        }
+
+        self.outputs[output].workspace = workspace;
+        self.resize_workspace(output);
+        self.update_current_workspace();
+
+        Ok(())
+    }
+
+    /// Return the index of the output that shows the given workspace (if any)
+    fn get_output_for_workspace(&self, workspace: WorkspaceIndex) -> Option<usize> {
+        self.outputs.iter().position(|output| output.workspace == workspace)
+    }
+
+    /// Return the offset of the given workspace in global coordinates.
+    /// A workspace that isn't shown will be shown on the focused output, so it has the offset of the focused output.
+    fn get_offset(&self, workspace: WorkspaceIndex) -> (c_int, c_int) {
+        let output = self.outputs[self.get_output_for_workspace(workspace).unwrap_or(self.focused_output)];
+        (output.x, output.y)
+    }
+
+    /// Return the offset of the workspace of the given window in global coordinates
+    fn get_offset_for_window(&self, window: Window) -> (c_int, c_int) {
+        let workspace = self.workspace_wm
+            .wrapped_wms
+            .iter()
+            .position(|wm| wm.is_managed(window))
+            .unwrap_or(self.workspace_wm.get_current_workspace_index());
+
+        self.get_offset(workspace)
+    }
+
+    /// Resize the workspace shown on the given output to the size of that output
+    fn resize_workspace(&mut self, output: usize) {
+        let output = self.outputs[output];
+        self.workspace_wm.wrapped_wms[output.workspace].resize_screen(output.screen);
+    }
+
+    /// Let the current workspace of the wrapped WM be the workspace of the focused output
+    fn update_current_workspace(&mut self) {
+        self.workspace_wm.current_workspace = self.outputs[self.focused_output].workspace;
+    }
+
+    /// The wrapped WM has switched its current workspace (e.g. to focus a window),
+    /// make sure it's visible and focus its output.
+    fn follow_current_workspace(&mut self) {
+        let workspace = self.workspace_wm.get_current_workspace_index();
+
+        match self.get_output_for_workspace(workspace) {
+            Some(output) => self.focused_output = output,
+            None => {
+                let output = self.focused_output;
+                self.outputs[output].workspace = workspace;
+                self.resize_workspace(output);
+            }
+        }
+    }
+}
+
+/// Translate the geometry by (dx, dy)
+fn translate(geometry: Geometry, (dx, dy): (c_int, c_int)) -> Geometry {
+    Geometry {
+        x: geometry.x + dx,
+        y: geometry.y + dy,
+        width: geometry.width,
+        height: geometry.height,
+    }
+}
+
+impl<WrappedWM: RealWindowInfo> WindowManager for OutputWM<WrappedWM> {
+    /// We use the Error from the WorkspaceWM as our Error type.
+    type Error = MultiWMError<WrappedWM::Error>;
+
+    /// Creates a window manager with one output at (0, 0) with the given screen
+    fn new(screen: Screen) -> OutputWM<WrappedWM> {
+        OutputWM {
+            outputs: vec![Output {
+                              x: 0,
+                              y: 0,
+                              screen: screen,
+                              workspace: 0,
+                          }],
+            focused_output: 0,
+            workspace_wm: WorkspaceWM::new(screen),
+        }
+    }
+
+    fn get_windows(&self) -> Vec<Window> {
+        self.workspace_wm.get_windows()
+    }
+
+    /// The geometry of the window is in global coordinates
+    fn add_window(&mut self, window_with_info: WindowWithInfo) -> Result<(), Self::Error> {
+        let (x, y) = self.get_offset(self.workspace_wm.get_current_workspace_index());
+
+        let mut wi = window_with_info;
+        wi.geometry = translate(wi.geometry, (-x, -y));
+
+        self.workspace_wm.add_window(wi)
+    }
+
+    fn remove_window(&mut self, window: Window) -> Result<(), Self::Error> {
+        self.workspace_wm.remove_window(window)
+    }
+
+    /// The windows of all outputs, in the order of the outputs
+    fn get_window_layout(&self) -> WindowLayout {
+        let windows = self.outputs
+            .iter()
+            .flat_map(|output| {
+                self.workspace_wm.wrapped_wms[output.workspace]
+                    .get_window_layout()
+                    .windows
+                    .into_iter()
+                    .map(move |(w, geom)| (w, translate(geom, (output.x, output.y))))
+            })
+            .collect();
+
+        WindowLayout {
+            focused_window: self.get_focused_window(),
+            windows: windows,
+        }
+    }
+
+    /// Will focus the output of the window (or show its workspace on the focused output)
+    fn focus_window(&mut self, window: Option<Window>) -> Result<(), Self::Error> {
+        try!(self.workspace_wm.focus_window(window));
+        self.follow_current_workspace();
+
+        Ok(())
+    }
+
+    fn cycle_focus(&mut self, dir: PrevOrNext) {
+        self.workspace_wm.cycle_focus(dir)
+    }
+
+    /// The geometry of the window is in global coordinates
+    fn get_window_info(&self, window: Window) -> Result<WindowWithInfo, Self::Error> {
+        let mut wi = try!(self.workspace_wm.get_window_info(window));
+        wi.geometry = translate(wi.geometry, self.get_offset_for_window(window));
+
+        Ok(wi)
+    }
+
+    /// Returns the screen of the focused output
+    fn get_screen(&self) -> Screen {
+        self.outputs[self.focused_output].screen
+    }
+
+    /// Resizes the focused output
+    fn resize_screen(&mut self, screen: Screen) {
+        let output = self.focused_output;
+        self.outputs[output].screen = screen;
+        self.resize_workspace(output);
+    }
+
+    fn get_focused_window(&self) -> Option<Window> {
+        self.workspace_wm.get_focused_window()
+    }
+
+    fn is_managed(&self, window: Window) -> bool {
+        self.workspace_wm.is_managed(window)
+    }
+}
+
+impl<WrappedWM: TilingSupport + RealWindowInfo> TilingSupport for OutputWM<WrappedWM> {
+    fn get_master_window(&self) -> Option<Window> {
+        self.workspace_wm.get_master_window()
+    }
+
+    /// If the window is not on the current workspace
+    /// it will move it to this workspace
+    fn swap_with_master(&mut self, window: Window) -> Result<(), Self::Error> {
+        self.workspace_wm.swap_with_master(window)
+    }
+
+    fn swap_windows(&mut self, dir: PrevOrNext) {
+        self.workspace_wm.swap_windows(dir)
+    }
+}
+
+impl<WrappedWM: FloatSupport + RealWindowInfo> FloatSupport for OutputWM<WrappedWM> {
+    fn get_floating_windows(&self) -> Vec<Window> {
+        self.workspace_wm.get_floating_windows()
+    }
+
+    fn toggle_floating(&mut self, window: Window) -> Result<(), Self::Error> {
+        self.workspace_wm.toggle_floating(window)
+    }
+
+    /// The geometry is in global coordinates
+    fn set_window_geometry(&mut self, window: Window, new_geometry: Geometry) -> Result<(), Self::Error> {
+        let (x, y) = self.get_offset_for_window(window);
+
+        self.workspace_wm.set_window_geometry(window, translate(new_geometry, (-x, -y)))
+    }
+}
+
+impl<WrappedWM: MinimiseSupport + RealWindowInfo> MinimiseSupport for OutputWM<WrappedWM> {
+    fn get_minimised_windows(&self) -> Vec<Window> {
+        self.workspace_wm.get_minimised_windows()
+    }
+
+    fn toggle_minimised(&mut self, window: Window) -> Result<(), Self::Error> {
+        self.workspace_wm.toggle_minimised(window)
+    }
+}
+
+impl<WrappedWM: FullscreenSupport + RealWindowInfo> FullscreenSupport for OutputWM<WrappedWM> {
+    fn get_fullscreen_window(&self) -> Option<Window> {
+        self.workspace_wm.get_fullscreen_window()
+    }
+
+    /// The window will be fullscreen on the output of its workspace
+    fn toggle_fullscreen(&mut self, window: Window) -> Result<(), Self::Error> {
+        try!(self.workspace_wm.toggle_fullscreen(window));
+        self.follow_current_workspace();
+
+        Ok(())
+    }
+}
+
+impl<WrappedWM: GapSupport + RealWindowInfo> GapSupport for OutputWM<WrappedWM> {
+    fn get_gap(&self) -> GapSize {
+        self.workspace_wm.get_gap()
+    }
+
+    fn set_gap(&mut self, gap_size: GapSize) {
+        self.workspace_wm.set_gap(gap_size)
+    }
+}
+
+impl<WrappedWM: ExtendedGapSupport + RealWindowInfo> ExtendedGapSupport for OutputWM<WrappedWM> {
+    fn get_inner_gap(&self) -> GapSize {
+        self.workspace_wm.get_inner_gap()
+    }
+
+    fn set_inner_gap(&mut self, gap: GapSize) {
+        self.workspace_wm.set_inner_gap(gap)
+    }
+
+    fn get_outer_gaps(&self) -> OuterGaps {
+        self.workspace_wm.get_outer_gaps()
+    }
+
+    fn set_outer_gaps(&mut self, gaps: OuterGaps) {
+        self.workspace_wm.set_outer_gaps(gaps)
+    }
+
+    fn get_smart_gaps(&self) -> bool {
+        self.workspace_wm.get_smart_gaps()
+    }
+
+    fn set_smart_gaps(&mut self, smart_gaps: bool) {
+        self.workspace_wm.set_smart_gaps(smart_gaps)
+    }
+
+    fn get_min_window_size(&self) -> c_uint {
+        self.workspace_wm.get_min_window_size()
+    }
+
+    fn set_min_window_size(&mut self, size: c_uint) {
+        self.workspace_wm.set_min_window_size(size)
+    }
+
+    fn try_set_gap(&mut self, gapsize: GapSize) -> Result<(), Self::Error> {
+        self.workspace_wm.try_set_gap(gapsize)
+    }
+}
+
+impl<WrappedWM: LayoutSupport + RealWindowInfo> LayoutSupport for OutputWM<WrappedWM> {
+    fn get_layout(&self) -> Layout {
+        self.workspace_wm.get_layout()
+    }
+
+    fn set_layout(&mut self, layout: Layout) {
+        self.workspace_wm.set_layout(layout)
+    }
+}
+
+/// Focusing in a direction uses the global coordinates so it can move the focus to another output.
+/// Swapping in a direction stays on the focused output.
+impl<WrappedWM: DirectionSupport + RealWindowInfo> DirectionSupport for OutputWM<WrappedWM> {
+    fn swap_direction(&mut self, dir: Direction) {
+        self.workspace_wm.swap_direction(dir)
+    }
+}
+
+impl<WrappedWM: DescriptionSupport + RealWindowInfo> DescriptionSupport for OutputWM<WrappedWM> {
+    /// The geometry of the window is in global coordinates
+    fn add_window_with_description(&mut self, description: WindowDescription) -> Result<(), Self::Error> {
+        // A transient window is added to the workspace of its parent
+        let (x, y) = match description.transient_for {
+            Some(parent) => self.get_offset_for_window(parent),
+            None => self.get_offset(self.workspace_wm.get_current_workspace_index()),
+        };
+
+        let mut description = description;
+        description.info.geometry = translate(description.info.geometry, (-x, -y));
+
+        self.workspace_wm.add_window_with_description(description)
+    }
+}
+
+impl<WrappedWM: MinimiseSupport + DescriptionSupport + RealWindowInfo> MoveWindowSupport for OutputWM<WrappedWM> {
+    /// The window is moved to the output of the workspace if that workspace is shown
+    fn move_window_to_workspace(&mut self, window: Window, index: WorkspaceIndex) -> Result<(), Self::Error> {
+        self.workspace_wm.move_window_to_workspace(window, index)
+    }
+
+    fn add_window_to_workspace(&mut self, description: WindowDescription, index: WorkspaceIndex, minimised: bool) -> Result<(), Self::Error> {
+        self.workspace_wm.add_window_to_workspace(description, index, minimised)
+    }
+}
+
+impl<WrappedWM> SnapshotSupport for OutputWM<WrappedWM>
+    where WrappedWM: FloatSupport + MinimiseSupport + FullscreenSupport + ExtendedGapSupport + LayoutSupport + RealWindowInfo
+{
+    /// The screen is the screen of the focused output
+    fn get_snapshot(&self) -> Snapshot {
+        Snapshot { screen: self.get_screen(), ..self.workspace_wm.get_snapshot() }
+    }
+}
+
+impl<WrappedWM: RealWindowInfo> MultiWorkspaceSupport<WrappedWM> for OutputWM<WrappedWM> {
+    fn get_current_workspace_index(&self) -> WorkspaceIndex {
+        self.workspace_wm.get_current_workspace_index()
+    }
+
+    fn get_workspace(&self, index: WorkspaceIndex) -> Result<&WrappedWM, Self::Error> {
+        self.workspace_wm.get_workspace(index)
+    }
+
+    fn get_workspace_mut(&mut self, index: WorkspaceIndex) -> Result<&mut WrappedWM, Self::Error> {
+        self.workspace_wm.get_workspace_mut(index)
+    }
+
+    /// Show the workspace at the given index on the focused output.
+    ///
+    /// If that workspace is shown on another output, the workspaces of both outputs are swapped.
+    fn switch_workspace(&mut self, index: WorkspaceIndex) -> Result<(), Self::Error> {
+        let output = self.focused_output;
+        self.move_workspace_to_output(index, output)
+    }
+}
+
+impl<WrappedWM: MinimiseSupport + RealWindowInfo> OutputWM<WrappedWM> {
+    /// Move the given window to the workspace shown on the output at the given index.
+    ///
+    /// See `WorkspaceWM::move_window_to_workspace`.
+    pub fn move_window_to_output(&mut self, window: Window, output: usize) -> Result<(), MultiWMError<WrappedWM::Error>> {
+        if output >= self.outputs.len() {
+            return Err(UnknownOutput(output));
+        }
+
+        let workspace = self.outputs[output].workspace;
+        self.workspace_wm.move_window_to_workspace(window, workspace)
+    }
+}
+
+#[cfg(test)]
+#[allow(unused_mut)]
+#[allow(unused_variables)]
+mod tests {
+    pub use super::*;
+
+    pub use cplwm_api::wm::*;
+    pub use cplwm_api::types::*;
+    pub use cplwm_api::types::PrevOrNext::*;
+    pub use direction::*;
+    pub use window_description::*;
+
+    // Import expectest names
+    pub use expectest::prelude::*;
+
+    conformance_tests!(WMName: window_manager, tiling, floating, minimising, fullscreen, gap, workspaces);
+
+    describe! wm {
+        before_each {
+            let screen: Screen = Screen {
+                width: 800,
+                height: 600,
+            };
+            let screen_geom = screen.to_geometry();
+
+            let small_screen: Screen = Screen {
+                width: 400,
+                height: 300,
+            };
+            let small_screen_geom = Geometry {
+                x: 800,
+                y: 0,
+                width: 400,
+                height: 300,
+            };
+
+            let some_geom: Geometry = Geometry {
+                x: 10,
+                y: 10,
+                width: 100,
+                height: 100,
+            };
+
+            let mut wm = WMName::new(screen);
+        }
+
+        it "should have one output initially" {
+            expect!(wm.get_outputs()).to(be_equal_to(vec![Output { x: 0, y: 0, screen: screen, workspace: 0 }]));
+            expect!(wm.get_window_layout()).to(be_equal_to(WindowLayout::new()));
+        }
+
+        it "should work like a WorkspaceWM with one output" {
+            wm.add_window(WindowWithInfo::new_tiled(1, some_geom)).unwrap();
+
+            expect!(wm.get_window_layout().windows).to(be_equal_to(vec![(1, screen_geom)]));
+            expect!(wm.get_focused_window()).to(be_equal_to(Some(1)));
+        }
+
+        describe! outputs {
+            before_each {
+                wm.add_window(WindowWithInfo::new_tiled(1, some_geom)).unwrap();
+                let output = wm.add_output(800, 0, small_screen);
+            }
+
+            it "should show the next workspace on a new output" {
+                expect!(output).to(be_equal_to(1));
+                expect!(wm.get_outputs()[1]).to(be_equal_to(Output { x: 800, y: 0, screen: small_screen, workspace: 1 }));
+                expect!(wm.get_workspace(1).unwrap().get_screen()).to(be_equal_to(small_screen));
+            }
+
+            it "should add a workspace if all workspaces are shown" {
+                for _ in 0..MAX_WORKSPACE_INDEX {
+                    wm.add_output(0, 600, screen);
+                }
+
+                expect!(wm.get_outputs().len()).to(be_equal_to(MAX_WORKSPACE_INDEX + 2));
+                expect!(wm.get_outputs()[MAX_WORKSPACE_INDEX + 1].workspace).to(be_equal_to(MAX_WORKSPACE_INDEX + 1));
+                expect!(wm.get_workspace(MAX_WORKSPACE_INDEX + 1)).to(be_ok());
+            }
+
+            it "should use global coordinates in the window layout" {
+                wm.cycle_output_focus(Next);
+                wm.add_window(WindowWithInfo::new_tiled(2, some_geom)).unwrap();
+
+                let wl = wm.get_window_layout();
+                expect!(wl.focused_window).to(be_equal_to(Some(2)));
+                expect!(wl.windows).to(be_equal_to(vec![(1, screen_geom), (2, small_screen_geom)]));
+                expect!(wm.get_window_info(2).unwrap().geometry).to(be_equal_to(small_screen_geom));
+            }
+
+            it "should translate the geometry of floating windows" {
+                let global_geom = Geometry { x: 850, y: 50, width: 100, height: 100 };
+                wm.cycle_output_focus(Next);
+                wm.add_window(WindowWithInfo::new_float(2, global_geom)).unwrap();
+
+                expect!(wm.get_window_layout().windows).to(be_equal_to(vec![(1, screen_geom), (2, global_geom)]));
+                let local_geom = Geometry { x: 50, y: 50, width: 100, height: 100 };
+                expect!(wm.get_workspace(1).unwrap().get_window_info(2).unwrap().geometry).to(be_equal_to(local_geom));
+
+                let new_geom = Geometry { x: 900, y: 100, width: 100, height: 100 };
+                wm.set_window_geometry(2, new_geom).unwrap();
+                expect!(wm.get_window_info(2).unwrap().geometry).to(be_equal_to(new_geom));
+            }
+
+            it "should translate the geometry of a transient window to the output of its parent" {
+                let global_geom = Geometry { x: 50, y: 50, width: 100, height: 100 };
+                wm.cycle_output_focus(Next);
+                let mut description = WindowDescription::new(WindowWithInfo::new_tiled(2, global_geom));
+                description.transient_for = Some(1);
+
+                wm.add_window_with_description(description).unwrap();
+
+                expect!(wm.get_workspace(0).unwrap().get_window_info(2).unwrap().geometry).to(be_equal_to(global_geom));
+                expect!(wm.get_window_layout().windows).to(be_equal_to(vec![(1, screen_geom), (2, global_geom)]));
+            }
+
+            it "should cycle the focus of the outputs" {
+                wm.cycle_output_focus(Next);
+                expect!(wm.get_focused_output()).to(be_equal_to(1));
+                expect!(wm.get_current_workspace_index()).to(be_equal_to(1));
+                expect!(wm.get_screen()).to(be_equal_to(small_screen));
+
+                wm.cycle_output_focus(Next);
+                expect!(wm.get_focused_output()).to(be_equal_to(0));
+                expect!(wm.get_focused_window()).to(be_equal_to(Some(1)));
+
+                wm.cycle_output_focus(Prev);
+                expect!(wm.get_focused_output()).to(be_equal_to(1));
+            }
+
+            it "should focus the output of a focused window" {
+                wm.focus_output(1).unwrap();
+                wm.add_window(WindowWithInfo::new_tiled(2, some_geom)).unwrap();
+
+                wm.focus_window(Some(1)).unwrap();
+
+                expect!(wm.get_focused_output()).to(be_equal_to(0));
+                expect!(wm.get_current_workspace_index()).to(be_equal_to(0));
+            }
+
+            it "should show the workspace of a focused window that isn't shown" {
+                wm.switch_workspace(2).unwrap();
+                wm.add_window(WindowWithInfo::new_tiled(2, some_geom)).unwrap();
+                wm.switch_workspace(0).unwrap();
+
+                wm.focus_window(Some(2)).unwrap();
+
+                expect!(wm.get_focused_output()).to(be_equal_to(0));
+                expect!(wm.get_outputs()[0].workspace).to(be_equal_to(2));
+                expect!(wm.get_window_layout().windows).to(be_equal_to(vec![(2, screen_geom)]));
+            }
+
+            it "should move the focus to another output in a direction" {
+                wm.focus_output(1).unwrap();
+                wm.add_window(WindowWithInfo::new_tiled(2, some_geom)).unwrap();
+
+                wm.focus_direction(Direction::Left);
+
+                expect!(wm.get_focused_window()).to(be_equal_to(Some(1)));
+                expect!(wm.get_focused_output()).to(be_equal_to(0));
+            }
+
+            it "should move a window to another output" {
+                wm.move_window_to_output(1, 1).unwrap();
+
+                expect!(wm.get_focused_output()).to(be_equal_to(0));
+                expect!(wm.get_window_layout().windows).to(be_equal_to(vec![(1, small_screen_geom)]));
+            }
+
+            it "should move a workspace to another output" {
+                wm.move_workspace_to_output(0, 1).unwrap();
+
+                expect!(wm.get_outputs()[0].workspace).to(be_equal_to(1));
+                expect!(wm.get_outputs()[1].workspace).to(be_equal_to(0));
+                expect!(wm.get_window_layout().windows).to(be_equal_to(vec![(1, small_screen_geom)]));
+            }
+
+            it "should swap the workspaces when switching to a workspace shown on another output" {
+                wm.switch_workspace(1).unwrap();
+
+                expect!(wm.get_current_workspace_index()).to(be_equal_to(1));
+                expect!(wm.get_outputs()[0].workspace).to(be_equal_to(1));
+                expect!(wm.get_outputs()[1].workspace).to(be_equal_to(0));
+            }
+
+            it "should show another workspace on the focused output" {
+                wm.switch_workspace(3).unwrap();
+
+                expect!(wm.get_outputs()[0].workspace).to(be_equal_to(3));
+                expect!(wm.get_outputs()[1].workspace).to(be_equal_to(1));
+                expect!(wm.get_window_layout()).to(be_equal_to(WindowLayout::new()));
+            }
+
+            it "should be able to remove an output" {
+                wm.focus_output(1).unwrap();
+
+                wm.remove_output(1).unwrap();
+
+                expect!(wm.get_focused_output()).to(be_equal_to(0));
+                expect!(wm.get_focused_window()).to(be_equal_to(Some(1)));
+                expect!(wm.remove_output(0)).to(be_err());
+            }
+
+            it "should error on unknown outputs or workspaces" {
+                expect!(wm.focus_output(2)).to(be_err());
+                expect!(wm.move_window_to_output(1, 2)).to(be_err());
+                expect!(wm.move_workspace_to_output(0, 2)).to(be_err());
+                expect!(wm.move_workspace_to_output(MAX_WORKSPACE_INDEX + 1, 0)).to(be_err());
+            }
+        }
+    }
+}
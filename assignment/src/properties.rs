@@ -0,0 +1,268 @@
+//! This module tests the full window manager with random sequences of operations
+//!
+//! Every sequence starts from a new `WMName` of `g_multiple_workspaces`. After every operation the invariants of the
+//! window manager are checked (see `check_invariants`). The operations are allowed to fail (e.g. for a window that
+//! isn't managed), but they should never panic or break an invariant.
+//!
+//! When quickcheck finds a failing sequence, it is shrunk to a minimal one before it's reported.
+//!
+
+use quickcheck::{Arbitrary, Gen, QuickCheck, TestResult};
+use rustc_serialize::json;
+
+use cplwm_api::types::{FloatOrTile, GapSize, Geometry, MAX_WORKSPACE_INDEX, PrevOrNext, Screen, Window,
+                       WindowWithInfo, WorkspaceIndex};
+use cplwm_api::wm::{FloatSupport, FullscreenSupport, GapSupport, MinimiseSupport, MultiWorkspaceSupport,
+                    TilingSupport, WindowManager};
+
+use g_multiple_workspaces::WMName;
+
+/// The windows of the operations, a small set so the operations often hit a managed window
+static WINDOWS: [Window; 6] = [1, 2, 3, 4, 5, 6];
+
+/// An operation on the window manager
+#[derive(Debug, Clone)]
+pub enum Operation {
+    /// `WindowManager::add_window`
+    AddWindow(WindowWithInfo),
+    /// `WindowManager::remove_window`
+    RemoveWindow(Window),
+    /// `WindowManager::focus_window`
+    FocusWindow(Option<Window>),
+    /// `WindowManager::cycle_focus`
+    CycleFocus(PrevOrNext),
+    /// `TilingSupport::swap_with_master`
+    SwapWithMaster(Window),
+    /// `TilingSupport::swap_windows`
+    SwapWindows(PrevOrNext),
+    /// `FloatSupport::toggle_floating`
+    ToggleFloating(Window),
+    /// `MinimiseSupport::toggle_minimised`
+    ToggleMinimised(Window),
+    /// `FullscreenSupport::toggle_fullscreen`
+    ToggleFullscreen(Window),
+    /// `MultiWorkspaceSupport::switch_workspace` (including a workspace that doesn't exist)
+    SwitchWorkspace(WorkspaceIndex),
+    /// `MoveWindowSupport::move_window_to_workspace` (including a workspace that doesn't exist)
+    MoveWindowToWorkspace(Window, WorkspaceIndex),
+    /// `WindowManager::resize_screen`
+    ResizeScreen(Screen),
+    /// `GapSupport::set_gap`
+    SetGap(GapSize),
+}
+
+/// Choose one of the values
+fn choose<T: Copy>(g: &mut Gen, values: &[T]) -> T {
+    *g.choose(values).unwrap()
+}
+
+impl Arbitrary for Operation {
+    fn arbitrary(g: &mut Gen) -> Operation {
+        let window = choose(g, &WINDOWS);
+        let dir = choose(g, &[PrevOrNext::Prev, PrevOrNext::Next]);
+
+        let index = choose(g, &[0, 1, 2, MAX_WORKSPACE_INDEX + 1]);
+
+        match choose(g, &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13]) {
+            0 | 1 => {
+                Operation::AddWindow(WindowWithInfo {
+                    window: window,
+                    geometry: Geometry {
+                        x: choose(g, &[0, 10, 300]),
+                        y: choose(g, &[0, 10, 200]),
+                        width: choose(g, &[1, 100, 500]),
+                        height: choose(g, &[1, 100, 400]),
+                    },
+                    float_or_tile: choose(g, &[FloatOrTile::Tile, FloatOrTile::Float]),
+                    fullscreen: choose(g, &[false, false, false, true]),
+                })
+            }
+            2 => Operation::RemoveWindow(window),
+            3 => Operation::FocusWindow(choose(g, &[Some(window), None])),
+            4 => Operation::CycleFocus(dir),
+            5 => Operation::SwapWithMaster(window),
+            6 => Operation::SwapWindows(dir),
+            7 => Operation::ToggleFloating(window),
+            8 => Operation::ToggleMinimised(window),
+            9 => Operation::ToggleFullscreen(window),
+            10 => Operation::SwitchWorkspace(index),
+            11 => Operation::MoveWindowToWorkspace(window, index),
+            12 => {
+                Operation::ResizeScreen(Screen {
+                    width: choose(g, &[7, 400, 401, 1021, 1920]),
+                    height: choose(g, &[9, 300, 301, 1021, 1080]),
+                })
+            }
+            _ => Operation::SetGap(choose(g, &[0, 5, 20])),
+        }
+    }
+}
+
+/// Apply the operation to the window manager
+pub fn apply(wm: &mut WMName, operation: &Operation) -> Result<(), <WMName as WindowManager>::Error> {
+    match *operation {
+        Operation::AddWindow(info) => wm.add_window(info),
+        Operation::RemoveWindow(window) => wm.remove_window(window),
+        Operation::FocusWindow(window) => wm.focus_window(window),
+        Operation::CycleFocus(dir) => Ok(wm.cycle_focus(dir)),
+        Operation::SwapWithMaster(window) => wm.swap_with_master(window),
+        Operation::SwapWindows(dir) => Ok(wm.swap_windows(dir)),
+        Operation::ToggleFloating(window) => wm.toggle_floating(window),
+        Operation::ToggleMinimised(window) => wm.toggle_minimised(window),
+        Operation::ToggleFullscreen(window) => wm.toggle_fullscreen(window),
+        Operation::SwitchWorkspace(index) => wm.switch_workspace(index),
+        Operation::MoveWindowToWorkspace(window, index) => wm.move_window_to_workspace(window, index),
+        Operation::ResizeScreen(screen) => Ok(wm.resize_screen(screen)),
+        Operation::SetGap(gap) => Ok(wm.set_gap(gap)),
+    }
+}
+
+/// Return the windows sorted, so they can be compared as sets
+fn sorted(mut windows: Vec<Window>) -> Vec<Window> {
+    windows.sort();
+    windows
+}
+
+/// Return an error if the windows contain duplicates
+fn check_no_duplicates(name: &str, windows: &[Window]) -> Result<(), String> {
+    let mut unique_windows = sorted(windows.to_vec());
+    unique_windows.dedup();
+    if unique_windows.len() == windows.len() {
+        Ok(())
+    } else {
+        Err(format!("{} contains duplicates: {:?}", name, windows))
+    }
+}
+
+/// Check the invariants of the window manager:
+///
+/// * There are no duplicates in the windows, the visible windows, the floating windows or the minimised windows.
+/// * The focused window is managed and visible.
+/// * `get_windows` is the union of the windows of all workspaces, which don't share windows.
+/// * The window layout contains exactly the visible windows: the fullscreen window or all windows of the current
+///   workspace that aren't minimised.
+/// * The floating windows are visible, the minimised windows are managed by the current workspace.
+/// * Encoding and decoding the window manager preserves the window layout.
+pub fn check_invariants(wm: &WMName) -> Result<(), String> {
+    let windows = wm.get_windows();
+    let layout = wm.get_window_layout();
+    let visible_windows: Vec<Window> = layout.windows.iter().map(|&(w, _)| w).collect();
+    let floating_windows = wm.get_floating_windows();
+    let minimised_windows = wm.get_minimised_windows();
+
+    try!(check_no_duplicates("get_windows", &windows));
+    try!(check_no_duplicates("the window layout", &visible_windows));
+    try!(check_no_duplicates("get_floating_windows", &floating_windows));
+    try!(check_no_duplicates("get_minimised_windows", &minimised_windows));
+
+    if layout.focused_window != wm.get_focused_window() {
+        return Err(format!("the focused window {:?} is not focused in the layout {:?}",
+                           wm.get_focused_window(),
+                           layout));
+    }
+    if let Some(window) = layout.focused_window {
+        if !wm.is_managed(window) || !visible_windows.contains(&window) {
+            return Err(format!("the focused window {} is not managed and visible", window));
+        }
+    }
+
+    let mut workspace_windows = vec![];
+    let mut index = 0;
+    while let Ok(workspace) = wm.get_workspace(index) {
+        workspace_windows.extend(workspace.get_windows());
+        index += 1;
+    }
+    try!(check_no_duplicates("the workspaces", &workspace_windows));
+    if sorted(workspace_windows) != sorted(windows.clone()) {
+        return Err(format!("get_windows {:?} is not the union of the workspaces", windows));
+    }
+
+    let current_windows = wm.get_workspace(wm.get_current_workspace_index()).unwrap().get_windows();
+    let expected_visible_windows = match wm.get_fullscreen_window() {
+        Some(window) => vec![window],
+        None => current_windows.iter().cloned().filter(|w| !minimised_windows.contains(w)).collect(),
+    };
+    if sorted(visible_windows.clone()) != sorted(expected_visible_windows.clone()) {
+        return Err(format!("the window layout {:?} doesn't contain exactly the visible windows {:?}",
+                           layout,
+                           expected_visible_windows));
+    }
+
+    if wm.get_fullscreen_window().is_none() && floating_windows.iter().any(|w| !visible_windows.contains(w)) {
+        return Err(format!("the floating windows {:?} are not visible", floating_windows));
+    }
+    if minimised_windows.iter().any(|w| !current_windows.contains(w)) {
+        return Err(format!("the minimised windows {:?} are not managed", minimised_windows));
+    }
+
+    let encoded = try!(json::encode(wm).map_err(|error| error.to_string()));
+    let decoded: WMName = try!(json::decode(&encoded).map_err(|error| error.to_string()));
+    if decoded.get_window_layout() != layout {
+        return Err(format!("the decoded window manager has the layout {:?} instead of {:?}",
+                           decoded.get_window_layout(),
+                           layout));
+    }
+
+    Ok(())
+}
+
+/// Apply the operations to a new window manager and check the invariants after every operation.
+///
+/// Removing a window that isn't focused shouldn't change the focus.
+fn keeps_invariants(operations: Vec<Operation>) -> TestResult {
+    let mut wm = WMName::new(Screen {
+        width: 800,
+        height: 600,
+    });
+
+    for (step, operation) in operations.iter().enumerate() {
+        let focused_window = wm.get_focused_window();
+        // The operations are allowed to fail
+        let _ = apply(&mut wm, operation);
+
+        if let Err(error) = check_invariants(&wm) {
+            return TestResult::error(format!("after step {} ({:?}): {}", step, operation, error));
+        }
+
+        // The same window remains focused, unless the focused window has been removed
+        if let Operation::RemoveWindow(window) = *operation {
+            if focused_window != Some(window) && wm.get_focused_window() != focused_window {
+                return TestResult::error(format!("after step {} ({:?}): the focus changed from {:?} to {:?}",
+                                                 step,
+                                                 operation,
+                                                 focused_window,
+                                                 wm.get_focused_window()));
+            }
+        }
+    }
+
+    TestResult::passed()
+}
+
+#[allow(unused_mut)]
+#[allow(unused_variables)]
+mod tests {
+    pub use super::*;
+
+    // Import expectest names
+    pub use expectest::prelude::*;
+
+    describe! properties {
+        it "should keep the invariants for random sequences of operations" {
+            QuickCheck::new()
+                .tests(500)
+                .quickcheck(keeps_invariants as fn(Vec<Operation>) -> TestResult);
+        }
+
+        it "should check the invariants of a known sequence" {
+            let operations = vec![Operation::AddWindow(WindowWithInfo::new_tiled(1, Geometry { x: 0, y: 0, width: 10, height: 10 })),
+                                  Operation::AddWindow(WindowWithInfo::new_float(2, Geometry { x: 0, y: 0, width: 10, height: 10 })),
+                                  Operation::ToggleMinimised(1),
+                                  Operation::SwitchWorkspace(1),
+                                  Operation::AddWindow(WindowWithInfo::new_fullscreen(3, Geometry { x: 0, y: 0, width: 10, height: 10 })),
+                                  Operation::SwitchWorkspace(0),
+                                  Operation::RemoveWindow(2)];
+            expect!(keeps_invariants(operations).is_failure()).to(be_false());
+        }
+    }
+}
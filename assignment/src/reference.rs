@@ -0,0 +1,764 @@
+//! This module provides a reference model of the full window manager and tests the window manager against it
+//!
+//! The window manager of `g_multiple_workspaces` is a stack of wrappers (workspaces around fullscreen around
+//! minimising around floating around tiling) and the interactions between these wrappers are easy to get wrong.
+//! `ReferenceWM` implements the same behaviour without any wrapping: it keeps a flat list of windows, each with its
+//! workspace, flags and geometry, and every operation is written out directly on that list. It's meant to be read
+//! as the specification of `g_multiple_workspaces::WMName`.
+//!
+//! The differential test replays the same random sequence of `properties::Operation`s against the model and the
+//! window manager and compares the result of every operation, the window layout and the queries after every step.
+//!
+
+use quickcheck::{QuickCheck, TestResult};
+use std::cmp;
+use std::os::raw::{c_int, c_uint};
+
+use cplwm_api::types::{FloatOrTile, GapSize, Geometry, MAX_WORKSPACE_INDEX, PrevOrNext, Screen, Window,
+                       WindowLayout, WindowWithInfo, WorkspaceIndex};
+use cplwm_api::types::PrevOrNext::*;
+use cplwm_api::wm::{FloatSupport, FullscreenSupport, GapSupport, MinimiseSupport, MultiWorkspaceSupport,
+                    TilingSupport, WindowManager};
+
+use error::MultiWMError;
+use error::MultiWMError::*;
+use error::WMError;
+use error::WMError::*;
+use f_gaps::DEFAULT_MIN_WINDOW_SIZE;
+use g_multiple_workspaces::WMName;
+use properties::{Operation, apply};
+
+/// The errors of the model, the same as the ones of the window manager
+pub type ModelError = MultiWMError<WMError>;
+
+/// A window of the model
+#[derive(Debug, Clone)]
+pub struct ModelWindow {
+    /// The window
+    pub window: Window,
+    /// The workspace the window is on
+    pub workspace: WorkspaceIndex,
+    /// Whether the window floats (or would float when it's not minimised or fullscreen anymore)
+    pub floating: bool,
+    /// Whether the window is minimised
+    pub minimised: bool,
+    /// Whether the window is fullscreen (or will be fullscreen again when it's unminimised)
+    pub fullscreen: bool,
+    /// The requested geometry, used when the window floats
+    pub geometry: Geometry,
+    /// When the window was raised for the last time, floating windows are stacked in this order
+    pub raised: usize,
+}
+
+/// The reference model of `g_multiple_workspaces::WMName`
+///
+/// A window is *visible* when it's neither minimised nor fullscreen, then it's tiled or floating. The visible windows
+/// of a workspace keep the order of `windows`: the tiled windows are tiled in that order, the focus cycles through
+/// the tiled windows and then through the floating windows in that order. A window moves to the end of `windows`
+/// whenever it becomes visible or minimised.
+#[derive(Debug, Clone)]
+pub struct ReferenceWM {
+    /// The screen, the same for all workspaces
+    pub screen: Screen,
+    /// The gap of every workspace
+    pub gaps: Vec<GapSize>,
+    /// The index of the current workspace
+    pub current_workspace: WorkspaceIndex,
+    /// The managed windows
+    pub windows: Vec<ModelWindow>,
+    /// The focused visible window of every workspace, a fullscreen window takes the focus over from this one
+    pub focus: Vec<Option<Window>>,
+    /// The number of times a window has been raised
+    pub nb_raised: usize,
+}
+
+impl ReferenceWM {
+    /// Create a new model with `MAX_WORKSPACE_INDEX + 1` empty workspaces
+    pub fn new(screen: Screen) -> ReferenceWM {
+        ReferenceWM {
+            screen: screen,
+            gaps: vec![0; MAX_WORKSPACE_INDEX + 1],
+            current_workspace: 0,
+            windows: vec![],
+            focus: vec![None; MAX_WORKSPACE_INDEX + 1],
+            nb_raised: 0,
+        }
+    }
+
+    // The state of the model
+
+    /// Return the window, if it's managed
+    fn find(&self, window: Window) -> Option<&ModelWindow> {
+        self.windows.iter().find(|w| w.window == window)
+    }
+
+    /// Return the window, panics if it isn't managed
+    fn get_mut(&mut self, window: Window) -> &mut ModelWindow {
+        self.windows.iter_mut().find(|w| w.window == window).unwrap()
+    }
+
+    /// Return the window or an `UnknownWindow` error
+    fn get(&self, window: Window) -> Result<ModelWindow, ModelError> {
+        self.find(window).cloned().ok_or(WrappedError(UnknownWindow(window)))
+    }
+
+    /// Return the windows of the workspace that match the predicate, in the order of `windows`
+    fn select<P: Fn(&ModelWindow) -> bool>(&self, workspace: WorkspaceIndex, predicate: P) -> Vec<Window> {
+        self.windows
+            .iter()
+            .filter(|w| w.workspace == workspace && predicate(w))
+            .map(|w| w.window)
+            .collect()
+    }
+
+    /// Return the tiled windows of the workspace
+    fn tiled(&self, workspace: WorkspaceIndex) -> Vec<Window> {
+        self.select(workspace, |w| !w.minimised && !w.fullscreen && !w.floating)
+    }
+
+    /// Return the floating windows of the workspace
+    fn floating(&self, workspace: WorkspaceIndex) -> Vec<Window> {
+        self.select(workspace, |w| !w.minimised && !w.fullscreen && w.floating)
+    }
+
+    /// Return the minimised windows of the workspace
+    fn minimised(&self, workspace: WorkspaceIndex) -> Vec<Window> {
+        self.select(workspace, |w| w.minimised)
+    }
+
+    /// Return the fullscreen window of the workspace
+    fn fullscreen(&self, workspace: WorkspaceIndex) -> Option<Window> {
+        self.select(workspace, |w| !w.minimised && w.fullscreen).first().cloned()
+    }
+
+    /// Return the geometry of the i-th of n tiled windows of the workspace: the master window takes the left half of the screen,
+    /// the other windows share the right half (or the master window takes the whole screen when it's alone).
+    /// The j-th of m slaves spans from `height * j / m` to `height * (j + 1) / m`, so the remainder of the height is spread
+    /// over the slaves. The gap shrinks when the tile is too small for it, a tile keeps at least the minimum window size.
+    fn tile(&self, workspace: WorkspaceIndex, i: usize, n: usize) -> Geometry {
+        let (width, height) = (self.screen.width, self.screen.height);
+        let (x, y, width, height) = if n == 1 {
+            (0, 0, width, height)
+        } else if i == 0 {
+            (0, 0, width / 2, height)
+        } else {
+            let boundary = |j: usize| (height as u64 * j as u64 / (n - 1) as u64) as c_uint;
+            let (top, bottom) = (boundary(i - 1), boundary(i));
+            (width / 2, top, width - width / 2, bottom - top)
+        };
+
+        let gap = |size: c_uint| cmp::min(self.gaps[workspace], size.saturating_sub(DEFAULT_MIN_WINDOW_SIZE) / 2);
+        let (horizontal_gap, vertical_gap) = (gap(width), gap(height));
+
+        Geometry {
+            x: x as c_int + horizontal_gap as c_int,
+            y: y as c_int + vertical_gap as c_int,
+            width: width - 2 * horizontal_gap,
+            height: height - 2 * vertical_gap,
+        }
+    }
+
+    /// Return the visible windows of the workspace with their geometries: first the tiled windows, then the
+    /// floating windows from bottom to top
+    fn visible_windows(&self, workspace: WorkspaceIndex) -> Vec<(Window, Geometry)> {
+        let tiled = self.tiled(workspace);
+        let mut windows: Vec<(Window, Geometry)> =
+            tiled.iter().enumerate().map(|(i, &w)| (w, self.tile(workspace, i, tiled.len()))).collect();
+
+        let mut floating: Vec<&ModelWindow> = self.windows
+            .iter()
+            .filter(|w| w.workspace == workspace && !w.minimised && !w.fullscreen && w.floating)
+            .collect();
+        floating.sort_by_key(|w| w.raised);
+        windows.extend(floating.iter().map(|w| (w.window, w.geometry)));
+
+        windows
+    }
+
+    // The building blocks of the operations
+
+    /// Focus the (visible) window on its workspace, a floating window is raised
+    fn focus_visible(&mut self, workspace: WorkspaceIndex, window: Option<Window>) {
+        self.focus[workspace] = window;
+
+        if let Some(window) = window {
+            if self.get_mut(window).floating {
+                self.nb_raised += 1;
+                self.get_mut(window).raised = self.nb_raised;
+            }
+        }
+    }
+
+    /// Move the (visible) window to the end of the windows and focus it
+    fn show(&mut self, window: Window) {
+        let index = self.windows.iter().position(|w| w.window == window).unwrap();
+        let model_window = self.windows.remove(index);
+        let workspace = model_window.workspace;
+        self.windows.push(model_window);
+
+        self.focus_visible(workspace, Some(window));
+    }
+
+    /// Update the focus before the (visible) window stops being visible.
+    ///
+    /// When the focused tiled window disappears, the previous tiled window gets the focus (the last one if it was the
+    /// first one). When the focused floating window disappears, the previous floating window gets the focus (no
+    /// window if it was the first one). When another floating window disappears, the focused floating window is
+    /// raised.
+    fn hide(&mut self, window: Window) {
+        let workspace = self.find(window).unwrap().workspace;
+        let focused_window = self.focus[workspace];
+        let tiled = self.tiled(workspace);
+        let floating = self.floating(workspace);
+
+        if self.find(window).unwrap().floating {
+            let i = floating.iter().position(|&w| w == window).unwrap();
+            let others: Vec<Window> = floating.iter().cloned().filter(|&w| w != window).collect();
+
+            match focused_window.and_then(|f| floating.iter().position(|&w| w == f)) {
+                _ if tiled.is_empty() && others.is_empty() => self.focus[workspace] = None,
+                Some(j) if i < j => self.focus_visible(workspace, focused_window),
+                Some(j) if i == j => {
+                    let previous = if j == 0 { None } else { Some(others[j - 1]) };
+                    self.focus_visible(workspace, previous)
+                }
+                _ => {}
+            }
+        } else if focused_window == Some(window) {
+            let i = tiled.iter().position(|&w| w == window).unwrap();
+            let others: Vec<Window> = tiled.iter().cloned().filter(|&w| w != window).collect();
+
+            self.focus[workspace] = if others.is_empty() {
+                None
+            } else {
+                Some(others[(i + others.len() - 1) % others.len()])
+            };
+        }
+    }
+
+    /// The fullscreen window of the workspace (if any) stops being fullscreen, it's shown and focused
+    fn un_fullscreen(&mut self, workspace: WorkspaceIndex) {
+        if let Some(window) = self.fullscreen(workspace) {
+            self.get_mut(window).fullscreen = false;
+            self.show(window);
+        }
+    }
+
+    /// Remove the window from the model
+    fn take(&mut self, window: Window) -> ModelWindow {
+        let model_window = self.find(window).unwrap().clone();
+        if !model_window.minimised && !model_window.fullscreen {
+            self.hide(window);
+        }
+
+        self.windows.retain(|w| w.window != window);
+        model_window
+    }
+
+    /// Put the window (back) on its workspace, it's not minimised anymore.
+    ///
+    /// The fullscreen window of the workspace stops being fullscreen, then the window is either fullscreen or it's
+    /// shown and focused.
+    fn restore(&mut self, mut model_window: ModelWindow) {
+        let window = model_window.window;
+        self.un_fullscreen(model_window.workspace);
+
+        model_window.minimised = false;
+        let fullscreen = model_window.fullscreen;
+        self.windows.push(model_window);
+
+        if !fullscreen {
+            self.show(window);
+        }
+    }
+
+    /// Minimise the window. A fullscreen window is shown first and will be fullscreen again when it's unminimised.
+    fn minimise(&mut self, window: Window) {
+        let fullscreen = self.find(window).unwrap().fullscreen;
+        if fullscreen {
+            self.get_mut(window).fullscreen = false;
+            self.show(window);
+        }
+
+        self.hide(window);
+        let mut model_window = self.take(window);
+        model_window.minimised = true;
+        model_window.fullscreen = fullscreen;
+        self.windows.push(model_window);
+    }
+
+    /// Unminimise the window, see `restore`
+    fn unminimise(&mut self, window: Window) {
+        let model_window = self.take(window);
+        self.restore(model_window);
+    }
+
+    /// Unminimise the window as a normal window, it's shown and focused but it's not fullscreen anymore and the
+    /// fullscreen window of the workspace stays fullscreen
+    fn show_minimised(&mut self, window: Window) {
+        {
+            let model_window = self.get_mut(window);
+            model_window.minimised = false;
+            model_window.fullscreen = false;
+        }
+        self.show(window);
+    }
+
+    /// Float or tile the (visible) window, it's shown again but the focus doesn't change
+    fn toggle_floating_visible(&mut self, window: Window) {
+        let workspace = self.find(window).unwrap().workspace;
+        let focused_window = self.focus[workspace];
+
+        self.hide(window);
+        let floating = self.find(window).unwrap().floating;
+        self.get_mut(window).floating = !floating;
+        self.show(window);
+
+        self.focus[workspace] = focused_window;
+    }
+
+    // The operations
+
+    /// See `WindowManager::add_window`, a window that's already managed is not added again
+    pub fn add_window(&mut self, info: WindowWithInfo) -> Result<(), ModelError> {
+        if self.find(info.window).is_none() {
+            let workspace = self.current_workspace;
+            self.restore(ModelWindow {
+                window: info.window,
+                workspace: workspace,
+                floating: info.float_or_tile == FloatOrTile::Float,
+                minimised: false,
+                fullscreen: info.fullscreen,
+                geometry: info.geometry,
+                raised: 0,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// See `WindowManager::remove_window`, the window can be on any workspace
+    pub fn remove_window(&mut self, window: Window) -> Result<(), ModelError> {
+        try!(self.get(window));
+
+        self.take(window);
+        Ok(())
+    }
+
+    /// See `WindowManager::focus_window`, switches to the workspace of the window.
+    ///
+    /// A minimised window is shown first (see `show_minimised`).
+    pub fn focus_window(&mut self, window: Option<Window>) -> Result<(), ModelError> {
+        let model_window = match window {
+            Some(window) => Some(try!(self.get(window))),
+            None => None,
+        };
+        if let Some(ref model_window) = model_window {
+            self.current_workspace = model_window.workspace;
+        }
+        let workspace = self.current_workspace;
+
+        if window.is_some() && window == self.fullscreen(workspace) {
+            return Ok(());
+        }
+
+        self.un_fullscreen(workspace);
+        if let Some(model_window) = model_window {
+            if model_window.minimised {
+                self.show_minimised(model_window.window);
+            }
+        }
+        self.focus_visible(workspace, window);
+        Ok(())
+    }
+
+    /// See `WindowManager::cycle_focus`, the focus cycles through the tiled windows and then the floating windows.
+    ///
+    /// Without a focused window, the first floating window (or the first tiled window if there are no floating
+    /// windows) or the last window gets the focus.
+    pub fn cycle_focus(&mut self, dir: PrevOrNext) {
+        let workspace = self.current_workspace;
+        self.un_fullscreen(workspace);
+
+        let floating = self.floating(workspace);
+        let mut windows = self.tiled(workspace);
+        windows.extend(floating.iter().cloned());
+        if windows.is_empty() {
+            return;
+        }
+
+        let n = windows.len();
+        let focused_window = match (self.focus[workspace].and_then(|f| windows.iter().position(|&w| w == f)), dir) {
+            (Some(i), Next) => windows[(i + 1) % n],
+            (Some(i), Prev) => windows[(i + n - 1) % n],
+            (None, Next) => floating.first().cloned().unwrap_or(windows[0]),
+            (None, Prev) => windows[n - 1],
+        };
+        self.focus_visible(workspace, Some(focused_window));
+    }
+
+    /// See `TilingSupport::swap_with_master`, a window on another workspace is moved to the current workspace first.
+    ///
+    /// The window stops being fullscreen, is shown (see `show_minimised`) and is tiled before it's swapped with the
+    /// master window.
+    pub fn swap_with_master(&mut self, window: Window) -> Result<(), ModelError> {
+        let model_window = try!(self.get(window));
+        let workspace = self.current_workspace;
+
+        if model_window.workspace != workspace {
+            let mut model_window = self.take(window);
+            model_window.workspace = workspace;
+            self.restore(model_window);
+        }
+
+        self.un_fullscreen(workspace);
+        if self.find(window).unwrap().minimised {
+            self.show_minimised(window);
+        }
+        if self.find(window).unwrap().floating {
+            self.toggle_floating_visible(window);
+        }
+
+        let master = self.tiled(workspace)[0];
+        let i = self.windows.iter().position(|w| w.window == window).unwrap();
+        let j = self.windows.iter().position(|w| w.window == master).unwrap();
+        self.windows.swap(i, j);
+        self.focus_visible(workspace, Some(window));
+        Ok(())
+    }
+
+    /// See `TilingSupport::swap_windows`, a focused floating window is tiled first
+    pub fn swap_windows(&mut self, dir: PrevOrNext) {
+        let workspace = self.current_workspace;
+        self.un_fullscreen(workspace);
+
+        let focused_window = match self.focus[workspace] {
+            Some(window) => window,
+            None => return,
+        };
+        if self.find(focused_window).unwrap().floating {
+            self.toggle_floating_visible(focused_window);
+        }
+
+        let tiled = self.tiled(workspace);
+        let n = tiled.len();
+        let i = tiled.iter().position(|&w| w == focused_window).unwrap();
+        let other = match dir {
+            Next => tiled[(i + 1) % n],
+            Prev => tiled[(i + n - 1) % n],
+        };
+
+        let i = self.windows.iter().position(|w| w.window == focused_window).unwrap();
+        let j = self.windows.iter().position(|w| w.window == other).unwrap();
+        self.windows.swap(i, j);
+    }
+
+    /// See `FloatSupport::toggle_floating`, the window must be on the current workspace.
+    ///
+    /// A fullscreen window stops being fullscreen, a minimised window is shown first (see `show_minimised`).
+    pub fn toggle_floating(&mut self, window: Window) -> Result<(), ModelError> {
+        let model_window = try!(self.get(window));
+        let workspace = self.current_workspace;
+        if model_window.workspace != workspace {
+            return Err(WrappedError(UnknownWindow(window)));
+        }
+
+        if self.fullscreen(workspace) == Some(window) {
+            self.un_fullscreen(workspace);
+        }
+        if model_window.minimised {
+            self.show_minimised(window);
+        }
+
+        self.toggle_floating_visible(window);
+        Ok(())
+    }
+
+    /// See `MinimiseSupport::toggle_minimised`, the window must be on the current workspace
+    pub fn toggle_minimised(&mut self, window: Window) -> Result<(), ModelError> {
+        let model_window = try!(self.get(window));
+        if model_window.workspace != self.current_workspace {
+            return Err(WrappedError(UnknownWindow(window)));
+        }
+
+        if model_window.minimised {
+            self.unminimise(window);
+        } else {
+            self.minimise(window);
+        }
+        Ok(())
+    }
+
+    /// See `FullscreenSupport::toggle_fullscreen`, switches to the workspace of the window.
+    ///
+    /// The fullscreen window of that workspace stops being fullscreen.
+    pub fn toggle_fullscreen(&mut self, window: Window) -> Result<(), ModelError> {
+        let model_window = try!(self.get(window));
+        let workspace = model_window.workspace;
+        self.current_workspace = workspace;
+
+        if self.fullscreen(workspace) == Some(window) {
+            self.un_fullscreen(workspace);
+        } else {
+            self.un_fullscreen(workspace);
+            if !model_window.minimised {
+                self.hide(window);
+            }
+
+            let model_window = self.get_mut(window);
+            model_window.minimised = false;
+            model_window.fullscreen = true;
+        }
+        Ok(())
+    }
+
+    /// See `MultiWorkspaceSupport::switch_workspace`
+    pub fn switch_workspace(&mut self, index: WorkspaceIndex) -> Result<(), ModelError> {
+        if index >= self.focus.len() {
+            return Err(UnknownWorkspace(index));
+        }
+
+        self.current_workspace = index;
+        Ok(())
+    }
+
+    /// See `MoveWindowSupport::move_window_to_workspace`.
+    ///
+    /// The window is put on the other workspace like it's added there, a minimised window is minimised again.
+    pub fn move_window_to_workspace(&mut self, window: Window, index: WorkspaceIndex) -> Result<(), ModelError> {
+        if index >= self.focus.len() {
+            return Err(UnknownWorkspace(index));
+        }
+        let model_window = try!(self.get(window));
+        if model_window.workspace == index {
+            return Ok(());
+        }
+
+        let mut model_window = self.take(window);
+        let minimised = model_window.minimised;
+        model_window.workspace = index;
+        self.restore(model_window);
+
+        if minimised {
+            self.minimise(window);
+        }
+        Ok(())
+    }
+
+    /// See `WindowManager::resize_screen`
+    pub fn resize_screen(&mut self, screen: Screen) {
+        self.screen = screen;
+    }
+
+    /// See `GapSupport::set_gap`: only the gap of the current workspace changes
+    pub fn set_gap(&mut self, gap: GapSize) {
+        self.gaps[self.current_workspace] = gap;
+    }
+
+    // The queries
+
+    /// See `WindowManager::get_windows`: per workspace the tiled, floating, minimised and fullscreen windows
+    pub fn get_windows(&self) -> Vec<Window> {
+        let mut windows = vec![];
+        for workspace in 0..self.focus.len() {
+            windows.extend(self.tiled(workspace));
+            windows.extend(self.floating(workspace));
+            windows.extend(self.minimised(workspace));
+            windows.extend(self.fullscreen(workspace));
+        }
+
+        windows
+    }
+
+    /// See `WindowManager::get_window_layout`, only the fullscreen window is visible when there is one
+    pub fn get_window_layout(&self) -> WindowLayout {
+        let workspace = self.current_workspace;
+
+        match self.fullscreen(workspace) {
+            Some(window) => {
+                WindowLayout {
+                    focused_window: Some(window),
+                    windows: vec![(window, self.screen.to_geometry())],
+                }
+            }
+            None => {
+                WindowLayout {
+                    focused_window: self.focus[workspace],
+                    windows: self.visible_windows(workspace),
+                }
+            }
+        }
+    }
+
+    /// See `WindowManager::get_focused_window`
+    pub fn get_focused_window(&self) -> Option<Window> {
+        self.get_window_layout().focused_window
+    }
+
+    /// See `WindowManager::get_window_info`, for a window that's not minimised
+    pub fn get_window_info(&self, window: Window) -> Result<WindowWithInfo, ModelError> {
+        let model_window = try!(self.get(window));
+        let workspace = model_window.workspace;
+
+        let geometry = if model_window.fullscreen {
+            self.screen.to_geometry()
+        } else {
+            self.visible_windows(workspace).iter().find(|&&(w, _)| w == window).unwrap().1
+        };
+
+        Ok(WindowWithInfo {
+            window: window,
+            geometry: geometry,
+            float_or_tile: if model_window.floating { FloatOrTile::Float } else { FloatOrTile::Tile },
+            fullscreen: model_window.fullscreen,
+        })
+    }
+
+    /// See `TilingSupport::get_master_window`
+    pub fn get_master_window(&self) -> Option<Window> {
+        self.tiled(self.current_workspace).first().cloned()
+    }
+
+    /// See `FloatSupport::get_floating_windows`
+    pub fn get_floating_windows(&self) -> Vec<Window> {
+        self.floating(self.current_workspace)
+    }
+
+    /// See `MinimiseSupport::get_minimised_windows`
+    pub fn get_minimised_windows(&self) -> Vec<Window> {
+        self.minimised(self.current_workspace)
+    }
+
+    /// See `FullscreenSupport::get_fullscreen_window`
+    pub fn get_fullscreen_window(&self) -> Option<Window> {
+        self.fullscreen(self.current_workspace)
+    }
+}
+
+/// Apply the operation to the model
+pub fn apply_to_model(model: &mut ReferenceWM, operation: &Operation) -> Result<(), ModelError> {
+    match *operation {
+        Operation::AddWindow(info) => model.add_window(info),
+        Operation::RemoveWindow(window) => model.remove_window(window),
+        Operation::FocusWindow(window) => model.focus_window(window),
+        Operation::CycleFocus(dir) => Ok(model.cycle_focus(dir)),
+        Operation::SwapWithMaster(window) => model.swap_with_master(window),
+        Operation::SwapWindows(dir) => Ok(model.swap_windows(dir)),
+        Operation::ToggleFloating(window) => model.toggle_floating(window),
+        Operation::ToggleMinimised(window) => model.toggle_minimised(window),
+        Operation::ToggleFullscreen(window) => model.toggle_fullscreen(window),
+        Operation::SwitchWorkspace(index) => model.switch_workspace(index),
+        Operation::MoveWindowToWorkspace(window, index) => model.move_window_to_workspace(window, index),
+        Operation::ResizeScreen(screen) => Ok(model.resize_screen(screen)),
+        Operation::SetGap(gap) => Ok(model.set_gap(gap)),
+    }
+}
+
+/// Return an error if the window manager and the model give a different answer to the query
+fn check_equal<T: PartialEq + ::std::fmt::Debug>(query: &str, actual: T, expected: T) -> Result<(), String> {
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(format!("{} returned {:?} instead of {:?}", query, actual, expected))
+    }
+}
+
+/// Compare the window layout and the results of the queries of the window manager with the ones of the model
+pub fn compare(wm: &WMName, model: &ReferenceWM) -> Result<(), String> {
+    try!(check_equal("get_window_layout", wm.get_window_layout(), model.get_window_layout()));
+    try!(check_equal("get_windows", wm.get_windows(), model.get_windows()));
+    try!(check_equal("get_focused_window", wm.get_focused_window(), model.get_focused_window()));
+    try!(check_equal("get_master_window", wm.get_master_window(), model.get_master_window()));
+    try!(check_equal("get_floating_windows", wm.get_floating_windows(), model.get_floating_windows()));
+    try!(check_equal("get_minimised_windows", wm.get_minimised_windows(), model.get_minimised_windows()));
+    try!(check_equal("get_fullscreen_window", wm.get_fullscreen_window(), model.get_fullscreen_window()));
+    try!(check_equal("get_current_workspace_index",
+                     wm.get_current_workspace_index(),
+                     model.current_workspace));
+    try!(check_equal("get_screen", wm.get_screen(), model.screen));
+    try!(check_equal("get_gap", wm.get_gap(), model.gaps[model.current_workspace]));
+
+    for model_window in model.windows.iter().filter(|w| !w.minimised) {
+        let window = model_window.window;
+        try!(check_equal(&format!("get_window_info({})", window),
+                         wm.get_window_info(window).ok(),
+                         model.get_window_info(window).ok()));
+    }
+
+    Ok(())
+}
+
+/// Apply the operations to a new window manager and a new model, after every operation the results and the state of
+/// both should be the same
+fn behaves_like_the_model(operations: Vec<Operation>) -> TestResult {
+    let screen = Screen {
+        width: 800,
+        height: 600,
+    };
+    let mut wm = WMName::new(screen);
+    let mut model = ReferenceWM::new(screen);
+
+    for (step, operation) in operations.iter().enumerate() {
+        let result = apply(&mut wm, operation).map_err(|error| error.to_string());
+        let expected = apply_to_model(&mut model, operation).map_err(|error| error.to_string());
+
+        if let Err(error) = check_equal("the operation", result, expected).and_then(|_| compare(&wm, &model)) {
+            return TestResult::error(format!("after step {} ({:?}): {}", step, operation, error));
+        }
+    }
+
+    TestResult::passed()
+}
+
+#[allow(unused_mut)]
+#[allow(unused_variables)]
+mod tests {
+    pub use super::*;
+
+    // Import expectest names
+    pub use expectest::prelude::*;
+
+    describe! reference {
+        before_each {
+            let some_geom = Geometry {
+                x: 10,
+                y: 10,
+                width: 100,
+                height: 100,
+            };
+        }
+
+        it "should behave like the model for random sequences of operations" {
+            QuickCheck::new()
+                .tests(500)
+                .quickcheck(behaves_like_the_model as fn(Vec<Operation>) -> TestResult);
+        }
+
+        it "should behave like the model when minimising, fullscreen and workspaces interact" {
+            let operations = vec![Operation::AddWindow(WindowWithInfo::new_tiled(1, some_geom)),
+                                  Operation::AddWindow(WindowWithInfo::new_float(2, some_geom)),
+                                  Operation::AddWindow(WindowWithInfo::new_fullscreen(3, some_geom)),
+                                  Operation::FocusWindow(Some(7)),
+                                  Operation::ToggleFullscreen(1),
+                                  Operation::ToggleMinimised(1),
+                                  Operation::SwitchWorkspace(1),
+                                  Operation::FocusWindow(Some(1)),
+                                  Operation::FocusWindow(Some(2)),
+                                  Operation::SwapWithMaster(3),
+                                  Operation::MoveWindowToWorkspace(2, 1),
+                                  Operation::ToggleMinimised(2),
+                                  Operation::SwapWithMaster(2)];
+
+            expect!(behaves_like_the_model(operations).is_failure()).to(be_false());
+        }
+
+        it "should behave like the model when the height doesn't divide by the number of slaves" {
+            let operations = vec![Operation::ResizeScreen(Screen { width: 400, height: 301 }),
+                                  Operation::AddWindow(WindowWithInfo::new_tiled(1, some_geom)),
+                                  Operation::AddWindow(WindowWithInfo::new_tiled(2, some_geom)),
+                                  Operation::AddWindow(WindowWithInfo::new_tiled(3, some_geom))];
+
+            expect!(behaves_like_the_model(operations).is_failure()).to(be_false());
+        }
+    }
+}
@@ -0,0 +1,267 @@
+//! This module provides a headless renderer that draws a `WindowLayout` on a grid of characters
+//!
+//! The screen is scaled to the size of the grid and every window is drawn as a box with its number in the top
+//! border. The windows are drawn in stacking order, so a window on top (e.g. a floating window) hides the windows
+//! below it. The focused window is drawn with `#` and `=`, the background with `.`:
+//!
+//! ```text
+//! +1-----------------+#2=================#
+//! |                  |#                  #
+//! |                  |#                  #
+//! +------------------+#==================#
+//! ```
+//!
+//! The rendering of a layout is a lot easier to read than its list of geometries, which makes it useful for
+//! debugging and for snapshot tests.
+//!
+
+use std::cmp;
+
+use cplwm_api::types::{Geometry, Screen, WindowLayout};
+use cplwm_api::wm::WindowManager;
+
+/// The character of the cells without a window
+const BACKGROUND: char = '.';
+
+/// The characters of the border of a window
+struct Border {
+    corner: char,
+    horizontal: char,
+    vertical: char,
+}
+
+/// The border of a window that isn't focused
+const NORMAL: Border = Border {
+    corner: '+',
+    horizontal: '-',
+    vertical: '|',
+};
+
+/// The border of the focused window
+const FOCUSED: Border = Border {
+    corner: '#',
+    horizontal: '=',
+    vertical: '#',
+};
+
+/// Return the cell of the pixel on a grid of `cells` cells for `pixels` pixels.
+///
+/// Pixels outside the screen are scaled to cells outside the grid.
+fn scale(pixel: i64, pixels: i64, cells: usize) -> i64 {
+    let scaled = pixel * cells as i64;
+    if scaled >= 0 {
+        scaled / pixels
+    } else {
+        -((-scaled + pixels - 1) / pixels)
+    }
+}
+
+/// A grid of characters
+struct Grid {
+    cells: Vec<Vec<char>>,
+}
+
+impl Grid {
+    /// Set the cell if it's on the grid
+    fn set(&mut self, column: i64, row: i64, c: char) {
+        if column >= 0 && row >= 0 {
+            if let Some(cell) = self.cells.get_mut(row as usize).and_then(|cells| cells.get_mut(column as usize)) {
+                *cell = c;
+            }
+        }
+    }
+
+    /// Draw a box from (`left`, `top`) to (`right`, `bottom`) (inclusive) with the label in the top border.
+    ///
+    /// The inside of the box is cleared, so the box hides everything that was drawn below it. Only the cells of the
+    /// box that are on the grid are visited, however large the box is.
+    fn draw_box(&mut self, left: i64, top: i64, right: i64, bottom: i64, label: &str, border: &Border) {
+        let rows = self.cells.len() as i64;
+        let columns = self.cells.first().map_or(0, |cells| cells.len()) as i64;
+
+        for row in cmp::max(top, 0)..cmp::min(bottom + 1, rows) {
+            for column in cmp::max(left, 0)..cmp::min(right + 1, columns) {
+                let on_horizontal_border = row == top || row == bottom;
+                let on_vertical_border = column == left || column == right;
+                let c = match (on_horizontal_border, on_vertical_border) {
+                    (true, true) => border.corner,
+                    (true, false) => border.horizontal,
+                    (false, true) => border.vertical,
+                    (false, false) => ' ',
+                };
+                self.set(column, row, c);
+            }
+        }
+
+        // The label starts in the first column of the box on the grid and is cut off when the box isn't wide enough
+        for (column, c) in (cmp::max(left + 1, 0)..right).zip(label.chars()) {
+            self.set(column, top, c);
+        }
+    }
+}
+
+/// Draw the layout of a screen on a grid of `columns` by `rows` characters.
+///
+/// Every row ends with a newline. A window covers at least one character, even if it's too small to be seen at this
+/// scale, and the parts of a window outside the screen are cut off.
+pub fn render(layout: &WindowLayout, screen: Screen, columns: usize, rows: usize) -> String {
+    let mut grid = Grid { cells: vec![vec![BACKGROUND; columns]; rows] };
+
+    if screen.width > 0 && screen.height > 0 {
+        for &(window, Geometry { x, y, width, height }) in &layout.windows {
+            let (x, y) = (x as i64, y as i64);
+            let left = scale(x, screen.width as i64, columns);
+            let top = scale(y, screen.height as i64, rows);
+            let right = cmp::max(scale(x + width as i64, screen.width as i64, columns) - 1, left);
+            let bottom = cmp::max(scale(y + height as i64, screen.height as i64, rows) - 1, top);
+
+            let border = if layout.focused_window == Some(window) {
+                &FOCUSED
+            } else {
+                &NORMAL
+            };
+            grid.draw_box(left, top, right, bottom, &window.to_string(), border);
+        }
+    }
+
+    let mut rendered = String::with_capacity((columns + 1) * rows);
+    for cells in grid.cells {
+        rendered.extend(cells);
+        rendered.push('\n');
+    }
+
+    rendered
+}
+
+/// Draw the layout of the window manager on a grid of `columns` by `rows` characters (see `render`)
+pub fn render_wm<WM: WindowManager>(wm: &WM, columns: usize, rows: usize) -> String {
+    render(&wm.get_window_layout(), wm.get_screen(), columns, rows)
+}
+
+#[cfg(test)]
+#[allow(unused_mut)]
+#[allow(unused_variables)]
+mod tests {
+    pub use super::*;
+
+    pub use cplwm_api::wm::*;
+    pub use cplwm_api::types::*;
+    pub use g_multiple_workspaces::WMName;
+
+    // Import expectest names
+    pub use expectest::prelude::*;
+
+    /// Return the rows of a rendering
+    pub fn rows(rendered: &str) -> Vec<&str> {
+        rendered.lines().collect()
+    }
+
+    describe! render {
+        before_each {
+            let screen: Screen = Screen {
+                width: 800,
+                height: 600,
+            };
+
+            let some_geom: Geometry = Geometry {
+                x: 10,
+                y: 10,
+                width: 100,
+                height: 100,
+            };
+
+            let mut wm = WMName::new(screen);
+        }
+
+        it "should only draw the background for an empty layout" {
+            expect!(render_wm(&wm, 8, 3)).to(be_equal_to(String::from("........\n........\n........\n")));
+        }
+
+        it "should draw a single tiled window over the whole screen" {
+            wm.add_window(WindowWithInfo::new_tiled(1, some_geom)).unwrap();
+            wm.focus_window(None).unwrap();
+
+            expect!(rows(&render_wm(&wm, 20, 5))).to(be_equal_to(vec!["+1-----------------+",
+                                                                       "|                  |",
+                                                                       "|                  |",
+                                                                       "|                  |",
+                                                                       "+------------------+"]));
+        }
+
+        it "should highlight the focused window" {
+            wm.add_window(WindowWithInfo::new_tiled(1, some_geom)).unwrap();
+            wm.add_window(WindowWithInfo::new_tiled(2, some_geom)).unwrap();
+            wm.add_window(WindowWithInfo::new_tiled(3, some_geom)).unwrap();
+
+            expect!(rows(&render_wm(&wm, 20, 6))).to(be_equal_to(vec!["+1-------++2-------+",
+                                                                       "|        ||        |",
+                                                                       "|        |+--------+",
+                                                                       "|        |#3=======#",
+                                                                       "|        |#        #",
+                                                                       "+--------+#========#"]));
+        }
+
+        it "should draw floating windows on top in stacking order" {
+            wm.add_window(WindowWithInfo::new_tiled(1, some_geom)).unwrap();
+            wm.add_window(WindowWithInfo::new_float(2, Geometry { x: 200, y: 120, width: 400, height: 360 })).unwrap();
+            wm.add_window(WindowWithInfo::new_float(3, Geometry { x: 400, y: 240, width: 400, height: 360 })).unwrap();
+            wm.focus_window(Some(2)).unwrap();
+
+            expect!(rows(&render_wm(&wm, 20, 10))).to(be_equal_to(vec!["+1-----------------+",
+                                                                        "|                  |",
+                                                                        "|    #2=======#    |",
+                                                                        "|    #        #    |",
+                                                                        "|    #        #----+",
+                                                                        "|    #        #    |",
+                                                                        "|    #        #    |",
+                                                                        "|    #========#    |",
+                                                                        "|         |        |",
+                                                                        "+---------+--------+"]));
+        }
+
+        it "should only draw the fullscreen window" {
+            wm.add_window(WindowWithInfo::new_tiled(1, some_geom)).unwrap();
+            wm.add_window(WindowWithInfo::new_float(2, some_geom)).unwrap();
+            wm.toggle_fullscreen(1).unwrap();
+
+            expect!(rows(&render_wm(&wm, 10, 3))).to(be_equal_to(vec!["#1=======#", "#        #", "#========#"]));
+        }
+
+        it "should cut off windows outside the screen and labels that don't fit" {
+            let layout = WindowLayout {
+                focused_window: None,
+                windows: vec![(1234, Geometry { x: -200, y: 300, width: 400, height: 600 }),
+                              (5678, Geometry { x: 600, y: 0, width: 200, height: 300 })],
+            };
+
+            expect!(rows(&render(&layout, screen, 8, 4))).to(be_equal_to(vec!["......++",
+                                                                              "......++",
+                                                                              "1+......",
+                                                                              " |......"]));
+        }
+
+        it "should draw a window that is too small as one character" {
+            let layout = WindowLayout {
+                focused_window: Some(1),
+                windows: vec![(1, Geometry { x: 400, y: 300, width: 1, height: 1 })],
+            };
+
+            expect!(rows(&render(&layout, screen, 8, 4))).to(be_equal_to(vec!["........",
+                                                                              "........",
+                                                                              "....#...",
+                                                                              "........"]));
+        }
+
+        it "should draw a huge window by only visiting the cells on the grid" {
+            let layout = WindowLayout {
+                focused_window: None,
+                windows: vec![(1, Geometry { x: -100, y: 0, width: u32::max_value(), height: u32::max_value() })],
+            };
+
+            expect!(rows(&render(&layout, screen, 8, 4))).to(be_equal_to(vec!["1-------",
+                                                                              "        ",
+                                                                              "        ",
+                                                                              "        "]));
+        }
+    }
+}
@@ -0,0 +1,688 @@
+//! This module provides declarative window rules
+//!
+//! A rule matches new windows by their class, title and type (see `WindowDescription`) and applies actions to them:
+//! let them float or tile, give them an initial geometry, put them on a workspace, start them fullscreen or minimised
+//! or don't focus them.
+//!
+//! The `RulesWM` wraps a window manager with multiple workspaces and applies the rules to every window that is added.
+//! Rules are checked in the order they were added, when several rules match a window the actions of the later rules
+//! take precedence.
+//!
+
+use std::os::raw::c_uint;
+
+use cplwm_api::types::{FloatOrTile, GapSize, Geometry, PrevOrNext, Screen, Window, WindowLayout, WindowWithInfo, WorkspaceIndex};
+use cplwm_api::wm::{FloatSupport, FullscreenSupport, GapSupport, MinimiseSupport, MultiWorkspaceSupport, TilingSupport, WindowManager};
+
+use g_multiple_workspaces::{MoveWindowSupport, WMName as WorkspaceWM};
+use direction::{Direction, DirectionSupport};
+use f_gaps::{ExtendedGapSupport, OuterGaps};
+use layouts::{Layout, LayoutSupport};
+use struts::{StrutSupport, Struts};
+use window_description::{DescriptionSupport, WindowDescription, WindowType};
+use events::{Snapshot, SnapshotSupport};
+
+/// Type alias for automated tests
+pub type WMName = RulesWM<WorkspaceWM>;
+
+/// Decides which windows a rule applies to.
+///
+/// Every field that is set has to match, a matcher without fields matches every window.
+#[derive(RustcDecodable, RustcEncodable, Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "with-serde", derive(Serialize, Deserialize))]
+pub struct Matcher {
+    /// The class of the window has to be equal to this class
+    pub class: Option<String>,
+    /// The title of the window has to contain this string
+    pub title: Option<String>,
+    /// The window has to be of this type
+    pub window_type: Option<WindowType>,
+}
+
+impl Matcher {
+    /// Returns true if the window with the given description matches
+    pub fn matches(&self, description: &WindowDescription) -> bool {
+        let class_matches = match self.class {
+            Some(ref class) => description.class.as_ref() == Some(class),
+            None => true,
+        };
+        let title_matches = match self.title {
+            Some(ref title) => description.title.as_ref().map_or(false, |t| t.contains(title.as_str())),
+            None => true,
+        };
+        let type_matches = self.window_type.map_or(true, |window_type| window_type == description.window_type);
+
+        class_matches && title_matches && type_matches
+    }
+}
+
+/// What to do with a window when it's added. Every action that is not set leaves the window as it is.
+#[derive(RustcDecodable, RustcEncodable, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "with-serde", derive(Serialize, Deserialize))]
+pub struct Actions {
+    /// Let the window float or tile (this takes precedence over the type of the window)
+    pub float_or_tile: Option<FloatOrTile>,
+    /// The initial geometry of the window (used when the window floats)
+    pub geometry: Option<Geometry>,
+    /// Put the window on the workspace with this index
+    pub workspace: Option<WorkspaceIndex>,
+    /// Start the window fullscreen (or not)
+    pub fullscreen: Option<bool>,
+    /// Start the window minimised
+    pub minimised: Option<bool>,
+    /// Set to `Some(false)` to keep the focus on the window that was focused before the window was added
+    pub focus: Option<bool>,
+}
+
+impl Actions {
+    /// Combine 2 actions, the actions that are set in `other` take precedence
+    pub fn merge(&self, other: &Actions) -> Actions {
+        Actions {
+            float_or_tile: other.float_or_tile.or(self.float_or_tile),
+            geometry: other.geometry.or(self.geometry),
+            workspace: other.workspace.or(self.workspace),
+            fullscreen: other.fullscreen.or(self.fullscreen),
+            minimised: other.minimised.or(self.minimised),
+            focus: other.focus.or(self.focus),
+        }
+    }
+}
+
+/// A window rule: the actions are applied to every new window that matches
+#[derive(RustcDecodable, RustcEncodable, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "with-serde", derive(Serialize, Deserialize))]
+pub struct Rule {
+    /// The windows this rule applies to
+    pub matcher: Matcher,
+    /// The actions to apply to the matching windows
+    pub actions: Actions,
+}
+
+impl Rule {
+    /// Create a new rule
+    pub fn new(matcher: Matcher, actions: Actions) -> Rule {
+        Rule {
+            matcher: matcher,
+            actions: actions,
+        }
+    }
+}
+
+/// Main struct of the window manager
+/// This WM applies the rules to new windows and uses the WrappedWM for everything else
+#[derive(RustcDecodable, RustcEncodable, Debug, Clone)]
+#[cfg_attr(feature = "with-serde", derive(Serialize, Deserialize))]
+pub struct RulesWM<WrappedWM: WindowManager> {
+    /// The rules, in the order they were added
+    pub rules: Vec<Rule>,
+    /// The wrapped window manager
+    pub wrapped_wm: WrappedWM,
+}
+
+impl<WrappedWM: WindowManager> RulesWM<WrappedWM> {
+    /// Add a rule. It takes precedence over the rules that were added before.
+    pub fn add_rule(&mut self, rule: Rule) {
+        self.rules.push(rule);
+    }
+
+    /// Replace all rules
+    pub fn set_rules(&mut self, rules: Vec<Rule>) {
+        self.rules = rules;
+    }
+
+    /// Return the rules in the order they were added
+    pub fn get_rules(&self) -> &Vec<Rule> {
+        &self.rules
+    }
+
+    /// Return the combined actions of all the rules that match the window
+    pub fn get_actions(&self, description: &WindowDescription) -> Actions {
+        self.rules
+            .iter()
+            .filter(|rule| rule.matcher.matches(description))
+            .fold(Actions::default(), |actions, rule| actions.merge(&rule.actions))
+    }
+}
+
+impl<WrappedWM: DescriptionSupport + MinimiseSupport + MoveWindowSupport> RulesWM<WrappedWM> {
+    /// Add a window with the given actions. The window is added straight to the workspace of the actions so the
+    /// current workspace doesn't change when the window goes elsewhere.
+    fn add_window_with_actions(&mut self, description: WindowDescription, actions: Actions) -> Result<(), WrappedWM::Error> {
+        let window = description.info.window;
+        let previous_focus = self.get_focused_window();
+
+        let mut description = description;
+        if let Some(geometry) = actions.geometry {
+            description.info.geometry = geometry;
+        }
+        if let Some(fullscreen) = actions.fullscreen {
+            description.info.fullscreen = fullscreen;
+        }
+        description.float_or_tile = actions.float_or_tile.or(description.float_or_tile);
+
+        let minimised = actions.minimised == Some(true);
+        match actions.workspace {
+            Some(index) => try!(self.wrapped_wm.add_window_to_workspace(description, index, minimised)),
+            None => {
+                try!(self.wrapped_wm.add_window_with_description(description));
+                if minimised {
+                    try!(self.wrapped_wm.toggle_minimised(window));
+                }
+            }
+        }
+
+        if actions.focus == Some(false) && self.get_focused_window() != previous_focus {
+            try!(self.wrapped_wm.focus_window(previous_focus));
+        }
+
+        Ok(())
+    }
+}
+
+impl<WrappedWM: DescriptionSupport + MinimiseSupport + MoveWindowSupport> WindowManager for RulesWM<WrappedWM> {
+    /// We use the Error from the WrappedWM as our Error type.
+    type Error = WrappedWM::Error;
+
+    fn new(screen: Screen) -> RulesWM<WrappedWM> {
+        RulesWM {
+            rules: Vec::new(),
+            wrapped_wm: WrappedWM::new(screen),
+        }
+    }
+
+    fn get_windows(&self) -> Vec<Window> {
+        self.wrapped_wm.get_windows()
+    }
+
+    /// Only the rules without a class and title can match a window without a description
+    fn add_window(&mut self, window_with_info: WindowWithInfo) -> Result<(), Self::Error> {
+        self.add_window_with_description(WindowDescription::new(window_with_info))
+    }
+
+    fn remove_window(&mut self, window: Window) -> Result<(), Self::Error> {
+        self.wrapped_wm.remove_window(window)
+    }
+
+    fn get_window_layout(&self) -> WindowLayout {
+        self.wrapped_wm.get_window_layout()
+    }
+
+    fn focus_window(&mut self, window: Option<Window>) -> Result<(), Self::Error> {
+        self.wrapped_wm.focus_window(window)
+    }
+
+    fn cycle_focus(&mut self, dir: PrevOrNext) {
+        self.wrapped_wm.cycle_focus(dir)
+    }
+
+    fn get_window_info(&self, window: Window) -> Result<WindowWithInfo, Self::Error> {
+        self.wrapped_wm.get_window_info(window)
+    }
+
+    fn get_screen(&self) -> Screen {
+        self.wrapped_wm.get_screen()
+    }
+
+    fn resize_screen(&mut self, screen: Screen) {
+        self.wrapped_wm.resize_screen(screen)
+    }
+
+    fn get_focused_window(&self) -> Option<Window> {
+        self.wrapped_wm.get_focused_window()
+    }
+
+    fn is_managed(&self, window: Window) -> bool {
+        self.wrapped_wm.is_managed(window)
+    }
+}
+
+impl<WrappedWM: DescriptionSupport + MinimiseSupport + MoveWindowSupport> DescriptionSupport for RulesWM<WrappedWM> {
+    /// Apply the actions of the matching rules to the window.
+    ///
+    /// When a rule decides whether the window floats, it overrules the type of the window through the
+    /// `float_or_tile` of the description. Docks are never affected by the rules.
+    fn add_window_with_description(&mut self, description: WindowDescription) -> Result<(), Self::Error> {
+        if description.is_dock() || self.is_managed(description.info.window) {
+            return self.wrapped_wm.add_window_with_description(description);
+        }
+
+        let actions = self.get_actions(&description);
+        self.add_window_with_actions(description, actions)
+    }
+}
+
+impl<WrappedWM: TilingSupport + DescriptionSupport + MinimiseSupport + MoveWindowSupport> TilingSupport for RulesWM<WrappedWM> {
+    fn get_master_window(&self) -> Option<Window> {
+        self.wrapped_wm.get_master_window()
+    }
+
+    fn swap_with_master(&mut self, window: Window) -> Result<(), Self::Error> {
+        self.wrapped_wm.swap_with_master(window)
+    }
+
+    fn swap_windows(&mut self, dir: PrevOrNext) {
+        self.wrapped_wm.swap_windows(dir)
+    }
+}
+
+impl<WrappedWM: FloatSupport + DescriptionSupport + MinimiseSupport + MoveWindowSupport> FloatSupport for RulesWM<WrappedWM> {
+    fn get_floating_windows(&self) -> Vec<Window> {
+        self.wrapped_wm.get_floating_windows()
+    }
+
+    fn toggle_floating(&mut self, window: Window) -> Result<(), Self::Error> {
+        self.wrapped_wm.toggle_floating(window)
+    }
+
+    fn set_window_geometry(&mut self, window: Window, new_geometry: Geometry) -> Result<(), Self::Error> {
+        self.wrapped_wm.set_window_geometry(window, new_geometry)
+    }
+}
+
+impl<WrappedWM: DescriptionSupport + MinimiseSupport + MoveWindowSupport> MinimiseSupport for RulesWM<WrappedWM> {
+    fn get_minimised_windows(&self) -> Vec<Window> {
+        self.wrapped_wm.get_minimised_windows()
+    }
+
+    fn toggle_minimised(&mut self, window: Window) -> Result<(), Self::Error> {
+        self.wrapped_wm.toggle_minimised(window)
+    }
+}
+
+impl<WrappedWM: FullscreenSupport + DescriptionSupport + MinimiseSupport + MoveWindowSupport> FullscreenSupport for RulesWM<WrappedWM> {
+    fn get_fullscreen_window(&self) -> Option<Window> {
+        self.wrapped_wm.get_fullscreen_window()
+    }
+
+    fn toggle_fullscreen(&mut self, window: Window) -> Result<(), Self::Error> {
+        self.wrapped_wm.toggle_fullscreen(window)
+    }
+}
+
+impl<WrappedWM: GapSupport + DescriptionSupport + MinimiseSupport + MoveWindowSupport> GapSupport for RulesWM<WrappedWM> {
+    fn get_gap(&self) -> GapSize {
+        self.wrapped_wm.get_gap()
+    }
+
+    fn set_gap(&mut self, gapsize: GapSize) {
+        self.wrapped_wm.set_gap(gapsize)
+    }
+}
+
+impl<WrappedWM: ExtendedGapSupport + DescriptionSupport + MinimiseSupport + MoveWindowSupport> ExtendedGapSupport for RulesWM<WrappedWM> {
+    fn get_inner_gap(&self) -> GapSize {
+        self.wrapped_wm.get_inner_gap()
+    }
+
+    fn set_inner_gap(&mut self, gap: GapSize) {
+        self.wrapped_wm.set_inner_gap(gap)
+    }
+
+    fn get_outer_gaps(&self) -> OuterGaps {
+        self.wrapped_wm.get_outer_gaps()
+    }
+
+    fn set_outer_gaps(&mut self, gaps: OuterGaps) {
+        self.wrapped_wm.set_outer_gaps(gaps)
+    }
+
+    fn get_smart_gaps(&self) -> bool {
+        self.wrapped_wm.get_smart_gaps()
+    }
+
+    fn set_smart_gaps(&mut self, smart_gaps: bool) {
+        self.wrapped_wm.set_smart_gaps(smart_gaps)
+    }
+
+    fn get_min_window_size(&self) -> c_uint {
+        self.wrapped_wm.get_min_window_size()
+    }
+
+    fn set_min_window_size(&mut self, size: c_uint) {
+        self.wrapped_wm.set_min_window_size(size)
+    }
+
+    fn try_set_gap(&mut self, gapsize: GapSize) -> Result<(), Self::Error> {
+        self.wrapped_wm.try_set_gap(gapsize)
+    }
+}
+
+impl<WrappedWM: LayoutSupport + DescriptionSupport + MinimiseSupport + MoveWindowSupport> LayoutSupport for RulesWM<WrappedWM> {
+    fn get_layout(&self) -> Layout {
+        self.wrapped_wm.get_layout()
+    }
+
+    fn set_layout(&mut self, layout: Layout) {
+        self.wrapped_wm.set_layout(layout)
+    }
+}
+
+impl<WrappedWM: StrutSupport + DescriptionSupport + MinimiseSupport + MoveWindowSupport> StrutSupport for RulesWM<WrappedWM> {
+    fn get_struts(&self) -> Struts {
+        self.wrapped_wm.get_struts()
+    }
+
+    fn set_struts(&mut self, struts: Struts) {
+        self.wrapped_wm.set_struts(struts)
+    }
+
+    fn add_dock(&mut self, window: Window, struts: Struts) {
+        self.wrapped_wm.add_dock(window, struts)
+    }
+
+    fn remove_dock(&mut self, window: Window) -> Result<(), Self::Error> {
+        self.wrapped_wm.remove_dock(window)
+    }
+
+    fn get_docks(&self) -> Vec<Window> {
+        self.wrapped_wm.get_docks()
+    }
+
+    fn copy_struts(&mut self, other: &RulesWM<WrappedWM>) {
+        self.wrapped_wm.copy_struts(&other.wrapped_wm)
+    }
+}
+
+impl<WrappedWM: DirectionSupport + DescriptionSupport + MinimiseSupport + MoveWindowSupport> DirectionSupport for RulesWM<WrappedWM> {
+    fn focus_direction(&mut self, dir: Direction) {
+        self.wrapped_wm.focus_direction(dir)
+    }
+
+    fn swap_direction(&mut self, dir: Direction) {
+        self.wrapped_wm.swap_direction(dir)
+    }
+}
+
+impl<WrappedWM: DescriptionSupport + MinimiseSupport + MoveWindowSupport> MoveWindowSupport for RulesWM<WrappedWM> {
+    fn move_window_to_workspace(&mut self, window: Window, index: WorkspaceIndex) -> Result<(), Self::Error> {
+        self.wrapped_wm.move_window_to_workspace(window, index)
+    }
+
+    /// The other actions of the matching rules are still applied to the window
+    fn add_window_to_workspace(&mut self, description: WindowDescription, index: WorkspaceIndex, minimised: bool) -> Result<(), Self::Error> {
+        if description.is_dock() || self.is_managed(description.info.window) {
+            return self.wrapped_wm.add_window_to_workspace(description, index, minimised);
+        }
+
+        let rule_actions = self.get_actions(&description);
+        let actions = Actions {
+            workspace: Some(index),
+            minimised: Some(minimised || rule_actions.minimised == Some(true)),
+            ..rule_actions
+        };
+        self.add_window_with_actions(description, actions)
+    }
+}
+
+impl<WrappedWM: SnapshotSupport + DescriptionSupport + MinimiseSupport + MoveWindowSupport> SnapshotSupport for RulesWM<WrappedWM> {
+    fn get_snapshot(&self) -> Snapshot {
+        self.wrapped_wm.get_snapshot()
+    }
+}
+
+impl<WM, WrappedWM> MultiWorkspaceSupport<WM> for RulesWM<WrappedWM>
+    where WM: WindowManager,
+          WrappedWM: MultiWorkspaceSupport<WM> + DescriptionSupport + MinimiseSupport + MoveWindowSupport
+{
+    fn get_current_workspace_index(&self) -> WorkspaceIndex {
+        self.wrapped_wm.get_current_workspace_index()
+    }
+
+    fn get_workspace(&self, index: WorkspaceIndex) -> Result<&WM, Self::Error> {
+        self.wrapped_wm.get_workspace(index)
+    }
+
+    fn get_workspace_mut(&mut self, index: WorkspaceIndex) -> Result<&mut WM, Self::Error> {
+        self.wrapped_wm.get_workspace_mut(index)
+    }
+
+    fn switch_workspace(&mut self, index: WorkspaceIndex) -> Result<(), Self::Error> {
+        self.wrapped_wm.switch_workspace(index)
+    }
+}
+
+#[cfg(test)]
+#[allow(unused_mut)]
+#[allow(unused_variables)]
+mod tests {
+    pub use super::*;
+    // Expectest has a Matcher as well
+    pub use super::Matcher;
+
+    pub use cplwm_api::wm::*;
+    pub use cplwm_api::types::*;
+    pub use cplwm_api::types::FloatOrTile::*;
+    pub use struts::*;
+    pub use window_description::*;
+
+    // Import expectest names
+    pub use expectest::prelude::*;
+
+    conformance_tests!(WMName: window_manager, tiling, floating, minimising, fullscreen, gap, workspaces);
+
+    describe! matcher {
+        before_each {
+            let some_geom: Geometry = Geometry {
+                x: 10,
+                y: 10,
+                width: 100,
+                height: 100,
+            };
+
+            let mut description = WindowDescription::new(WindowWithInfo::new_tiled(1, some_geom));
+            description.class = Some(String::from("Firefox"));
+            description.title = Some(String::from("Rust - Mozilla Firefox"));
+        }
+
+        it "should match every window without fields" {
+            expect!(Matcher::default().matches(&description)).to(be_true());
+        }
+
+        it "should match the class exactly" {
+            let matcher = Matcher { class: Some(String::from("Firefox")), ..Matcher::default() };
+            let other_matcher = Matcher { class: Some(String::from("Fire")), ..Matcher::default() };
+
+            expect!(matcher.matches(&description)).to(be_true());
+            expect!(other_matcher.matches(&description)).to(be_false());
+        }
+
+        it "should match a part of the title" {
+            let matcher = Matcher { title: Some(String::from("Rust")), ..Matcher::default() };
+            let other_matcher = Matcher { title: Some(String::from("Chrome")), ..Matcher::default() };
+
+            expect!(matcher.matches(&description)).to(be_true());
+            expect!(other_matcher.matches(&description)).to(be_false());
+        }
+
+        it "should match all fields" {
+            let matcher = Matcher {
+                class: Some(String::from("Firefox")),
+                title: None,
+                window_type: Some(WindowType::Dialog),
+            };
+
+            expect!(matcher.matches(&description)).to(be_false());
+        }
+
+        it "should not match a window without a class" {
+            let matcher = Matcher { class: Some(String::from("Firefox")), ..Matcher::default() };
+
+            expect!(matcher.matches(&WindowDescription::new(WindowWithInfo::new_tiled(2, some_geom)))).to(be_false());
+        }
+    }
+
+    describe! wm {
+        before_each {
+            let screen: Screen = Screen {
+                width: 800,
+                height: 600,
+            };
+            let screen_geom = screen.to_geometry();
+
+            let some_geom: Geometry = Geometry {
+                x: 10,
+                y: 10,
+                width: 100,
+                height: 100,
+            };
+
+            let floating_geom: Geometry = Geometry {
+                x: 20,
+                y: 40,
+                width: 200,
+                height: 20,
+            };
+
+            let firefox = Matcher { class: Some(String::from("Firefox")), ..Matcher::default() };
+            let mut description = WindowDescription::new(WindowWithInfo::new_tiled(2, some_geom));
+            description.class = Some(String::from("Firefox"));
+
+            let mut wm = WMName::new(screen);
+            wm.add_window(WindowWithInfo::new_tiled(1, some_geom)).unwrap();
+        }
+
+        it "should work like the wrapped WM without rules" {
+            wm.add_window_with_description(description).unwrap();
+
+            expect!(wm.get_focused_window()).to(be_equal_to(Some(2)));
+            expect!(wm.get_windows()).to(be_equal_to(vec![1, 2]));
+            expect!(wm.get_floating_windows()).to(be_equal_to(vec![]));
+        }
+
+        it "should let a window float with a geometry" {
+            wm.add_rule(Rule::new(firefox, Actions {
+                float_or_tile: Some(Float),
+                geometry: Some(floating_geom),
+                ..Actions::default()
+            }));
+
+            wm.add_window_with_description(description).unwrap();
+
+            expect!(wm.get_floating_windows()).to(be_equal_to(vec![2]));
+            expect!(wm.get_window_layout().windows).to(be_equal_to(vec![(1, screen_geom), (2, floating_geom)]));
+        }
+
+        it "should let a rule tile a dialog" {
+            wm.add_rule(Rule::new(firefox, Actions { float_or_tile: Some(Tile), ..Actions::default() }));
+            description.window_type = WindowType::Dialog;
+
+            wm.add_window_with_description(description).unwrap();
+
+            expect!(wm.get_floating_windows()).to(be_equal_to(vec![]));
+        }
+
+        it "should keep the parent of a window that a rule tiles" {
+            wm.add_rule(Rule::new(firefox, Actions { float_or_tile: Some(Tile), ..Actions::default() }));
+            wm.switch_workspace(1).unwrap();
+            description.transient_for = Some(1);
+
+            wm.add_window_with_description(description).unwrap();
+
+            expect!(wm.get_current_workspace_index()).to(be_equal_to(1));
+            expect!(wm.get_workspace(0).unwrap().get_windows()).to(be_equal_to(vec![1, 2]));
+            expect!(wm.get_workspace(0).unwrap().get_floating_windows()).to(be_equal_to(vec![]));
+        }
+
+        it "should put a window on a workspace" {
+            wm.add_rule(Rule::new(firefox, Actions { workspace: Some(3), ..Actions::default() }));
+
+            wm.add_window_with_description(description).unwrap();
+
+            expect!(wm.get_current_workspace_index()).to(be_equal_to(0));
+            expect!(wm.get_workspace(0).unwrap().get_windows()).to(be_equal_to(vec![1]));
+            expect!(wm.get_workspace(3).unwrap().get_windows()).to(be_equal_to(vec![2]));
+            expect!(wm.get_focused_window()).to(be_equal_to(Some(1)));
+        }
+
+        it "should error on an unknown workspace" {
+            wm.add_rule(Rule::new(firefox, Actions { workspace: Some(MAX_WORKSPACE_INDEX + 1), ..Actions::default() }));
+
+            expect!(wm.add_window_with_description(description)).to(be_err());
+            expect!(wm.is_managed(2)).to(be_false());
+            expect!(wm.get_focused_window()).to(be_equal_to(Some(1)));
+        }
+
+        it "should not touch the current workspace when putting a window on another one" {
+            wm.add_rule(Rule::new(firefox, Actions { workspace: Some(3), ..Actions::default() }));
+            wm.toggle_fullscreen(1).unwrap();
+
+            wm.add_window_with_description(description).unwrap();
+
+            expect!(wm.get_fullscreen_window()).to(be_equal_to(Some(1)));
+            expect!(wm.get_focused_window()).to(be_equal_to(Some(1)));
+            expect!(wm.get_workspace(3).unwrap().get_focused_window()).to(be_equal_to(Some(2)));
+        }
+
+        it "should start a window minimised on another workspace" {
+            wm.add_rule(Rule::new(firefox, Actions {
+                workspace: Some(2),
+                minimised: Some(true),
+                ..Actions::default()
+            }));
+
+            wm.add_window_with_description(description).unwrap();
+
+            expect!(wm.get_workspace(2).unwrap().get_minimised_windows()).to(be_equal_to(vec![2]));
+            expect!(wm.get_focused_window()).to(be_equal_to(Some(1)));
+        }
+
+        it "should start a window fullscreen" {
+            wm.add_rule(Rule::new(firefox, Actions { fullscreen: Some(true), ..Actions::default() }));
+
+            wm.add_window_with_description(description).unwrap();
+
+            expect!(wm.get_fullscreen_window()).to(be_equal_to(Some(2)));
+        }
+
+        it "should start a window minimised" {
+            wm.add_rule(Rule::new(firefox, Actions { minimised: Some(true), ..Actions::default() }));
+
+            wm.add_window_with_description(description).unwrap();
+
+            expect!(wm.get_minimised_windows()).to(be_equal_to(vec![2]));
+            expect!(wm.get_focused_window()).to(be_equal_to(Some(1)));
+        }
+
+        it "should not focus a window" {
+            wm.add_rule(Rule::new(firefox, Actions { focus: Some(false), ..Actions::default() }));
+
+            wm.add_window_with_description(description).unwrap();
+
+            expect!(wm.get_focused_window()).to(be_equal_to(Some(1)));
+            expect!(wm.get_windows()).to(be_equal_to(vec![1, 2]));
+        }
+
+        it "should let later rules take precedence" {
+            wm.add_rule(Rule::new(Matcher::default(), Actions {
+                float_or_tile: Some(Float),
+                workspace: Some(1),
+                ..Actions::default()
+            }));
+            wm.add_rule(Rule::new(firefox, Actions { float_or_tile: Some(Tile), ..Actions::default() }));
+
+            wm.add_window_with_description(description).unwrap();
+
+            expect!(wm.get_workspace(1).unwrap().get_windows()).to(be_equal_to(vec![2]));
+            expect!(wm.get_workspace(1).unwrap().get_floating_windows()).to(be_equal_to(vec![]));
+        }
+
+        it "should apply rules without a class to windows added with add_window" {
+            wm.add_rule(Rule::new(Matcher::default(), Actions { float_or_tile: Some(Float), ..Actions::default() }));
+
+            wm.add_window(WindowWithInfo::new_tiled(3, floating_geom)).unwrap();
+
+            expect!(wm.get_floating_windows()).to(be_equal_to(vec![3]));
+        }
+
+        it "should not apply rules to docks" {
+            wm.add_rule(Rule::new(Matcher::default(), Actions { workspace: Some(1), ..Actions::default() }));
+            let struts = Struts { top: 20, bottom: 0, left: 0, right: 0 };
+
+            wm.add_window_with_description(WindowDescription::new_dock(WindowWithInfo::new_tiled(10, some_geom), struts)).unwrap();
+
+            expect!(wm.get_docks()).to(be_equal_to(vec![10]));
+            let usable_area = Geometry { x: 0, y: 20, width: 800, height: 580 };
+            expect!(wm.get_window_layout().windows).to(be_equal_to(vec![(1, usable_area)]));
+        }
+    }
+}
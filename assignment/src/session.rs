@@ -0,0 +1,373 @@
+//! This module saves the state of a window manager to a file and restores it
+//!
+//! The complete state of the window manager (e.g. the workspaces, floating geometries, minimised windows,
+//! the fullscreen window, the gap and the layouter settings) is encoded as JSON.
+//!
+//! When the window manager is restarted in place, `restore` loads the state again, removes the windows that don't
+//! exist anymore and resizes the window manager to the current screen.
+//!
+//! The state is wrapped in a versioned envelope: `{"version": 1, "state": ...}`. Older states (including the
+//! unversioned session files of version 0) are upgraded by the `migrations` module before they are decoded. The
+//! golden files in `tests/golden` contain a session file of every version, and old session files of `f_gaps::WMName`
+//! for a window manager without workspaces.
+//!
+
+use std::fs;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use rustc_serialize::{Decodable, Encodable};
+use rustc_serialize::json;
+use rustc_serialize::json::{DecoderError, Json};
+
+use cplwm_api::types::{Screen, Window};
+
+use error::SessionError;
+use migrations;
+use migrations::SESSION_VERSION;
+use struts::StrutSupport;
+
+/// The versioned envelope around the encoded state
+#[derive(RustcEncodable)]
+struct Envelope<'a, WM: 'a> {
+    version: u64,
+    state: &'a WM,
+}
+
+/// Encode the state of the window manager in a versioned envelope
+pub fn encode<WM: Encodable>(wm: &WM) -> Result<String, SessionError> {
+    Ok(try!(json::encode(&Envelope {
+        version: SESSION_VERSION,
+        state: wm,
+    })))
+}
+
+/// Decode the state of a window manager, upgrading it first if it was encoded by an older version.
+///
+/// A JSON value without an envelope is an (unversioned) state of version 0.
+pub fn decode<WM: Decodable>(encoded: &str) -> Result<WM, SessionError> {
+    let json = try!(Json::from_str(encoded).map_err(DecoderError::ParseError));
+
+    let (version, state) = match json {
+        Json::Object(mut envelope) => {
+            match (envelope.remove("version"), envelope.remove("state")) {
+                (Some(Json::U64(version)), Some(state)) if envelope.is_empty() => (version, state),
+                (version, state) => {
+                    // Not an envelope, put everything back
+                    if let Some(version) = version {
+                        envelope.insert("version".to_string(), version);
+                    }
+                    if let Some(state) = state {
+                        envelope.insert("state".to_string(), state);
+                    }
+                    (0, Json::Object(envelope))
+                }
+            }
+        }
+        json => (0, json),
+    };
+
+    let state = try!(migrations::migrate(state, version));
+    Ok(try!(WM::decode(&mut json::Decoder::new(state))))
+}
+
+/// Write the state of the window manager to the file at the given path.
+///
+/// The state is first written to a temporary file next to it (the file name with `.tmp` appended), so an existing
+/// session file is never left half written.
+pub fn save<WM: Encodable, P: AsRef<Path>>(wm: &WM, path: P) -> Result<(), SessionError> {
+    let encoded = try!(encode(wm));
+
+    let path = path.as_ref();
+    let mut temporary_path = path.as_os_str().to_os_string();
+    temporary_path.push(".tmp");
+    let temporary_path = PathBuf::from(temporary_path);
+    {
+        let mut file = try!(File::create(&temporary_path));
+        try!(file.write_all(encoded.as_bytes()));
+        try!(file.sync_all());
+    }
+    try!(fs::rename(&temporary_path, path));
+
+    Ok(())
+}
+
+/// Read the state of a window manager from the file at the given path, exactly as it was saved.
+///
+/// Session files of older versions are upgraded (see `decode`).
+pub fn load<WM: Decodable, P: AsRef<Path>>(path: P) -> Result<WM, SessionError> {
+    let mut encoded = String::new();
+    try!(try!(File::open(path)).read_to_string(&mut encoded));
+
+    decode(&encoded)
+}
+
+/// Remove the windows (and docks) that are not in `existing_windows`.
+///
+/// The window managers forget everything they know about these windows (e.g. their floating geometry).
+pub fn prune_windows<WM: StrutSupport>(wm: &mut WM, existing_windows: &[Window]) -> Result<(), SessionError> {
+    let mut windows = wm.get_windows();
+    windows.extend(wm.get_docks());
+
+    for window in windows.into_iter().filter(|w| !existing_windows.contains(w)) {
+        try!(wm.remove_window(window).map_err(|error| SessionError::InvalidState(error.to_string())));
+    }
+
+    Ok(())
+}
+
+/// Load the state of a window manager after a restart: the windows that are not in `existing_windows` are pruned
+/// (see `prune_windows`) and the window manager is resized to the given screen.
+pub fn restore<WM, P>(path: P, screen: Screen, existing_windows: &[Window]) -> Result<WM, SessionError>
+    where WM: StrutSupport + Decodable,
+          P: AsRef<Path>
+{
+    let mut wm: WM = try!(load(path));
+
+    try!(prune_windows(&mut wm, existing_windows));
+    wm.resize_screen(screen);
+
+    Ok(wm)
+}
+
+#[cfg(test)]
+#[allow(unused_mut)]
+#[allow(unused_variables)]
+mod tests {
+    pub use super::*;
+
+    pub use std::env;
+    pub use std::sync::atomic::{AtomicUsize, ATOMIC_USIZE_INIT, Ordering};
+
+    pub use cplwm_api::wm::*;
+    pub use cplwm_api::types::*;
+    pub use error::SessionError;
+    pub use f_gaps::WMName as GapsWM;
+    pub use g_multiple_workspaces::WMName;
+    pub use layouts::*;
+    pub use struts::*;
+
+    // Import expectest names
+    pub use expectest::prelude::*;
+
+    /// Every test uses its own session file
+    static SESSION_COUNTER: AtomicUsize = ATOMIC_USIZE_INIT;
+
+    /// Old golden session files of `f_gaps::WMName`, which has no workspaces and still uses a `SimpleLayouter`
+    static F_GAPS_GOLDEN_FILES: &'static [&'static str] = &[include_str!("../tests/golden/f_gaps_session_v0.json"),
+                                                             include_str!("../tests/golden/f_gaps_session_v3.json")];
+
+    /// The golden session files, every version encodes the same state (see `golden_files`)
+    static GOLDEN_FILES: &'static [(u64, &'static str)] = &[(0, include_str!("../tests/golden/session_v0.json")),
+                                                            (1, include_str!("../tests/golden/session_v1.json")),
+                                                            (2, include_str!("../tests/golden/session_v2.json")),
+                                                            (3, include_str!("../tests/golden/session_v3.json")),
+                                                            (4, include_str!("../tests/golden/session_v4.json"))];
+
+    describe! session {
+        before_each {
+            let screen: Screen = Screen {
+                width: 800,
+                height: 600,
+            };
+
+            let some_geom: Geometry = Geometry {
+                x: 10,
+                y: 10,
+                width: 100,
+                height: 100,
+            };
+
+            let floating_geom: Geometry = Geometry {
+                x: 20,
+                y: 40,
+                width: 200,
+                height: 20,
+            };
+
+            let session = SESSION_COUNTER.fetch_add(1, Ordering::SeqCst);
+            let path = env::temp_dir().join(format!("cplwm-session-test-{}.json", session));
+
+            let mut wm = WMName::new(screen);
+            wm.set_gap(10);
+            wm.add_dock(10, Struts { top: 20, bottom: 0, left: 0, right: 0 });
+            wm.add_window(WindowWithInfo::new_tiled(1, some_geom)).unwrap();
+            wm.add_window(WindowWithInfo::new_float(2, floating_geom)).unwrap();
+            wm.add_window(WindowWithInfo::new_tiled(3, some_geom)).unwrap();
+            wm.toggle_minimised(3).unwrap();
+            wm.switch_workspace(1).unwrap();
+            wm.add_window(WindowWithInfo::new_tiled(4, some_geom)).unwrap();
+            wm.toggle_fullscreen(4).unwrap();
+            wm.switch_workspace(0).unwrap();
+        }
+
+        after_each {
+            let _ = fs::remove_file(&path);
+        }
+
+        it "should restore the state that was saved" {
+            save(&wm, &path).unwrap();
+            let mut loaded: WMName = load(&path).unwrap();
+
+            expect!(loaded.get_windows()).to(be_equal_to(wm.get_windows()));
+            expect!(loaded.get_window_layout()).to(be_equal_to(wm.get_window_layout()));
+            expect!(loaded.get_floating_windows()).to(be_equal_to(vec![2]));
+            expect!(loaded.get_minimised_windows()).to(be_equal_to(vec![3]));
+            expect!(loaded.get_gap()).to(be_equal_to(10));
+            expect!(loaded.get_docks()).to(be_equal_to(vec![10]));
+
+            loaded.switch_workspace(1).unwrap();
+            expect!(loaded.get_fullscreen_window()).to(be_equal_to(Some(4)));
+        }
+
+        it "should prune the windows that don't exist anymore" {
+            save(&wm, &path).unwrap();
+            let mut restored: WMName = restore(&path, screen, &[1, 4]).unwrap();
+
+            expect!(restored.get_windows()).to(be_equal_to(vec![1, 4]));
+            expect!(restored.get_docks()).to(be_equal_to(vec![]));
+            expect!(restored.get_minimised_windows()).to(be_equal_to(vec![]));
+            expect!(restored.get_window_layout().windows).to(be_equal_to(vec![(1, Geometry { x: 10, y: 10, width: 780, height: 580 })]));
+
+            let workspace = restored.get_workspace(0).unwrap();
+            expect!(workspace.wrapped_wm.infos.contains_key(&3)).to(be_false());
+            expect!(workspace.wrapped_wm.wrapped_wm.infos.contains_key(&2)).to(be_false());
+        }
+
+        it "should resize the restored window manager to the new screen" {
+            save(&wm, &path).unwrap();
+            let new_screen = Screen { width: 1024, height: 768 };
+            let mut restored: WMName = restore(&path, new_screen, &[1, 2, 3, 4, 10]).unwrap();
+
+            expect!(restored.get_screen()).to(be_equal_to(new_screen));
+            restored.switch_workspace(1).unwrap();
+            expect!(restored.get_window_layout().windows).to(be_equal_to(vec![(4, new_screen.to_geometry())]));
+        }
+
+        it "should replace an existing session file" {
+            save(&WMName::new(screen), &path).unwrap();
+            save(&wm, &path).unwrap();
+
+            let loaded: WMName = load(&path).unwrap();
+            expect!(loaded.get_windows()).to(be_equal_to(wm.get_windows()));
+        }
+
+        it "should not touch the files with the same name but another extension" {
+            let other_path = path.with_extension("tmp");
+            File::create(&other_path).unwrap().write_all(b"other").unwrap();
+
+            save(&wm, &path).unwrap();
+
+            let mut other = String::new();
+            File::open(&other_path).unwrap().read_to_string(&mut other).unwrap();
+            fs::remove_file(&other_path).unwrap();
+            expect!(other).to(be_equal_to(String::from("other")));
+            expect!(load::<WMName, _>(&path)).to(be_ok());
+        }
+
+        it "should return an error for a missing or invalid file" {
+            match load::<WMName, _>(env::temp_dir().join("cplwm-session-test-missing.json")) {
+                Err(SessionError::Io(_)) => (),
+                result => panic!("Expected an IO error, got {:?}", result),
+            }
+
+            File::create(&path).unwrap().write_all(b"{}").unwrap();
+            match load::<WMName, _>(&path) {
+                Err(SessionError::DecodeError(_)) => (),
+                result => panic!("Expected a decode error, got {:?}", result),
+            }
+        }
+    }
+
+    describe! golden_files {
+        before_each {
+            let screen: Screen = Screen {
+                width: 800,
+                height: 600,
+            };
+
+            let some_geom: Geometry = Geometry {
+                x: 10,
+                y: 10,
+                width: 100,
+                height: 100,
+            };
+
+            let floating_geom: Geometry = Geometry {
+                x: 20,
+                y: 40,
+                width: 200,
+                height: 20,
+            };
+
+            // The state in the golden files
+            let mut wm = WMName::new(screen);
+            for index in 0..MAX_WORKSPACE_INDEX + 1 {
+                wm.get_workspace_mut(index).unwrap().set_gap(10);
+            }
+            wm.add_window(WindowWithInfo::new_tiled(1, some_geom)).unwrap();
+            wm.add_window(WindowWithInfo::new_float(2, floating_geom)).unwrap();
+            wm.add_window(WindowWithInfo::new_tiled(3, some_geom)).unwrap();
+            wm.toggle_minimised(3).unwrap();
+            wm.switch_workspace(1).unwrap();
+            wm.add_window(WindowWithInfo::new_tiled(4, some_geom)).unwrap();
+            wm.toggle_fullscreen(4).unwrap();
+            wm.switch_workspace(0).unwrap();
+        }
+
+        it "should have a golden file of every version" {
+            let versions: Vec<u64> = GOLDEN_FILES.iter().map(|&(version, _)| version).collect();
+            expect!(versions).to(be_equal_to((0..SESSION_VERSION + 1).collect::<Vec<_>>()));
+        }
+
+        it "should load the golden file of every version" {
+            for &(version, golden) in GOLDEN_FILES {
+                let mut loaded: WMName = decode(golden).unwrap();
+
+                expect!(loaded.get_windows()).to(be_equal_to(wm.get_windows()));
+                expect!(loaded.get_window_layout()).to(be_equal_to(wm.get_window_layout()));
+                expect!(loaded.get_floating_windows()).to(be_equal_to(vec![2]));
+                expect!(loaded.get_minimised_windows()).to(be_equal_to(vec![3]));
+                expect!(loaded.get_gap()).to(be_equal_to(10));
+                expect!(loaded.get_docks()).to(be_equal_to(vec![]));
+
+                expect!(loaded.get_layout()).to(be_equal_to(Layout::Simple));
+
+                loaded.switch_workspace(1).unwrap();
+                expect!(loaded.get_gap()).to(be_equal_to(10));
+                expect!(loaded.get_fullscreen_window()).to(be_equal_to(Some(4)));
+                expect!(loaded.get_window_layout()).to(be_equal_to(wm.get_workspace(1).unwrap().get_window_layout()));
+            }
+        }
+
+        it "should load the old golden files of a window manager without workspaces" {
+            let mut expected = GapsWM::new(screen);
+            expected.set_gap(10);
+            expected.add_window(WindowWithInfo::new_tiled(1, some_geom)).unwrap();
+            expected.add_window(WindowWithInfo::new_tiled(2, some_geom)).unwrap();
+
+            for golden in F_GAPS_GOLDEN_FILES {
+                let loaded: GapsWM = decode(golden).unwrap();
+
+                expect!(loaded.get_window_layout()).to(be_equal_to(expected.get_window_layout()));
+                expect!(loaded.get_gap()).to(be_equal_to(10));
+                expect!(loaded.layouter.wrapped_layouter.master_ratio).to(be_equal_to(0.5));
+            }
+        }
+
+        it "should encode the state of the golden file of the current version" {
+            let (_, golden) = GOLDEN_FILES[SESSION_VERSION as usize];
+            let encoded = Json::from_str(&encode(&wm).unwrap()).unwrap();
+            expect!(encoded).to(be_equal_to(Json::from_str(golden).unwrap()));
+        }
+
+        it "should refuse a session file of a newer version" {
+            let encoded = format!(r#"{{"version":{},"state":{{}}}}"#, SESSION_VERSION + 1);
+            match decode::<WMName>(&encoded) {
+                Err(SessionError::UnsupportedVersion(version)) => expect!(version).to(be_equal_to(SESSION_VERSION + 1)),
+                result => panic!("Expected an unsupported version, got {:?}", result),
+            }
+        }
+    }
+}
@@ -0,0 +1,116 @@
+//! This module provides reserved screen edges (struts), e.g. for status bars and docks
+use std::cmp;
+use std::os::raw::{c_int, c_uint};
+
+use cplwm_api::types::{Geometry, Window};
+use cplwm_api::wm::WindowManager;
+
+/// The size of the strips that are reserved at the edges of the screen
+#[derive(RustcDecodable, RustcEncodable, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "with-serde", derive(Serialize, Deserialize))]
+pub struct Struts {
+    /// The height of the strip at the top of the screen
+    pub top: c_uint,
+    /// The height of the strip at the bottom of the screen
+    pub bottom: c_uint,
+    /// The width of the strip at the left of the screen
+    pub left: c_uint,
+    /// The width of the strip at the right of the screen
+    pub right: c_uint,
+}
+
+impl Struts {
+    /// No reserved edges
+    pub fn new() -> Struts {
+        Struts::default()
+    }
+
+    /// Combine 2 struts by taking the largest strip for each side
+    pub fn max(&self, other: &Struts) -> Struts {
+        Struts {
+            top: cmp::max(self.top, other.top),
+            bottom: cmp::max(self.bottom, other.bottom),
+            left: cmp::max(self.left, other.left),
+            right: cmp::max(self.right, other.right),
+        }
+    }
+
+    /// Return the part of the given geometry that is not reserved.
+    /// The width and height are 0 if the struts are larger than the geometry.
+    pub fn apply(&self, geometry: Geometry) -> Geometry {
+        Geometry {
+            x: geometry.x + cmp::min(self.left, geometry.width) as c_int,
+            y: geometry.y + cmp::min(self.top, geometry.height) as c_int,
+            width: geometry.width.saturating_sub(self.left).saturating_sub(self.right),
+            height: geometry.height.saturating_sub(self.top).saturating_sub(self.bottom),
+        }
+    }
+}
+
+/// A window manager that keeps tiled windows out of the reserved edges of the screen.
+///
+/// Edges can be reserved explicitly with `set_struts` or by docks (e.g. a status bar) with `add_dock`.
+/// The reserved edges are the largest strip for each side of the explicit struts and the struts of all docks.
+pub trait StrutSupport: WindowManager {
+    /// Return the reserved edges (the combination of the explicit struts and the struts of the docks).
+    ///
+    /// Initially no edges are reserved.
+    fn get_struts(&self) -> Struts;
+
+    /// Set the explicitly reserved edges.
+    fn set_struts(&mut self, struts: Struts);
+
+    /// Reserve edges for a dock window. The dock window itself is not managed by the window manager.
+    ///
+    /// If the window already is a dock, its struts are replaced.
+    fn add_dock(&mut self, window: Window, struts: Struts);
+
+    /// Release the edges that were reserved for the dock window.
+    ///
+    /// This function *should* return an appropriate error when the window is not a dock.
+    fn remove_dock(&mut self, window: Window) -> Result<(), Self::Error>;
+
+    /// Return the dock windows in the order they were added.
+    fn get_docks(&self) -> Vec<Window>;
+
+    /// Reserve the same edges as the other window manager: its explicit struts and its docks replace the ones of
+    /// this window manager.
+    fn copy_struts(&mut self, other: &Self);
+}
+
+#[cfg(test)]
+mod tests {
+    pub use super::*;
+
+    pub use cplwm_api::types::Geometry;
+
+    // Import expectest names
+    pub use expectest::prelude::*;
+
+    describe! struts {
+        before_each {
+            let geom = Geometry { x: 10, y: 20, width: 800, height: 600 };
+            let struts = Struts { top: 20, bottom: 0, left: 5, right: 10 };
+        }
+
+        it "should reserve nothing by default" {
+            expect!(Struts::new().apply(geom)).to(be_equal_to(geom));
+        }
+
+        it "should remove the reserved edges from a geometry" {
+            expect!(struts.apply(geom)).to(be_equal_to(Geometry { x: 15, y: 40, width: 785, height: 580 }));
+        }
+
+        it "should not underflow if the struts are larger than the geometry" {
+            let struts = Struts { top: 400, bottom: 400, left: 0, right: 900 };
+
+            expect!(struts.apply(geom)).to(be_equal_to(Geometry { x: 10, y: 420, width: 0, height: 0 }));
+        }
+
+        it "should combine struts by taking the largest strip for each side" {
+            let other = Struts { top: 10, bottom: 30, left: 5, right: 0 };
+
+            expect!(struts.max(&other)).to(be_equal_to(Struts { top: 20, bottom: 30, left: 5, right: 10 }));
+        }
+    }
+}
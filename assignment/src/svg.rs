@@ -0,0 +1,323 @@
+//! This module provides an SVG exporter for layouts and workspaces
+//!
+//! A layout is drawn as its screen with the windows on top of it in stacking order. Every window shows its number,
+//! the focused window has a red border and a focus marker in its top right corner. The exports of a window manager
+//! also show which windows float or are fullscreen, and list the gap and the minimised windows below the screen.
+//! The workspaces of a window manager are drawn side by side.
+//!
+//! The screen is drawn in grey, so the gaps between the windows are visible.
+//!
+
+use cplwm_api::types::{GapSize, Screen, Window, WindowLayout};
+use cplwm_api::wm::{FloatSupport, FullscreenSupport, GapSupport, MinimiseSupport, MultiWorkspaceSupport};
+
+/// The space between two screens
+const MARGIN: u32 = 20;
+
+/// The height of a line of text above or below a screen
+const LINE_HEIGHT: u32 = 20;
+
+/// The style of the exported SVG
+const STYLE: &'static str = ".title, .footer { font: 16px sans-serif; }
+.screen { fill: #d0d0d0; }
+.window rect { fill: #ffffff; stroke: #404040; stroke-width: 2; }
+.window text { font: 14px monospace; }
+.floating rect { fill: #e8f0ff; stroke-dasharray: 8 4; }
+.fullscreen rect { fill: #fff4e0; }
+.focused rect { stroke: #d02020; stroke-width: 4; }
+.focus { fill: #d02020; }";
+
+/// A screen with everything that is drawn on it, above it and below it
+struct Panel {
+    title: Option<String>,
+    screen: Screen,
+    layout: WindowLayout,
+    floating_windows: Vec<Window>,
+    minimised_windows: Vec<Window>,
+    fullscreen_window: Option<Window>,
+    gap: Option<GapSize>,
+}
+
+impl Panel {
+    /// A panel of a layout, without anything that's not in the layout
+    fn of_layout(layout: &WindowLayout, screen: Screen) -> Panel {
+        Panel {
+            title: None,
+            screen: screen,
+            layout: layout.clone(),
+            floating_windows: Vec::new(),
+            minimised_windows: Vec::new(),
+            fullscreen_window: None,
+            gap: None,
+        }
+    }
+
+    /// A panel of a window manager, with the floating, minimised and fullscreen windows and the gap
+    fn of_wm<WM>(wm: &WM, title: Option<String>) -> Panel
+        where WM: FloatSupport + MinimiseSupport + FullscreenSupport + GapSupport
+    {
+        Panel {
+            title: title,
+            screen: wm.get_screen(),
+            layout: wm.get_window_layout(),
+            floating_windows: wm.get_floating_windows(),
+            minimised_windows: wm.get_minimised_windows(),
+            fullscreen_window: wm.get_fullscreen_window(),
+            gap: Some(wm.get_gap()),
+        }
+    }
+
+    /// The lines below the screen
+    fn footer(&self) -> Vec<String> {
+        let mut footer = Vec::new();
+        if let Some(gap) = self.gap {
+            footer.push(format!("Gap: {}", gap));
+        }
+        if !self.minimised_windows.is_empty() {
+            let windows: Vec<String> = self.minimised_windows.iter().map(|window| window.to_string()).collect();
+            footer.push(format!("Minimised: {}", windows.join(", ")));
+        }
+
+        footer
+    }
+
+    /// The height of the title above the screen
+    fn title_height(&self) -> u32 {
+        if self.title.is_some() { LINE_HEIGHT } else { 0 }
+    }
+
+    /// The height of the panel
+    fn height(&self) -> u32 {
+        self.title_height() + self.screen.height + self.footer().len() as u32 * LINE_HEIGHT
+    }
+
+    /// The classes of the window, used by the style
+    fn classes(&self, window: Window) -> Vec<&'static str> {
+        let mut classes = vec!["window"];
+        if self.floating_windows.contains(&window) {
+            classes.push("floating");
+        }
+        if self.fullscreen_window == Some(window) {
+            classes.push("fullscreen");
+        }
+        if self.layout.focused_window == Some(window) {
+            classes.push("focused");
+        }
+
+        classes
+    }
+
+    /// Write the panel with its top left corner at (`x`, 0)
+    fn write(&self, svg: &mut String, x: u32) {
+        svg.push_str(&format!("<g transform=\"translate({}, 0)\">\n", x));
+        if let Some(ref title) = self.title {
+            svg.push_str(&format!("<text class=\"title\" y=\"{}\">{}</text>\n", LINE_HEIGHT - 5, title));
+        }
+
+        // A nested svg cuts off the parts of the windows outside the screen
+        let top = self.title_height();
+        svg.push_str(&format!("<svg y=\"{}\" width=\"{}\" height=\"{}\">\n",
+                              top,
+                              self.screen.width,
+                              self.screen.height));
+        svg.push_str(&format!("<rect class=\"screen\" width=\"{}\" height=\"{}\"/>\n",
+                              self.screen.width,
+                              self.screen.height));
+        for &(window, geometry) in &self.layout.windows {
+            // The positions of the text and the focus marker can be outside the range of an i32
+            let (x, y, width) = (geometry.x as i64, geometry.y as i64, geometry.width as i64);
+            let classes = self.classes(window);
+            svg.push_str(&format!("<g class=\"{}\">\n", classes.join(" ")));
+            svg.push_str(&format!("<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\"/>\n",
+                                  geometry.x,
+                                  geometry.y,
+                                  geometry.width,
+                                  geometry.height));
+            svg.push_str(&format!("<text x=\"{}\" y=\"{}\">{}</text>\n", x + 8, y + 20, window));
+
+            let annotations: Vec<&str> =
+                classes.into_iter().filter(|&class| class == "floating" || class == "fullscreen").collect();
+            if !annotations.is_empty() {
+                svg.push_str(&format!("<text x=\"{}\" y=\"{}\">{}</text>\n",
+                                      x + 8,
+                                      y + 40,
+                                      annotations.join(", ")));
+            }
+            if self.layout.focused_window == Some(window) {
+                svg.push_str(&format!("<circle class=\"focus\" cx=\"{}\" cy=\"{}\" r=\"6\"/>\n",
+                                      x + width - 14,
+                                      y + 14));
+            }
+            svg.push_str("</g>\n");
+        }
+        svg.push_str("</svg>\n");
+
+        for (i, line) in self.footer().iter().enumerate() {
+            svg.push_str(&format!("<text class=\"footer\" y=\"{}\">{}</text>\n",
+                                  top + self.screen.height + (i as u32 + 1) * LINE_HEIGHT - 5,
+                                  line));
+        }
+        svg.push_str("</g>\n");
+    }
+}
+
+/// Draw the panels side by side
+fn to_svg(panels: &[Panel]) -> String {
+    let width = panels.iter().map(|panel| panel.screen.width).sum::<u32>() +
+                panels.len().saturating_sub(1) as u32 * MARGIN;
+    let height = panels.iter().map(|panel| panel.height()).max().unwrap_or(0);
+
+    let mut svg = format!("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} \
+                           {1}\">\n",
+                          width,
+                          height);
+    svg.push_str(&format!("<style>\n{}\n</style>\n", STYLE));
+
+    let mut x = 0;
+    for panel in panels {
+        panel.write(&mut svg, x);
+        x += panel.screen.width + MARGIN;
+    }
+    svg.push_str("</svg>\n");
+
+    svg
+}
+
+/// Export the layout of a screen
+pub fn layout_to_svg(layout: &WindowLayout, screen: Screen) -> String {
+    to_svg(&[Panel::of_layout(layout, screen)])
+}
+
+/// Export the layout of the window manager, with its floating, minimised and fullscreen windows and its gap
+pub fn wm_to_svg<WM>(wm: &WM) -> String
+    where WM: FloatSupport + MinimiseSupport + FullscreenSupport + GapSupport
+{
+    to_svg(&[Panel::of_wm(wm, None)])
+}
+
+/// Export all workspaces of the window manager side by side (see `wm_to_svg`)
+pub fn workspaces_to_svg<W, WM>(wm: &WM) -> String
+    where W: FloatSupport + MinimiseSupport + FullscreenSupport + GapSupport,
+          WM: MultiWorkspaceSupport<W>
+{
+    let current = wm.get_current_workspace_index();
+    let mut panels = vec![];
+    let mut index = 0;
+    while let Ok(workspace) = wm.get_workspace(index) {
+        let title = if index == current {
+            format!("Workspace {} (current)", index)
+        } else {
+            format!("Workspace {}", index)
+        };
+        panels.push(Panel::of_wm(workspace, Some(title)));
+        index += 1;
+    }
+
+    to_svg(&panels)
+}
+
+#[cfg(test)]
+#[allow(unused_mut)]
+#[allow(unused_variables)]
+mod tests {
+    pub use super::*;
+
+    pub use cplwm_api::wm::*;
+    pub use cplwm_api::types::*;
+    pub use g_multiple_workspaces::WMName;
+
+    // Import expectest names
+    pub use expectest::prelude::*;
+
+    describe! svg {
+        before_each {
+            let screen: Screen = Screen {
+                width: 800,
+                height: 600,
+            };
+
+            let some_geom: Geometry = Geometry {
+                x: 10,
+                y: 10,
+                width: 100,
+                height: 100,
+            };
+
+            let mut wm = WMName::new(screen);
+            wm.set_gap(10);
+            wm.add_window(WindowWithInfo::new_tiled(1, some_geom)).unwrap();
+            wm.add_window(WindowWithInfo::new_tiled(2, some_geom)).unwrap();
+            wm.add_window(WindowWithInfo::new_float(3, some_geom)).unwrap();
+            wm.add_window(WindowWithInfo::new_tiled(4, some_geom)).unwrap();
+            wm.toggle_minimised(4).unwrap();
+            wm.focus_window(Some(2)).unwrap();
+        }
+
+        it "should draw the screen and every window of a layout in stacking order" {
+            let svg = layout_to_svg(&wm.get_window_layout(), screen);
+
+            expect!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"800\" height=\"600\""))
+                .to(be_true());
+            expect!(svg.contains("<rect class=\"screen\" width=\"800\" height=\"600\"/>")).to(be_true());
+            expect!(svg.matches("<g class=\"window").count()).to(be_equal_to(3));
+
+            let first = svg.find("<g class=\"window\">\n<rect x=\"10\" y=\"10\" width=\"380\" height=\"580\"/>");
+            let second = svg.find("<g class=\"window focused\">\n<rect x=\"410\" y=\"10\" width=\"380\" height=\"580\"/>");
+            let third = svg.find("<g class=\"window\">\n<rect x=\"10\" y=\"10\" width=\"100\" height=\"100\"/>");
+            expect!(first.is_some() && first < second && second < third).to(be_true());
+        }
+
+        it "should mark the focused window" {
+            let svg = layout_to_svg(&wm.get_window_layout(), screen);
+
+            expect!(svg.matches("<g class=\"window focused\">").count()).to(be_equal_to(1));
+            expect!(svg.contains("<circle class=\"focus\" cx=\"776\" cy=\"24\" r=\"6\"/>")).to(be_true());
+        }
+
+        it "should place the text and focus marker of a huge window without overflowing" {
+            let layout = WindowLayout {
+                focused_window: Some(1),
+                windows: vec![(1, Geometry { x: i32::max_value(), y: i32::max_value(), width: u32::max_value(), height: 100 })],
+            };
+
+            let svg = layout_to_svg(&layout, screen);
+            expect!(svg.contains("<text x=\"2147483655\" y=\"2147483667\">1</text>")).to(be_true());
+            expect!(svg.contains("<circle class=\"focus\" cx=\"6442450928\" cy=\"2147483661\" r=\"6\"/>")).to(be_true());
+        }
+
+        it "should annotate the floating, minimised and fullscreen windows and the gap of a window manager" {
+            let svg = wm_to_svg(&wm);
+            expect!(svg.contains("<g class=\"window floating\">")).to(be_true());
+            expect!(svg.contains(">floating</text>")).to(be_true());
+            expect!(svg.contains("<text class=\"footer\" y=\"615\">Gap: 10</text>")).to(be_true());
+            expect!(svg.contains("<text class=\"footer\" y=\"635\">Minimised: 4</text>")).to(be_true());
+            expect!(svg.contains("height=\"640\"")).to(be_true());
+
+            wm.toggle_fullscreen(3).unwrap();
+            let svg = wm_to_svg(&wm);
+            expect!(svg.contains("<g class=\"window fullscreen focused\">")).to(be_true());
+            expect!(svg.contains(">fullscreen</text>")).to(be_true());
+        }
+
+        it "should draw all workspaces side by side" {
+            wm.switch_workspace(2).unwrap();
+            wm.add_window(WindowWithInfo::new_tiled(5, some_geom)).unwrap();
+
+            let svg = workspaces_to_svg(&wm);
+            expect!(svg.contains("width=\"3260\" height=\"660\"")).to(be_true());
+            for x in &[0, 820, 1640, 2460] {
+                expect!(svg.contains(&format!("<g transform=\"translate({}, 0)\">", x))).to(be_true());
+            }
+            expect!(svg.contains(">Workspace 0</text>")).to(be_true());
+            expect!(svg.contains(">Workspace 2 (current)</text>")).to(be_true());
+            expect!(svg.matches("<g class=\"window").count()).to(be_equal_to(4));
+        }
+
+        it "should draw the workspaces that were added" {
+            let index = wm.add_workspace();
+
+            let svg = workspaces_to_svg(&wm);
+            expect!(svg.contains(&format!(">Workspace {}</text>", index))).to(be_true());
+        }
+    }
+}
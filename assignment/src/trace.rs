@@ -0,0 +1,1100 @@
+//! This module provides recording and replaying of the calls made on a window manager
+//!
+//! The `RecordingWM` wraps a window manager and records every call of its traits in a trace: a line of JSON per call
+//! with the arguments, the result and the `WindowLayout` after the call. The first line records the creation of the
+//! window manager, so `replay` can execute the trace against a fresh window manager. The replay stops at the first
+//! call after which the layout differs from the recorded one.
+//!
+//! A trace attached to a bug report can be reproduced in a unit test:
+//!
+//! ```ignore
+//! let trace = load_trace("bad-layout.trace").unwrap();
+//! let wm: WMName = replay(&trace).unwrap();
+//! ```
+//!
+//! The calls made on the workspace returned by `get_workspace_mut` can't be recorded: the trait returns the wrapped
+//! window manager itself. The trace only has a `GetWorkspaceMut` entry, which the replay skips, so the changes made
+//! through the workspace are lost. The replay diverges at the next entry if they changed the layout, otherwise (e.g.
+//! after a change of another workspace, or at the end of the trace) it succeeds without them.
+//!
+
+use std::cell::RefCell;
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::os::raw::c_uint;
+use std::path::Path;
+
+use rustc_serialize::{Decodable, Decoder, Encodable, Encoder};
+use rustc_serialize::json;
+#[cfg(feature = "with-serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use cplwm_api::types::{GapSize, Geometry, PrevOrNext, Screen, Window, WindowLayout, WindowWithInfo, WorkspaceIndex};
+use cplwm_api::wm::{FloatSupport, FullscreenSupport, GapSupport, MinimiseSupport, MultiWorkspaceSupport, TilingSupport, WindowManager};
+
+use command::CommandSupport;
+use direction::{Direction, DirectionSupport};
+use error::TraceError;
+use events::{Snapshot, SnapshotSupport, WMName as EventWM};
+use f_gaps::{ExtendedGapSupport, OuterGaps};
+use g_multiple_workspaces::MoveWindowSupport;
+use layouts::{Layout, LayoutSupport};
+use struts::{StrutSupport, Struts};
+use window_description::{DescriptionSupport, WindowDescription};
+
+/// Type alias for automated tests
+pub type WMName = RecordingWM<EventWM>;
+
+/// A call of a trait method with its arguments
+#[derive(RustcDecodable, RustcEncodable, Debug, Clone, PartialEq)]
+pub enum Call {
+    /// `WindowManager::new`
+    New(Screen),
+    /// `WindowManager::get_windows`
+    GetWindows,
+    /// `WindowManager::add_window`
+    AddWindow(WindowWithInfo),
+    /// `WindowManager::remove_window`
+    RemoveWindow(Window),
+    /// `WindowManager::get_window_layout`
+    GetWindowLayout,
+    /// `WindowManager::focus_window`
+    FocusWindow(Option<Window>),
+    /// `WindowManager::cycle_focus`
+    CycleFocus(PrevOrNext),
+    /// `WindowManager::get_window_info`
+    GetWindowInfo(Window),
+    /// `WindowManager::get_screen`
+    GetScreen,
+    /// `WindowManager::resize_screen`
+    ResizeScreen(Screen),
+    /// `WindowManager::get_focused_window`
+    GetFocusedWindow,
+    /// `WindowManager::is_managed`
+    IsManaged(Window),
+    /// `TilingSupport::get_master_window`
+    GetMasterWindow,
+    /// `TilingSupport::swap_with_master`
+    SwapWithMaster(Window),
+    /// `TilingSupport::swap_windows`
+    SwapWindows(PrevOrNext),
+    /// `FloatSupport::get_floating_windows`
+    GetFloatingWindows,
+    /// `FloatSupport::toggle_floating`
+    ToggleFloating(Window),
+    /// `FloatSupport::set_window_geometry`
+    SetWindowGeometry(Window, Geometry),
+    /// `MinimiseSupport::get_minimised_windows`
+    GetMinimisedWindows,
+    /// `MinimiseSupport::toggle_minimised`
+    ToggleMinimised(Window),
+    /// `FullscreenSupport::get_fullscreen_window`
+    GetFullscreenWindow,
+    /// `FullscreenSupport::toggle_fullscreen`
+    ToggleFullscreen(Window),
+    /// `GapSupport::get_gap`
+    GetGap,
+    /// `GapSupport::set_gap`
+    SetGap(GapSize),
+    /// `ExtendedGapSupport::get_inner_gap`
+    GetInnerGap,
+    /// `ExtendedGapSupport::set_inner_gap`
+    SetInnerGap(GapSize),
+    /// `ExtendedGapSupport::get_outer_gaps`
+    GetOuterGaps,
+    /// `ExtendedGapSupport::set_outer_gaps`
+    SetOuterGaps(OuterGaps),
+    /// `ExtendedGapSupport::get_smart_gaps`
+    GetSmartGaps,
+    /// `ExtendedGapSupport::set_smart_gaps`
+    SetSmartGaps(bool),
+    /// `ExtendedGapSupport::get_min_window_size`
+    GetMinWindowSize,
+    /// `ExtendedGapSupport::set_min_window_size`
+    SetMinWindowSize(c_uint),
+    /// `ExtendedGapSupport::try_set_gap`
+    TrySetGap(GapSize),
+    /// `LayoutSupport::get_layout`
+    GetLayout,
+    /// `LayoutSupport::set_layout`
+    SetLayout(Layout),
+    /// `StrutSupport::get_struts`
+    GetStruts,
+    /// `StrutSupport::set_struts`
+    SetStruts(Struts),
+    /// `StrutSupport::add_dock`
+    AddDock(Window, Struts),
+    /// `StrutSupport::remove_dock`
+    RemoveDock(Window),
+    /// `StrutSupport::get_docks`
+    GetDocks,
+    /// `DescriptionSupport::add_window_with_description`
+    AddWindowWithDescription(WindowDescription),
+    /// `DirectionSupport::focus_direction`
+    FocusDirection(Direction),
+    /// `DirectionSupport::swap_direction`
+    SwapDirection(Direction),
+    /// `MoveWindowSupport::move_window_to_workspace`
+    MoveWindowToWorkspace(Window, WorkspaceIndex),
+    /// `MoveWindowSupport::add_window_to_workspace`
+    AddWindowToWorkspace(WindowDescription, WorkspaceIndex, bool),
+    /// `MultiWorkspaceSupport::get_current_workspace_index`
+    GetCurrentWorkspaceIndex,
+    /// `MultiWorkspaceSupport::get_workspace`
+    GetWorkspace(WorkspaceIndex),
+    /// `MultiWorkspaceSupport::get_workspace_mut`
+    GetWorkspaceMut(WorkspaceIndex),
+    /// `MultiWorkspaceSupport::switch_workspace`
+    SwitchWorkspace(WorkspaceIndex),
+}
+
+/// The result of a call
+#[derive(RustcDecodable, RustcEncodable, Debug, Clone, PartialEq)]
+pub enum Outcome {
+    /// The call returned nothing, `Ok(())` or a workspace
+    Done,
+    /// The call returned an error with this message
+    Failed(String),
+    /// The call returned windows
+    Windows(Vec<Window>),
+    /// The call returned a window or no window
+    Window(Option<Window>),
+    /// The call returned a layout
+    Layout(WindowLayout),
+    /// The call returned the information of a window
+    Info(WindowWithInfo),
+    /// The call returned a screen
+    Screen(Screen),
+    /// The call returned a yes or no
+    Bool(bool),
+    /// The call returned a gap or a minimum window size
+    Gap(GapSize),
+    /// The call returned the outer gaps
+    OuterGaps(OuterGaps),
+    /// The call returned the layout of the tiled windows
+    TilingLayout(Layout),
+    /// The call returned struts
+    Struts(Struts),
+    /// The call returned a workspace index
+    Workspace(WorkspaceIndex),
+}
+
+/// A result of a call that can be recorded
+trait ToOutcome {
+    /// Return the outcome that is recorded for this result
+    fn to_outcome(&self) -> Outcome;
+}
+
+impl ToOutcome for () {
+    fn to_outcome(&self) -> Outcome {
+        Outcome::Done
+    }
+}
+
+impl ToOutcome for Vec<Window> {
+    fn to_outcome(&self) -> Outcome {
+        Outcome::Windows(self.clone())
+    }
+}
+
+impl ToOutcome for Option<Window> {
+    fn to_outcome(&self) -> Outcome {
+        Outcome::Window(*self)
+    }
+}
+
+impl ToOutcome for WindowLayout {
+    fn to_outcome(&self) -> Outcome {
+        Outcome::Layout(self.clone())
+    }
+}
+
+impl ToOutcome for WindowWithInfo {
+    fn to_outcome(&self) -> Outcome {
+        Outcome::Info(*self)
+    }
+}
+
+impl ToOutcome for Screen {
+    fn to_outcome(&self) -> Outcome {
+        Outcome::Screen(*self)
+    }
+}
+
+impl ToOutcome for bool {
+    fn to_outcome(&self) -> Outcome {
+        Outcome::Bool(*self)
+    }
+}
+
+impl ToOutcome for GapSize {
+    fn to_outcome(&self) -> Outcome {
+        Outcome::Gap(*self)
+    }
+}
+
+impl ToOutcome for OuterGaps {
+    fn to_outcome(&self) -> Outcome {
+        Outcome::OuterGaps(*self)
+    }
+}
+
+impl ToOutcome for Layout {
+    fn to_outcome(&self) -> Outcome {
+        Outcome::TilingLayout(*self)
+    }
+}
+
+impl ToOutcome for Struts {
+    fn to_outcome(&self) -> Outcome {
+        Outcome::Struts(*self)
+    }
+}
+
+impl ToOutcome for WorkspaceIndex {
+    fn to_outcome(&self) -> Outcome {
+        Outcome::Workspace(*self)
+    }
+}
+
+/// A workspace is only recorded as `Done`, its own calls aren't recorded
+impl<'a, WM: WindowManager> ToOutcome for &'a WM {
+    fn to_outcome(&self) -> Outcome {
+        Outcome::Done
+    }
+}
+
+impl<T: ToOutcome, E: Error> ToOutcome for Result<T, E> {
+    fn to_outcome(&self) -> Outcome {
+        match *self {
+            Ok(ref value) => value.to_outcome(),
+            Err(ref error) => Outcome::Failed(error.to_string()),
+        }
+    }
+}
+
+/// A line of a trace
+#[derive(RustcDecodable, RustcEncodable, Debug, Clone, PartialEq)]
+pub struct TraceEntry {
+    /// The call
+    pub call: Call,
+    /// The result of the call
+    pub outcome: Outcome,
+    /// The layout of the window manager after the call
+    pub layout: WindowLayout,
+}
+
+/// The first entry of a trace after which the replayed layout differs from the recorded one
+#[derive(Debug, Clone, PartialEq)]
+pub struct Divergence {
+    /// The index of the entry in the trace
+    pub step: usize,
+    /// The call of the entry
+    pub call: Call,
+    /// The recorded layout
+    pub expected: WindowLayout,
+    /// The layout of the replayed window manager
+    pub actual: WindowLayout,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f,
+               "Diverged at step {} ({:?}): expected {:?}, got {:?}",
+               self.step,
+               self.call,
+               self.expected,
+               self.actual)
+    }
+}
+
+/// The writer a `RecordingWM` writes its trace to.
+///
+/// The writer is part of the running process: it's not encoded, a decoded or cloned window manager doesn't record.
+#[derive(Default)]
+pub struct Recorder {
+    writer: RefCell<Option<Box<Write + Send>>>,
+}
+
+impl Recorder {
+    /// Write the entry as a line of JSON and stop recording when writing fails
+    fn write(&self, entry: &TraceEntry) {
+        let mut writer = self.writer.borrow_mut();
+        let written = match *writer {
+            Some(ref mut writer) => {
+                json::encode(entry)
+                    .ok()
+                    .map_or(false, |line| writeln!(writer, "{}", line).and_then(|_| writer.flush()).is_ok())
+            }
+            None => return,
+        };
+
+        if !written {
+            *writer = None;
+        }
+    }
+}
+
+impl fmt::Debug for Recorder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Recorder({})", self.writer.borrow().is_some())
+    }
+}
+
+impl Clone for Recorder {
+    fn clone(&self) -> Recorder {
+        Recorder::default()
+    }
+}
+
+impl Encodable for Recorder {
+    fn encode<S: Encoder>(&self, s: &mut S) -> Result<(), S::Error> {
+        s.emit_nil()
+    }
+}
+
+impl Decodable for Recorder {
+    fn decode<D: Decoder>(d: &mut D) -> Result<Recorder, D::Error> {
+        try!(d.read_nil());
+        Ok(Recorder::default())
+    }
+}
+
+#[cfg(feature = "with-serde")]
+impl Serialize for Recorder {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_unit()
+    }
+}
+
+#[cfg(feature = "with-serde")]
+impl<'de> Deserialize<'de> for Recorder {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Recorder, D::Error> {
+        try!(<()>::deserialize(d));
+        Ok(Recorder::default())
+    }
+}
+
+/// Main struct of the window manager
+/// This WM records every call made on the WrappedWM
+#[derive(RustcDecodable, RustcEncodable, Debug, Clone)]
+#[cfg_attr(feature = "with-serde", derive(Serialize, Deserialize))]
+pub struct RecordingWM<WrappedWM: WindowManager> {
+    /// The wrapped window manager
+    pub wrapped_wm: WrappedWM,
+    /// The writer of the trace
+    pub recorder: Recorder,
+}
+
+impl<WrappedWM: WindowManager> RecordingWM<WrappedWM> {
+    /// Create a window manager that writes its trace to the given writer, starting with its creation.
+    ///
+    /// A window manager created with `WindowManager::new` doesn't record.
+    pub fn with_writer(screen: Screen, writer: Box<Write + Send>) -> RecordingWM<WrappedWM> {
+        let wm = RecordingWM {
+            wrapped_wm: WrappedWM::new(screen),
+            recorder: Recorder { writer: RefCell::new(Some(writer)) },
+        };
+        wm.record(Call::New(screen), ());
+
+        wm
+    }
+
+    /// Create a window manager that writes its trace to a new file at the given path
+    pub fn create<P: AsRef<Path>>(screen: Screen, path: P) -> Result<RecordingWM<WrappedWM>, TraceError> {
+        let file = try!(File::create(path));
+        Ok(RecordingWM::with_writer(screen, Box::new(BufWriter::new(file))))
+    }
+
+    /// Return true if the calls are recorded, i.e. there's a writer and writing to it never failed
+    pub fn is_recording(&self) -> bool {
+        self.recorder.writer.borrow().is_some()
+    }
+
+    /// Stop recording and return the writer
+    pub fn stop_recording(&mut self) -> Option<Box<Write + Send>> {
+        self.recorder.writer.get_mut().take()
+    }
+
+    /// Record the call with its result and the layout after the call, return the result
+    fn record<T: ToOutcome>(&self, call: Call, result: T) -> T {
+        if self.is_recording() {
+            self.recorder.write(&TraceEntry {
+                call: call,
+                outcome: result.to_outcome(),
+                layout: self.wrapped_wm.get_window_layout(),
+            });
+        }
+
+        result
+    }
+}
+
+impl<WrappedWM: WindowManager> WindowManager for RecordingWM<WrappedWM> {
+    /// We use the Error from the WrappedWM as our Error type.
+    type Error = WrappedWM::Error;
+
+    fn new(screen: Screen) -> RecordingWM<WrappedWM> {
+        RecordingWM {
+            wrapped_wm: WrappedWM::new(screen),
+            recorder: Recorder::default(),
+        }
+    }
+
+    fn get_windows(&self) -> Vec<Window> {
+        self.record(Call::GetWindows, self.wrapped_wm.get_windows())
+    }
+
+    fn add_window(&mut self, window_with_info: WindowWithInfo) -> Result<(), Self::Error> {
+        let result = self.wrapped_wm.add_window(window_with_info);
+        self.record(Call::AddWindow(window_with_info), result)
+    }
+
+    fn remove_window(&mut self, window: Window) -> Result<(), Self::Error> {
+        let result = self.wrapped_wm.remove_window(window);
+        self.record(Call::RemoveWindow(window), result)
+    }
+
+    fn get_window_layout(&self) -> WindowLayout {
+        self.record(Call::GetWindowLayout, self.wrapped_wm.get_window_layout())
+    }
+
+    fn focus_window(&mut self, window: Option<Window>) -> Result<(), Self::Error> {
+        let result = self.wrapped_wm.focus_window(window);
+        self.record(Call::FocusWindow(window), result)
+    }
+
+    fn cycle_focus(&mut self, dir: PrevOrNext) {
+        self.wrapped_wm.cycle_focus(dir);
+        self.record(Call::CycleFocus(dir), ())
+    }
+
+    fn get_window_info(&self, window: Window) -> Result<WindowWithInfo, Self::Error> {
+        self.record(Call::GetWindowInfo(window), self.wrapped_wm.get_window_info(window))
+    }
+
+    fn get_screen(&self) -> Screen {
+        self.record(Call::GetScreen, self.wrapped_wm.get_screen())
+    }
+
+    fn resize_screen(&mut self, screen: Screen) {
+        self.wrapped_wm.resize_screen(screen);
+        self.record(Call::ResizeScreen(screen), ())
+    }
+
+    fn get_focused_window(&self) -> Option<Window> {
+        self.record(Call::GetFocusedWindow, self.wrapped_wm.get_focused_window())
+    }
+
+    fn is_managed(&self, window: Window) -> bool {
+        self.record(Call::IsManaged(window), self.wrapped_wm.is_managed(window))
+    }
+}
+
+impl<WrappedWM: TilingSupport> TilingSupport for RecordingWM<WrappedWM> {
+    fn get_master_window(&self) -> Option<Window> {
+        self.record(Call::GetMasterWindow, self.wrapped_wm.get_master_window())
+    }
+
+    fn swap_with_master(&mut self, window: Window) -> Result<(), Self::Error> {
+        let result = self.wrapped_wm.swap_with_master(window);
+        self.record(Call::SwapWithMaster(window), result)
+    }
+
+    fn swap_windows(&mut self, dir: PrevOrNext) {
+        self.wrapped_wm.swap_windows(dir);
+        self.record(Call::SwapWindows(dir), ())
+    }
+}
+
+impl<WrappedWM: FloatSupport> FloatSupport for RecordingWM<WrappedWM> {
+    fn get_floating_windows(&self) -> Vec<Window> {
+        self.record(Call::GetFloatingWindows, self.wrapped_wm.get_floating_windows())
+    }
+
+    fn toggle_floating(&mut self, window: Window) -> Result<(), Self::Error> {
+        let result = self.wrapped_wm.toggle_floating(window);
+        self.record(Call::ToggleFloating(window), result)
+    }
+
+    fn set_window_geometry(&mut self, window: Window, new_geometry: Geometry) -> Result<(), Self::Error> {
+        let result = self.wrapped_wm.set_window_geometry(window, new_geometry);
+        self.record(Call::SetWindowGeometry(window, new_geometry), result)
+    }
+}
+
+impl<WrappedWM: MinimiseSupport> MinimiseSupport for RecordingWM<WrappedWM> {
+    fn get_minimised_windows(&self) -> Vec<Window> {
+        self.record(Call::GetMinimisedWindows, self.wrapped_wm.get_minimised_windows())
+    }
+
+    fn toggle_minimised(&mut self, window: Window) -> Result<(), Self::Error> {
+        let result = self.wrapped_wm.toggle_minimised(window);
+        self.record(Call::ToggleMinimised(window), result)
+    }
+}
+
+impl<WrappedWM: FullscreenSupport> FullscreenSupport for RecordingWM<WrappedWM> {
+    fn get_fullscreen_window(&self) -> Option<Window> {
+        self.record(Call::GetFullscreenWindow, self.wrapped_wm.get_fullscreen_window())
+    }
+
+    fn toggle_fullscreen(&mut self, window: Window) -> Result<(), Self::Error> {
+        let result = self.wrapped_wm.toggle_fullscreen(window);
+        self.record(Call::ToggleFullscreen(window), result)
+    }
+}
+
+impl<WrappedWM: GapSupport> GapSupport for RecordingWM<WrappedWM> {
+    fn get_gap(&self) -> GapSize {
+        self.record(Call::GetGap, self.wrapped_wm.get_gap())
+    }
+
+    fn set_gap(&mut self, gapsize: GapSize) {
+        self.wrapped_wm.set_gap(gapsize);
+        self.record(Call::SetGap(gapsize), ())
+    }
+}
+
+impl<WrappedWM: ExtendedGapSupport> ExtendedGapSupport for RecordingWM<WrappedWM> {
+    fn get_inner_gap(&self) -> GapSize {
+        self.record(Call::GetInnerGap, self.wrapped_wm.get_inner_gap())
+    }
+
+    fn set_inner_gap(&mut self, gap: GapSize) {
+        self.wrapped_wm.set_inner_gap(gap);
+        self.record(Call::SetInnerGap(gap), ())
+    }
+
+    fn get_outer_gaps(&self) -> OuterGaps {
+        self.record(Call::GetOuterGaps, self.wrapped_wm.get_outer_gaps())
+    }
+
+    fn set_outer_gaps(&mut self, gaps: OuterGaps) {
+        self.wrapped_wm.set_outer_gaps(gaps);
+        self.record(Call::SetOuterGaps(gaps), ())
+    }
+
+    fn get_smart_gaps(&self) -> bool {
+        self.record(Call::GetSmartGaps, self.wrapped_wm.get_smart_gaps())
+    }
+
+    fn set_smart_gaps(&mut self, smart_gaps: bool) {
+        self.wrapped_wm.set_smart_gaps(smart_gaps);
+        self.record(Call::SetSmartGaps(smart_gaps), ())
+    }
+
+    fn get_min_window_size(&self) -> c_uint {
+        self.record(Call::GetMinWindowSize, self.wrapped_wm.get_min_window_size())
+    }
+
+    fn set_min_window_size(&mut self, size: c_uint) {
+        self.wrapped_wm.set_min_window_size(size);
+        self.record(Call::SetMinWindowSize(size), ())
+    }
+
+    fn try_set_gap(&mut self, gapsize: GapSize) -> Result<(), Self::Error> {
+        let result = self.wrapped_wm.try_set_gap(gapsize);
+        self.record(Call::TrySetGap(gapsize), result)
+    }
+}
+
+impl<WrappedWM: LayoutSupport> LayoutSupport for RecordingWM<WrappedWM> {
+    fn get_layout(&self) -> Layout {
+        self.record(Call::GetLayout, self.wrapped_wm.get_layout())
+    }
+
+    fn set_layout(&mut self, layout: Layout) {
+        self.wrapped_wm.set_layout(layout);
+        self.record(Call::SetLayout(layout), ())
+    }
+}
+
+impl<WrappedWM: StrutSupport> StrutSupport for RecordingWM<WrappedWM> {
+    fn get_struts(&self) -> Struts {
+        self.record(Call::GetStruts, self.wrapped_wm.get_struts())
+    }
+
+    fn set_struts(&mut self, struts: Struts) {
+        self.wrapped_wm.set_struts(struts);
+        self.record(Call::SetStruts(struts), ())
+    }
+
+    fn add_dock(&mut self, window: Window, struts: Struts) {
+        self.wrapped_wm.add_dock(window, struts);
+        self.record(Call::AddDock(window, struts), ())
+    }
+
+    fn remove_dock(&mut self, window: Window) -> Result<(), Self::Error> {
+        let result = self.wrapped_wm.remove_dock(window);
+        self.record(Call::RemoveDock(window), result)
+    }
+
+    fn get_docks(&self) -> Vec<Window> {
+        self.record(Call::GetDocks, self.wrapped_wm.get_docks())
+    }
+
+    /// Not recorded: a trace can't contain the other window manager
+    fn copy_struts(&mut self, other: &RecordingWM<WrappedWM>) {
+        self.wrapped_wm.copy_struts(&other.wrapped_wm)
+    }
+}
+
+impl<WrappedWM: DescriptionSupport> DescriptionSupport for RecordingWM<WrappedWM> {
+    fn add_window_with_description(&mut self, description: WindowDescription) -> Result<(), Self::Error> {
+        let result = self.wrapped_wm.add_window_with_description(description.clone());
+        self.record(Call::AddWindowWithDescription(description), result)
+    }
+}
+
+impl<WrappedWM: DirectionSupport> DirectionSupport for RecordingWM<WrappedWM> {
+    fn focus_direction(&mut self, dir: Direction) {
+        self.wrapped_wm.focus_direction(dir);
+        self.record(Call::FocusDirection(dir), ())
+    }
+
+    fn swap_direction(&mut self, dir: Direction) {
+        self.wrapped_wm.swap_direction(dir);
+        self.record(Call::SwapDirection(dir), ())
+    }
+}
+
+impl<WrappedWM: MoveWindowSupport> MoveWindowSupport for RecordingWM<WrappedWM> {
+    fn move_window_to_workspace(&mut self, window: Window, index: WorkspaceIndex) -> Result<(), Self::Error> {
+        let result = self.wrapped_wm.move_window_to_workspace(window, index);
+        self.record(Call::MoveWindowToWorkspace(window, index), result)
+    }
+
+    fn add_window_to_workspace(&mut self, description: WindowDescription, index: WorkspaceIndex, minimised: bool) -> Result<(), Self::Error> {
+        let result = self.wrapped_wm.add_window_to_workspace(description.clone(), index, minimised);
+        self.record(Call::AddWindowToWorkspace(description, index, minimised), result)
+    }
+}
+
+impl<WM, WrappedWM> MultiWorkspaceSupport<WM> for RecordingWM<WrappedWM>
+    where WM: WindowManager,
+          WrappedWM: MultiWorkspaceSupport<WM>
+{
+    fn get_current_workspace_index(&self) -> WorkspaceIndex {
+        self.record(Call::GetCurrentWorkspaceIndex,
+                    self.wrapped_wm.get_current_workspace_index())
+    }
+
+    fn get_workspace(&self, index: WorkspaceIndex) -> Result<&WM, Self::Error> {
+        self.record(Call::GetWorkspace(index), self.wrapped_wm.get_workspace(index))
+    }
+
+    /// Changes made through the returned window manager aren't recorded and are lost in a replay (see the module docs)
+    fn get_workspace_mut(&mut self, index: WorkspaceIndex) -> Result<&mut WM, Self::Error> {
+        let _ = self.record(Call::GetWorkspaceMut(index), self.wrapped_wm.get_workspace(index));
+        self.wrapped_wm.get_workspace_mut(index)
+    }
+
+    fn switch_workspace(&mut self, index: WorkspaceIndex) -> Result<(), Self::Error> {
+        let result = self.wrapped_wm.switch_workspace(index);
+        self.record(Call::SwitchWorkspace(index), result)
+    }
+}
+
+/// Snapshots aren't calls of the window manager, they're not recorded
+impl<WrappedWM: SnapshotSupport> SnapshotSupport for RecordingWM<WrappedWM> {
+    fn get_snapshot(&self) -> Snapshot {
+        self.wrapped_wm.get_snapshot()
+    }
+}
+
+/// Read a trace, one entry per line. Empty lines are skipped.
+pub fn read_trace<R: BufRead>(reader: R) -> Result<Vec<TraceEntry>, TraceError> {
+    let mut trace = Vec::new();
+    for line in reader.lines() {
+        let line = try!(line);
+        if !line.trim().is_empty() {
+            trace.push(try!(json::decode(&line)));
+        }
+    }
+
+    Ok(trace)
+}
+
+/// Read the trace file at the given path (see `read_trace`)
+pub fn load_trace<P: AsRef<Path>>(path: P) -> Result<Vec<TraceEntry>, TraceError> {
+    read_trace(BufReader::new(try!(File::open(path))))
+}
+
+/// Execute the call on the window manager.
+///
+/// Errors of the window manager are ignored: the call may have failed when it was recorded as well, a different
+/// outcome shows up as a different layout.
+fn execute<W, WM>(wm: &mut WM, call: &Call) -> Result<(), TraceError>
+    where W: WindowManager,
+          WM: CommandSupport<W> + StrutSupport + DescriptionSupport
+{
+    match *call {
+        Call::New(_) => {
+            return Err(TraceError::InvalidTrace(String::from("The window manager can only be created at the start of \
+                                                              the trace")))
+        }
+        Call::AddWindow(window_with_info) => {
+            let _ = wm.add_window(window_with_info);
+        }
+        Call::RemoveWindow(window) => {
+            let _ = wm.remove_window(window);
+        }
+        Call::FocusWindow(window) => {
+            let _ = wm.focus_window(window);
+        }
+        Call::CycleFocus(dir) => wm.cycle_focus(dir),
+        Call::ResizeScreen(screen) => wm.resize_screen(screen),
+        Call::SwapWithMaster(window) => {
+            let _ = wm.swap_with_master(window);
+        }
+        Call::SwapWindows(dir) => wm.swap_windows(dir),
+        Call::ToggleFloating(window) => {
+            let _ = wm.toggle_floating(window);
+        }
+        Call::SetWindowGeometry(window, geometry) => {
+            let _ = wm.set_window_geometry(window, geometry);
+        }
+        Call::ToggleMinimised(window) => {
+            let _ = wm.toggle_minimised(window);
+        }
+        Call::ToggleFullscreen(window) => {
+            let _ = wm.toggle_fullscreen(window);
+        }
+        Call::SetGap(gap) => wm.set_gap(gap),
+        Call::SetInnerGap(gap) => wm.set_inner_gap(gap),
+        Call::SetOuterGaps(gaps) => wm.set_outer_gaps(gaps),
+        Call::SetSmartGaps(smart_gaps) => wm.set_smart_gaps(smart_gaps),
+        Call::SetMinWindowSize(size) => wm.set_min_window_size(size),
+        Call::TrySetGap(gap) => {
+            let _ = wm.try_set_gap(gap);
+        }
+        Call::SetLayout(layout) => wm.set_layout(layout),
+        Call::SetStruts(struts) => wm.set_struts(struts),
+        Call::AddDock(window, struts) => wm.add_dock(window, struts),
+        Call::RemoveDock(window) => {
+            let _ = wm.remove_dock(window);
+        }
+        Call::AddWindowWithDescription(ref description) => {
+            let _ = wm.add_window_with_description(description.clone());
+        }
+        Call::FocusDirection(dir) => wm.focus_direction(dir),
+        Call::SwapDirection(dir) => wm.swap_direction(dir),
+        Call::MoveWindowToWorkspace(window, index) => {
+            let _ = wm.move_window_to_workspace(window, index);
+        }
+        Call::AddWindowToWorkspace(ref description, index, minimised) => {
+            let _ = wm.add_window_to_workspace(description.clone(), index, minimised);
+        }
+        Call::SwitchWorkspace(index) => {
+            let _ = wm.switch_workspace(index);
+        }
+        // Queries don't change the window manager
+        Call::GetWindows |
+        Call::GetWindowLayout |
+        Call::GetWindowInfo(_) |
+        Call::GetScreen |
+        Call::GetFocusedWindow |
+        Call::IsManaged(_) |
+        Call::GetMasterWindow |
+        Call::GetFloatingWindows |
+        Call::GetMinimisedWindows |
+        Call::GetFullscreenWindow |
+        Call::GetGap |
+        Call::GetInnerGap |
+        Call::GetOuterGaps |
+        Call::GetSmartGaps |
+        Call::GetMinWindowSize |
+        Call::GetLayout |
+        Call::GetStruts |
+        Call::GetDocks |
+        Call::GetCurrentWorkspaceIndex |
+        Call::GetWorkspace(_) |
+        Call::GetWorkspaceMut(_) => (),
+    }
+
+    Ok(())
+}
+
+/// Replay the trace against a fresh window manager and return it.
+///
+/// The layout is compared with the recorded one after every entry, the first difference is returned as a
+/// `TraceError::Diverged`.
+pub fn replay<W, WM>(trace: &[TraceEntry]) -> Result<WM, TraceError>
+    where W: WindowManager,
+          WM: CommandSupport<W> + StrutSupport + DescriptionSupport
+{
+    let mut wm = match trace.first() {
+        Some(&TraceEntry { call: Call::New(screen), .. }) => WM::new(screen),
+        _ => {
+            return Err(TraceError::InvalidTrace(String::from("The trace doesn't start with the creation of the \
+                                                              window manager")))
+        }
+    };
+
+    for (step, entry) in trace.iter().enumerate() {
+        if step > 0 {
+            try!(execute(&mut wm, &entry.call));
+        }
+
+        let layout = wm.get_window_layout();
+        if layout != entry.layout {
+            return Err(TraceError::Diverged(Divergence {
+                step: step,
+                call: entry.call.clone(),
+                expected: entry.layout.clone(),
+                actual: layout,
+            }));
+        }
+    }
+
+    Ok(wm)
+}
+
+#[cfg(test)]
+#[allow(unused_mut)]
+#[allow(unused_variables)]
+mod tests {
+    pub use super::*;
+
+    pub use std::env;
+    pub use std::fs;
+    pub use std::io;
+    pub use std::sync::atomic::{AtomicUsize, ATOMIC_USIZE_INIT, Ordering};
+
+    pub use cplwm_api::wm::*;
+    pub use cplwm_api::types::*;
+    pub use cplwm_api::types::PrevOrNext::*;
+    pub use g_multiple_workspaces::WMName as WorkspaceWM;
+
+    // Import expectest names
+    pub use expectest::prelude::*;
+
+    conformance_tests!(WMName: window_manager, tiling, floating, minimising, fullscreen, gap, workspaces);
+
+    /// Every test uses its own trace file
+    static TRACE_COUNTER: AtomicUsize = ATOMIC_USIZE_INIT;
+
+    /// A writer that always fails
+    pub struct BrokenWriter;
+
+    impl Write for BrokenWriter {
+        fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+            Err(io::Error::new(io::ErrorKind::Other, "broken"))
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Return the calls of the trace
+    pub fn calls(trace: &[TraceEntry]) -> Vec<Call> {
+        trace.iter().map(|entry| entry.call.clone()).collect()
+    }
+
+    describe! recording {
+        before_each {
+            let screen: Screen = Screen {
+                width: 800,
+                height: 600,
+            };
+
+            let some_geom: Geometry = Geometry {
+                x: 10,
+                y: 10,
+                width: 100,
+                height: 100,
+            };
+
+            let trace_number = TRACE_COUNTER.fetch_add(1, Ordering::SeqCst);
+            let path = env::temp_dir().join(format!("cplwm-trace-test-{}.trace", trace_number));
+
+            let mut wm = WMName::create(screen, &path).unwrap();
+            wm.add_window(WindowWithInfo::new_tiled(1, some_geom)).unwrap();
+            wm.add_window(WindowWithInfo::new_tiled(2, some_geom)).unwrap();
+            wm.add_window(WindowWithInfo::new_float(3, some_geom)).unwrap();
+            wm.toggle_fullscreen(2).unwrap();
+            wm.switch_workspace(1).unwrap();
+            wm.add_window(WindowWithInfo::new_tiled(4, some_geom)).unwrap();
+            wm.switch_workspace(0).unwrap();
+        }
+
+        after_each {
+            let _ = fs::remove_file(&path);
+        }
+
+        it "should record every call with its arguments" {
+            wm.get_windows();
+            wm.cycle_focus(Next);
+            drop(wm);
+
+            let trace = load_trace(&path).unwrap();
+            expect!(calls(&trace)).to(be_equal_to(vec![Call::New(screen),
+                                                       Call::AddWindow(WindowWithInfo::new_tiled(1, some_geom)),
+                                                       Call::AddWindow(WindowWithInfo::new_tiled(2, some_geom)),
+                                                       Call::AddWindow(WindowWithInfo::new_float(3, some_geom)),
+                                                       Call::ToggleFullscreen(2),
+                                                       Call::SwitchWorkspace(1),
+                                                       Call::AddWindow(WindowWithInfo::new_tiled(4, some_geom)),
+                                                       Call::SwitchWorkspace(0),
+                                                       Call::GetWindows,
+                                                       Call::CycleFocus(Next)]));
+        }
+
+        it "should record the results and the layouts" {
+            let windows = wm.get_windows();
+            let layout = wm.get_window_layout();
+            expect!(wm.toggle_floating(5).is_err()).to(be_true());
+            drop(wm);
+
+            let trace = load_trace(&path).unwrap();
+            expect!(trace[8].outcome.clone()).to(be_equal_to(Outcome::Windows(windows)));
+            expect!(trace[9].outcome.clone()).to(be_equal_to(Outcome::Layout(layout.clone())));
+            expect!(trace[9].layout.clone()).to(be_equal_to(layout.clone()));
+            match trace[10].outcome {
+                Outcome::Failed(ref message) => expect!(message.contains('5')).to(be_true()),
+                ref outcome => panic!("Expected a failure, got {:?}", outcome),
+            }
+            expect!(trace[10].layout.clone()).to(be_equal_to(layout));
+        }
+
+        it "should not record when created with new, decoded or cloned" {
+            expect!(wm.is_recording()).to(be_true());
+            expect!(wm.clone().is_recording()).to(be_false());
+            let decoded: WMName = json::decode(&json::encode(&wm).unwrap()).unwrap();
+            expect!(decoded.is_recording()).to(be_false());
+            expect!(WMName::new(screen).is_recording()).to(be_false());
+        }
+
+        it "should stop recording when writing fails" {
+            let mut broken = WMName::with_writer(screen, Box::new(BrokenWriter));
+            expect!(broken.is_recording()).to(be_false());
+            broken.add_window(WindowWithInfo::new_tiled(1, some_geom)).unwrap();
+            expect!(broken.get_windows()).to(be_equal_to(vec![1]));
+        }
+
+        it "should stop recording on request" {
+            expect!(wm.stop_recording().is_some()).to(be_true());
+            expect!(wm.is_recording()).to(be_false());
+            wm.remove_window(1).unwrap();
+
+            expect!(load_trace(&path).unwrap().len()).to(be_equal_to(8));
+        }
+    }
+
+    describe! replaying {
+        before_each {
+            let screen: Screen = Screen {
+                width: 800,
+                height: 600,
+            };
+
+            let some_geom: Geometry = Geometry {
+                x: 10,
+                y: 10,
+                width: 100,
+                height: 100,
+            };
+
+            let trace_number = TRACE_COUNTER.fetch_add(1, Ordering::SeqCst);
+            let path = env::temp_dir().join(format!("cplwm-trace-test-{}.trace", trace_number));
+
+            let mut wm = WMName::create(screen, &path).unwrap();
+            wm.set_gap(5);
+            wm.add_window(WindowWithInfo::new_tiled(1, some_geom)).unwrap();
+            wm.add_window(WindowWithInfo::new_tiled(2, some_geom)).unwrap();
+            wm.add_window(WindowWithInfo::new_float(3, some_geom)).unwrap();
+            wm.swap_with_master(2).unwrap();
+            wm.toggle_minimised(1).unwrap();
+            wm.move_window_to_workspace(3, 2).unwrap();
+            wm.focus_window(Some(7)).unwrap_err();
+            wm.get_focused_window();
+            wm.resize_screen(Screen { width: 1024, height: 768 });
+        }
+
+        after_each {
+            let _ = fs::remove_file(&path);
+        }
+
+        it "should reproduce the recorded window manager" {
+            let trace = load_trace(&path).unwrap();
+            let replayed: WorkspaceWM = replay(&trace).unwrap();
+
+            expect!(replayed.get_window_layout()).to(be_equal_to(wm.get_window_layout()));
+            expect!(replayed.get_minimised_windows()).to(be_equal_to(wm.get_minimised_windows()));
+            expect!(replayed.get_workspace(2).unwrap().get_windows()).to(be_equal_to(vec![3]));
+        }
+
+        it "should report the first divergence" {
+            let mut trace = load_trace(&path).unwrap();
+            let mut wrong_layout = trace[3].layout.clone();
+            wrong_layout.focused_window = None;
+            trace[3].layout = wrong_layout.clone();
+            trace[5].layout = WindowLayout::new();
+
+            match replay::<_, WorkspaceWM>(&trace) {
+                Err(TraceError::Diverged(divergence)) => {
+                    expect!(divergence.step).to(be_equal_to(3));
+                    expect!(divergence.call).to(be_equal_to(Call::AddWindow(WindowWithInfo::new_tiled(2, some_geom))));
+                    expect!(divergence.expected).to(be_equal_to(wrong_layout));
+                    expect!(divergence.actual.focused_window).to(be_equal_to(Some(2)));
+                }
+                result => panic!("Expected a divergence, got {:?}", result),
+            }
+        }
+
+        it "should report changes made through a workspace" {
+            wm.get_workspace_mut(0).unwrap().remove_window(2).unwrap();
+            wm.get_windows();
+
+            let trace = load_trace(&path).unwrap();
+            match replay::<_, WorkspaceWM>(&trace) {
+                Err(TraceError::Diverged(divergence)) => expect!(divergence.call).to(be_equal_to(Call::GetWindows)),
+                result => panic!("Expected a divergence, got {:?}", result),
+            }
+        }
+
+        it "should lose the changes made through a workspace at the end of the trace" {
+            wm.get_workspace_mut(0).unwrap().set_gap(20);
+
+            let trace = load_trace(&path).unwrap();
+            expect!(trace.last().unwrap().call.clone()).to(be_equal_to(Call::GetWorkspaceMut(0)));
+
+            let replayed: WorkspaceWM = replay(&trace).unwrap();
+            expect!(wm.get_gap()).to(be_equal_to(20));
+            expect!(replayed.get_gap()).to(be_equal_to(5));
+            expect!(replayed.get_window_layout() == wm.get_window_layout()).to(be_false());
+        }
+
+        it "should only replay a trace that starts with the creation of the window manager" {
+            let trace = load_trace(&path).unwrap();
+
+            match replay::<_, WorkspaceWM>(&trace[1..]) {
+                Err(TraceError::InvalidTrace(_)) => (),
+                result => panic!("Expected an invalid trace, got {:?}", result),
+            }
+
+            let mut twice = trace.clone();
+            twice.push(trace[0].clone());
+            match replay::<_, WorkspaceWM>(&twice) {
+                Err(TraceError::InvalidTrace(_)) => (),
+                result => panic!("Expected an invalid trace, got {:?}", result),
+            }
+        }
+
+        it "should return an error for an invalid trace file" {
+            match read_trace(&b"{}"[..]) {
+                Err(TraceError::DecodeError(_)) => (),
+                result => panic!("Expected a decode error, got {:?}", result),
+            }
+
+            match load_trace(env::temp_dir().join("cplwm-trace-test-missing.trace")) {
+                Err(TraceError::Io(_)) => (),
+                result => panic!("Expected an IO error, got {:?}", result),
+            }
+        }
+    }
+}
@@ -0,0 +1,135 @@
+//! This module provides extended window metadata (class, title, role, window type, ...)
+//!
+//! A `WindowWithInfo` doesn't say what kind of window it is. A `WindowDescription` adds the information
+//! a window manager needs to treat dialogs, docks, splash screens, ... differently from normal windows.
+use std::os::raw::c_uint;
+
+use cplwm_api::types::{FloatOrTile, Window, WindowWithInfo};
+use cplwm_api::wm::WindowManager;
+
+use struts::Struts;
+
+/// The type of a window (see `_NET_WM_WINDOW_TYPE`)
+#[derive(Copy, Clone, RustcDecodable, RustcEncodable, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "with-serde", derive(Serialize, Deserialize))]
+pub enum WindowType {
+    /// A normal top-level window
+    Normal,
+    /// A dialog window
+    Dialog,
+    /// A dock or panel (e.g. a status bar), these reserve edges of the screen and are not managed
+    Dock,
+    /// A splash screen shown while an application is starting
+    Splash,
+    /// A small persistent utility window (e.g. a palette)
+    Utility,
+    /// A torn off toolbar
+    Toolbar,
+    /// A torn off menu
+    Menu,
+}
+
+/// The size hints of a window (see `WM_NORMAL_HINTS`)
+#[derive(Copy, Clone, RustcDecodable, RustcEncodable, Debug, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "with-serde", derive(Serialize, Deserialize))]
+pub struct SizeHints {
+    /// The minimal width of the window
+    pub min_width: Option<c_uint>,
+    /// The minimal height of the window
+    pub min_height: Option<c_uint>,
+    /// The maximal width of the window
+    pub max_width: Option<c_uint>,
+    /// The maximal height of the window
+    pub max_height: Option<c_uint>,
+}
+
+impl SizeHints {
+    /// Returns true if the window can't be resized (the minimal and maximal sizes are equal)
+    pub fn is_fixed_size(&self) -> bool {
+        self.min_width.is_some() && self.min_width == self.max_width && self.min_height.is_some() &&
+        self.min_height == self.max_height
+    }
+}
+
+/// A window with all the information that is known about it
+#[derive(Clone, RustcDecodable, RustcEncodable, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "with-serde", derive(Serialize, Deserialize))]
+pub struct WindowDescription {
+    /// The window, its requested geometry, whether it floats and whether it's fullscreen
+    pub info: WindowWithInfo,
+    /// The class of the window (the class part of `WM_CLASS`)
+    pub class: Option<String>,
+    /// The title of the window
+    pub title: Option<String>,
+    /// The role of the window (`WM_WINDOW_ROLE`)
+    pub role: Option<String>,
+    /// The type of the window
+    pub window_type: WindowType,
+    /// The window this window is transient for (e.g. the parent of a dialog)
+    pub transient_for: Option<Window>,
+    /// The size hints of the window
+    pub size_hints: SizeHints,
+    /// The edges of the screen reserved by this window (only used for docks)
+    pub struts: Struts,
+    /// Let the window float or tile whatever its type, e.g. when a window rule decides it
+    pub float_or_tile: Option<FloatOrTile>,
+}
+
+impl WindowDescription {
+    /// Create a description of a normal window without extra information
+    pub fn new(info: WindowWithInfo) -> WindowDescription {
+        WindowDescription {
+            info: info,
+            class: None,
+            title: None,
+            role: None,
+            window_type: WindowType::Normal,
+            transient_for: None,
+            size_hints: SizeHints::default(),
+            struts: Struts::new(),
+            float_or_tile: None,
+        }
+    }
+
+    /// Create a description of a dock window that reserves the given edges of the screen
+    pub fn new_dock(info: WindowWithInfo, struts: Struts) -> WindowDescription {
+        WindowDescription {
+            window_type: WindowType::Dock,
+            struts: struts,
+            ..WindowDescription::new(info)
+        }
+    }
+
+    /// Returns true if the window is a dock
+    pub fn is_dock(&self) -> bool {
+        self.window_type == WindowType::Dock
+    }
+
+    /// Returns true if the window should float: dialogs, splash screens, utility windows, toolbars, menus,
+    /// transient windows and windows that can't be resized. Otherwise the float_or_tile of the info is used.
+    ///
+    /// The `float_or_tile` of the description overrules all of these.
+    pub fn should_float(&self) -> bool {
+        if let Some(float_or_tile) = self.float_or_tile {
+            return float_or_tile == FloatOrTile::Float;
+        }
+
+        match self.window_type {
+            WindowType::Dialog | WindowType::Splash | WindowType::Utility | WindowType::Toolbar | WindowType::Menu => true,
+            WindowType::Normal | WindowType::Dock => {
+                self.info.float_or_tile == FloatOrTile::Float || self.transient_for.is_some() ||
+                self.size_hints.is_fixed_size()
+            }
+        }
+    }
+}
+
+/// A window manager that uses the description of a window to decide how to manage it
+pub trait DescriptionSupport: WindowManager {
+    /// Add a window with the given description.
+    ///
+    /// Docks are not managed, they reserve edges of the screen. Removing a dock window with
+    /// `remove_window` releases these edges again. Other windows are added like with `add_window`,
+    /// but the window manager may e.g. let the window float depending on its description.
+    fn add_window_with_description(&mut self, description: WindowDescription) -> Result<(), Self::Error>;
+}
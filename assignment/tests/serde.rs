@@ -0,0 +1,102 @@
+//! Tests of the serde serialization of the window managers (enable the `with-serde` feature to run them)
+//!
+//! These tests live in their own crate: `serde_json` adds `PartialEq` implementations that would make the type
+//! inference of the other tests ambiguous.
+
+#![cfg(feature = "with-serde")]
+#![feature(plugin)]
+#![plugin(stainless)]
+
+extern crate cplwm_api;
+extern crate cplwm_assignment;
+extern crate rustc_serialize;
+extern crate serde_json;
+#[macro_use(expect)]
+extern crate expectest;
+
+pub use rustc_serialize::json;
+pub use rustc_serialize::json::Json;
+
+pub use cplwm_api::wm::*;
+pub use cplwm_api::types::*;
+pub use cplwm_assignment::g_multiple_workspaces::WMName;
+pub use cplwm_assignment::layouts::*;
+
+// Import expectest names
+pub use expectest::prelude::*;
+
+describe! serde {
+    before_each {
+        let screen: Screen = Screen {
+            width: 800,
+            height: 600,
+        };
+
+        let some_geom: Geometry = Geometry {
+            x: 10,
+            y: 10,
+            width: 100,
+            height: 100,
+        };
+
+        let floating_geom: Geometry = Geometry {
+            x: 20,
+            y: 40,
+            width: 200,
+            height: 20,
+        };
+
+        // The state in the golden session files
+        let mut wm = WMName::new(screen);
+        for index in 0..MAX_WORKSPACE_INDEX + 1 {
+            wm.get_workspace_mut(index).unwrap().set_gap(10);
+        }
+        wm.add_window(WindowWithInfo::new_tiled(1, some_geom)).unwrap();
+        wm.add_window(WindowWithInfo::new_float(2, floating_geom)).unwrap();
+        wm.add_window(WindowWithInfo::new_tiled(3, some_geom)).unwrap();
+        wm.toggle_minimised(3).unwrap();
+        wm.switch_workspace(1).unwrap();
+        wm.add_window(WindowWithInfo::new_tiled(4, some_geom)).unwrap();
+        wm.toggle_fullscreen(4).unwrap();
+        wm.switch_workspace(0).unwrap();
+    }
+
+    it "should serialize the same JSON as rustc_serialize" {
+        let serialized = Json::from_str(&serde_json::to_string(&wm).unwrap()).unwrap();
+        let encoded = Json::from_str(&json::encode(&wm).unwrap()).unwrap();
+        expect!(serialized).to(be_equal_to(encoded));
+    }
+
+    it "should deserialize the state of the golden session file of the current version" {
+        let golden = include_str!("golden/session_v4.json");
+        let envelope: serde_json::Value = serde_json::from_str(golden).unwrap();
+        let mut loaded: WMName = serde_json::from_value(envelope["state"].clone()).unwrap();
+
+        expect!(loaded.get_windows()).to(be_equal_to(wm.get_windows()));
+        expect!(loaded.get_window_layout()).to(be_equal_to(wm.get_window_layout()));
+        expect!(loaded.get_floating_windows()).to(be_equal_to(vec![2 as Window]));
+        expect!(loaded.get_minimised_windows()).to(be_equal_to(vec![3 as Window]));
+        expect!(loaded.get_gap()).to(be_equal_to(10 as GapSize));
+        expect!(loaded.get_layout()).to(be_equal_to(Layout::Simple));
+
+        loaded.switch_workspace(1).unwrap();
+        expect!(loaded.get_fullscreen_window()).to(be_equal_to(Some(4 as Window)));
+    }
+
+    it "should decode the serialized state with rustc_serialize" {
+        let serialized = serde_json::to_string(&wm).unwrap();
+        let decoded: WMName = json::decode(&serialized).unwrap();
+
+        let decoded = Json::from_str(&json::encode(&decoded).unwrap()).unwrap();
+        expect!(decoded).to(be_equal_to(Json::from_str(&json::encode(&wm).unwrap()).unwrap()));
+    }
+
+    it "should serialize the types of the API like rustc_serialize" {
+        let info = WindowWithInfo::new_float(5, some_geom);
+        expect!(serde_json::to_string(&info).unwrap()).to(be_equal_to(json::encode(&info).unwrap()));
+        expect!(serde_json::from_str::<WindowWithInfo>(&json::encode(&info).unwrap()).unwrap()).to(be_equal_to(info));
+
+        let layout = wm.get_window_layout();
+        expect!(serde_json::to_string(&layout).unwrap()).to(be_equal_to(json::encode(&layout).unwrap()));
+    }
+}